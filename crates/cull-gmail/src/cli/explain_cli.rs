@@ -0,0 +1,191 @@
+//! # Explain CLI Module
+//!
+//! This module provides the `explain` subcommand, which shows how the
+//! retention rules treat a single message.
+//!
+//! ## Overview
+//!
+//! The message's labels, sender and received date are fetched, and every rule
+//! is checked against them in turn with [`Rules::evaluate`], the same local
+//! evaluation used to test rules offline:
+//!
+//! - **labels**: the message must carry one of the rule's labels; wildcard
+//!   labels are expanded as they are for `rules run`
+//! - **protected senders**: messages from a protected sender are never acted on
+//! - **cutoff**: the message must have been received before the start of the
+//!   day that is today less the rule's retention period
+//!
+//! For each rule the cutoff date is shown with the reason it does or does not
+//! apply, and the action it would take (or has taken, for a message already in
+//! the trash).
+//!
+//! ## Usage
+//!
+//! ```bash
+//! # Why was (or wasn't) this message trashed?
+//! cull-gmail explain 18c2f3a4b5d6e7f8
+//! ```
+//!
+//! Message IDs are shown by `cull-gmail messages list` and appear in the URL
+//! of a message opened in Gmail.
+
+use chrono::{DateTime, Local};
+use clap::Parser;
+use cull_gmail::{
+    EolAction, Error, GmailClient, MessageList, Result, RuleDecision, RuleEvaluation, Rules,
+};
+
+/// Gmail system label of messages in the trash.
+const TRASH: &str = "TRASH";
+
+/// Show which retention rules apply to a message and why.
+#[derive(Debug, Parser)]
+pub struct ExplainCli {
+    /// Gmail ID of the message to explain
+    #[arg(value_name = "MESSAGE_ID")]
+    message_id: String,
+}
+
+impl ExplainCli {
+    pub async fn run(&self, client: &mut GmailClient, rules: Rules) -> Result<()> {
+        let unknown = client
+            .add_message_ids(std::slice::from_ref(&self.message_id))
+            .await?;
+        let Some(message) = client.messages().first().filter(|_| unknown.is_empty()) else {
+            return Err(Error::NotFound(format!(
+                "message `{}` (it may have been permanently deleted)",
+                self.message_id
+            )));
+        };
+        let message = message.clone();
+
+        let labels = message
+            .label_ids()
+            .iter()
+            .map(|id| client.get_label_name(id).unwrap_or_else(|| id.clone()))
+            .collect::<Vec<_>>();
+
+        println!("Message {}: {}", message.id(), message.subject());
+        println!(
+            "  From: {}",
+            message.sender_address().as_deref().unwrap_or("(unknown)")
+        );
+        println!(
+            "  Received: {}",
+            message
+                .received()
+                .map_or("(unknown)".to_string(), |date| date
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string())
+        );
+        println!("  Labels: {}", labels.join(", "));
+        println!();
+
+        let in_trash = labels.iter().any(|label| label == TRASH);
+        let evaluations = rules.evaluate(&message, client.label_map(), Local::now());
+
+        for evaluation in &evaluations {
+            let Some(rule) = rules.get_rule(evaluation.rule_id) else {
+                continue;
+            };
+            println!("{}", rule.describe());
+            println!(
+                "  {}",
+                describe_evaluation(evaluation, rule.action(), in_trash)
+            );
+        }
+
+        if evaluations.is_empty() {
+            println!("No rules are configured");
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes why a rule does or does not apply and the action that follows.
+fn describe_evaluation(
+    evaluation: &RuleEvaluation,
+    action: Option<EolAction>,
+    in_trash: bool,
+) -> String {
+    let on = evaluation
+        .label
+        .as_ref()
+        .map_or(String::new(), |label| format!("matches `{label}`, "));
+    let date = |cutoff: Option<DateTime<Local>>| {
+        cutoff.map_or(String::new(), |cutoff| {
+            cutoff.format("%Y-%m-%d").to_string()
+        })
+    };
+    let cutoff = date(evaluation.cutoff);
+
+    match &evaluation.decision {
+        RuleDecision::NoLabel => "no match: the message has none of the rule's labels".to_string(),
+        RuleDecision::InvalidRetention => format!("{on}but the retention period is invalid"),
+        RuleDecision::ProtectedSender(sender) => {
+            format!("{on}but `{sender}` is a protected sender")
+        }
+        RuleDecision::NotDue => {
+            format!("{on}cutoff {cutoff}: received after the cutoff, not due yet")
+        }
+        RuleDecision::NoDate => format!("{on}cutoff {cutoff}: the received date is unknown"),
+        RuleDecision::Due => {
+            let outcome = match action {
+                Some(EolAction::Trash) if in_trash => "already moved to trash".to_string(),
+                Some(action) => format!("would {action} the message"),
+                None => "no action is set".to_string(),
+            };
+            format!("{on}cutoff {cutoff}: received before the cutoff, {outcome}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn evaluation(decision: RuleDecision) -> RuleEvaluation {
+        RuleEvaluation {
+            rule_id: 1,
+            label: Some("news".to_string()),
+            cutoff: Some(Local.with_ymd_and_hms(2024, 9, 15, 0, 0, 0).unwrap()),
+            decision,
+        }
+    }
+
+    #[test]
+    fn test_describe_evaluation() {
+        let due = evaluation(RuleDecision::Due);
+
+        assert_eq!(
+            describe_evaluation(&due, Some(EolAction::Trash), false),
+            "matches `news`, cutoff 2024-09-15: received before the cutoff, would trash the message"
+        );
+        assert_eq!(
+            describe_evaluation(&due, Some(EolAction::Trash), true),
+            "matches `news`, cutoff 2024-09-15: received before the cutoff, already moved to trash"
+        );
+        assert_eq!(
+            describe_evaluation(
+                &evaluation(RuleDecision::NotDue),
+                Some(EolAction::Trash),
+                false
+            ),
+            "matches `news`, cutoff 2024-09-15: received after the cutoff, not due yet"
+        );
+        assert_eq!(
+            describe_evaluation(
+                &evaluation(RuleDecision::ProtectedSender(
+                    "boss@example.com".to_string()
+                )),
+                Some(EolAction::Delete),
+                false
+            ),
+            "matches `news`, but `boss@example.com` is a protected sender"
+        );
+    }
+}
@@ -68,7 +68,7 @@ use indicatif::{ProgressBar, ProgressStyle};
 use std::path::{Path, PathBuf};
 use std::{env, fs};
 
-use cull_gmail::{ClientConfig, Error, GmailClient, Result};
+use cull_gmail::{ClientConfig, Error, GmailClient, GmailScope, Result, Rules};
 use lazy_regex::{Lazy, Regex, lazy_regex};
 
 /// Parse configuration root path with h:, c:, r: prefixes.
@@ -336,10 +336,6 @@ config_root = "h:.cull-gmail"
 # Rules configuration file
 rules = "rules.toml"
 
-# Default execution mode (false = dry-run, true = execute)
-# Set to false for safety - you can override with --execute flag
-execute = false
-
 # Environment variable name for token cache (for ephemeral environments)
 token_cache_env = "CULL_GMAIL_TOKEN_CACHE"
 "#;
@@ -359,10 +355,6 @@ config_root = "h:.cull-gmail"
 # Rules configuration file (supports h:, c:, r: prefixes)
 rules = "{rules_path}"
 
-# Default execution mode (false = dry-run, true = execute)
-# Set to false for safety - you can override with --execute flag
-execute = false
-
 # Environment variable name for token cache (for ephemeral environments)
 token_cache_env = "CULL_GMAIL_TOKEN_CACHE"
 "#
@@ -386,10 +378,6 @@ config_root = "h:.cull-gmail"
 # The rules file is expected to be provided externally (e.g., in ephemeral environments)
 rules = "{rules_path}"
 
-# Default execution mode (false = dry-run, true = execute)
-# Set to false for safety - you can override with --execute flag
-execute = false
-
 # Environment variable name for token cache (for ephemeral environments)
 token_cache_env = "CULL_GMAIL_TOKEN_CACHE"
 "#
@@ -423,7 +411,7 @@ token_cache_env = "CULL_GMAIL_TOKEN_CACHE"
 # action = "Trash"
 #
 # Uncomment and modify the examples above to create your own rules.
-# Run 'cull-gmail rules run --dry-run' to test rules before execution.
+# Run 'cull-gmail rules run' to preview rules before planning changes.
 "#;
 
     fn credential_filename() -> &'static str {
@@ -553,10 +541,16 @@ impl InitCli {
             .map_err(|e| Error::FileIo(format!("Cannot read credential file: {e}")))?;
 
         // Try to parse as ConsoleApplicationSecret to validate format
-        serde_json::from_str::<ConsoleApplicationSecret>(&content).map_err(|e| {
+        let console = serde_json::from_str::<ConsoleApplicationSecret>(&content).map_err(|e| {
             Error::SerializationError(format!("Invalid credential file format: {e}"))
         })?;
 
+        if console.installed.is_none() && console.web.is_none() {
+            return Err(Error::CredentialFileMissingClient(
+                path.display().to_string(),
+            ));
+        }
+
         log::info!("Credential file validated: {}", path.display());
         Ok(())
     }
@@ -1004,9 +998,20 @@ impl InitCli {
         // Parse config root and build ClientConfig
         let config_path = parse_config_root(config_root);
 
+        // Authorise the access the configured rules will need so that a later
+        // `rules apply` can reuse the cached token
+        let rules_path = self
+            .get_rules_directory(&config_path)
+            .join(InitDefaults::rules_filename());
+        let scope = Rules::load_from(Some(&rules_path))
+            .map(|rules| GmailScope::for_rules(&rules))
+            .unwrap_or(GmailScope::Modify);
+        log::info!("Requesting `{scope}` access for the configured rules");
+
         let client_config = ClientConfig::builder()
-            .with_credential_file(InitDefaults::credential_filename())
             .with_config_path(config_path.to_string_lossy().as_ref())
+            .with_credential_file(InitDefaults::credential_filename())?
+            .with_scope(scope)
             .build();
 
         // Initialize Gmail client which will trigger OAuth flow if needed
@@ -1051,14 +1056,14 @@ impl InitCli {
             println!("   1. Test Gmail connection: cull-gmail labels");
             if self.skip_rules {
                 println!("   2. Ensure rules.toml is provided at the configured path");
-                println!("   3. Review rules: cull-gmail rules run --dry-run");
-                println!("   4. Run rules safely: cull-gmail rules run --dry-run");
-                println!("   5. Execute for real: cull-gmail rules run --execute");
+                println!("   3. Preview rules: cull-gmail rules run");
+                println!("   4. Plan the changes: cull-gmail rules plan --out plan.json");
+                println!("   5. Apply the plan: cull-gmail rules apply plan.json");
             } else {
-                println!("   2. Review rules template: cull-gmail rules run --dry-run");
+                println!("   2. Review rules template: cull-gmail rules run");
                 println!("   3. Customize rules.toml as needed");
-                println!("   4. Run rules safely: cull-gmail rules run --dry-run");
-                println!("   5. Execute for real: cull-gmail rules run --execute");
+                println!("   4. Plan the changes: cull-gmail rules plan --out plan.json");
+                println!("   5. Apply the plan: cull-gmail rules apply plan.json");
             }
         } else {
             println!("   1. Add your OAuth2 credential file to:");
@@ -1073,8 +1078,8 @@ impl InitCli {
         println!();
 
         println!("💡 Tips:");
-        println!("   - All operations use dry-run mode by default for safety");
-        println!("   - Use --execute flag or set execute=true in config for real actions");
+        println!("   - Rules only preview by default for safety");
+        println!("   - Review a plan before applying it; stale plans are refused");
         println!("   - See 'cull-gmail --help' for all available commands");
     }
 }
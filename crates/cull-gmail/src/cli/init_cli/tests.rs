@@ -84,7 +84,7 @@ mod unit_tests {
         let config_content = InitDefaults::CONFIG_FILE_CONTENT;
         assert!(config_content.contains("credential_file = \"credential.json\""));
         assert!(config_content.contains("config_root = \"h:.cull-gmail\""));
-        assert!(config_content.contains("execute = false"));
+        assert!(!config_content.contains("execute"));
 
         // Test that rules content is a valid template
         let rules_content = InitDefaults::RULES_FILE_CONTENT;
@@ -1,125 +1,378 @@
 //! # Gmail Labels CLI Module
 //!
-//! This module provides command-line interface functionality for inspecting and displaying
-//! Gmail labels. It enables users to list all available labels in their Gmail account
-//! along with their internal Gmail IDs and display names.
+//! This module provides command-line interface functionality for inspecting and managing
+//! Gmail labels. It lists the labels in the account with their type and message counts,
+//! and creates, renames, deletes and colours user labels.
 //!
 //! ## Purpose
 //!
 //! The labels command is essential for:
 //! - Understanding the structure of Gmail labels in an account
 //! - Finding correct label names for use in message queries
-//! - Inspecting label IDs for advanced Gmail API usage
-//! - Verifying label availability before creating rules
+//! - Seeing which labels hold the most (unread) messages
+//! - Keeping retention labels tidy without leaving the terminal
 //!
 //! ## Usage
 //!
 //! ```bash
-//! cull-gmail labels
+//! cull-gmail labels [list]
+//! cull-gmail labels create "retention/1-years"
+//! cull-gmail labels rename newsletters "Newsletters/Weekly"
+//! cull-gmail labels delete "Old Project"
+//! cull-gmail labels set-color receipts --background "#16a766" --text "#ffffff"
+//! cull-gmail labels sync
 //! ```
 //!
+//! Running `labels` without a subcommand lists the labels.
+//!
 //! ## Output Format
 //!
-//! The command displays labels in a human-readable format showing:
-//! - **Label Name**: User-visible label name
-//! - **Label ID**: Internal Gmail identifier
+//! `list` writes a table to stdout (or JSON with `--format json`) showing:
+//! - **Name**: User-visible label name
+//! - **Type**: `system` for Gmail's own labels, `user` for the rest
+//! - **Messages**: Number of messages with the label
+//! - **Unread**: Number of unread messages with the label
 //!
 //! Example output:
 //! ```text
-//! INBOX: INBOX
-//! IMPORTANT: IMPORTANT  
-//! promotions: Label_1234567890
-//! newsletters: Label_0987654321
+//! NAME          TYPE    MESSAGES  UNREAD
+//! INBOX         system      1520      12
+//! newsletters   user         842     311
 //! ```
 //!
+//! ## Renaming and Rules
+//!
+//! When a label is renamed, rules in `rules.toml` that reference the old name
+//! are updated to the new name and the rules file is saved.
+//!
+//! ## Syncing Rule Labels
+//!
+//! `sync` creates every label referenced by the rules that does not exist in
+//! Gmail yet, such as the `retention/1-years` labels generated from retention
+//! periods, together with missing parent labels. `rules run` and
+//! `rules plan` report the labels that `sync` would create.
+//!
 //! ## Integration
 //!
 //! This module integrates with:
 //! - **GmailClient**: For Gmail API communication and authentication
-//! - **Main CLI**: As a subcommand in the primary CLI application
-//! - **Error handling**: Using the unified crate error types
+//! - **LabelManager**: For label listing and changes
+//! - **Rules**: To keep rule labels in step with renamed labels
 
-use clap::Parser;
-use cull_gmail::{Error, GmailClient};
+use std::{
+    io::{self, Write},
+    path::Path,
+};
 
-/// Command-line interface for Gmail label inspection and display.
-///
-/// This structure represents the `labels` subcommand, which provides functionality
-/// to list and inspect all Gmail labels available in the user's account. The command
-/// requires no additional arguments and displays comprehensive label information.
-///
-/// # Features
+use clap::{Parser, Subcommand, ValueEnum};
+use cull_gmail::{Error, GmailClient, GmailScope, LabelInfo, LabelManager, Result, Rules};
+
+use crate::{rules_cli, table::write_table};
+
+/// Output formats for the label list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LabelsFormat {
+    /// An aligned table for reading in a terminal
+    #[default]
+    Table,
+    /// A JSON array of labels
+    Json,
+}
+
+/// Label management subcommands.
+#[derive(Debug, Subcommand)]
+enum LabelsCmd {
+    /// List labels with their type and message counts
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = LabelsFormat::Table)]
+        format: LabelsFormat,
+    },
+    /// Create a label (use `/` to nest it under another label)
+    Create {
+        /// Name of the new label
+        name: String,
+    },
+    /// Rename a label and update rules that reference it
+    Rename {
+        /// Current label name
+        name: String,
+        /// New label name
+        new_name: String,
+    },
+    /// Delete a label (messages keep their other labels)
+    Delete {
+        /// Name of the label to delete
+        name: String,
+    },
+    /// Set the background and text colours of a label
+    SetColor {
+        /// Name of the label to colour
+        name: String,
+        /// Background colour from Gmail's palette, e.g. #16a766
+        #[arg(short, long, value_parser = parse_color)]
+        background: String,
+        /// Text colour from Gmail's palette, e.g. #ffffff
+        #[arg(short, long, value_parser = parse_color)]
+        text: String,
+    },
+    /// Create the labels used by rules that are missing from Gmail
+    Sync {
+        /// Show the labels that would be created without creating them
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+}
+
+/// Command-line interface for Gmail label inspection and management.
 ///
-/// - **Complete label listing**: Shows all labels including system and user-created labels
-/// - **ID mapping**: Displays both human-readable names and internal Gmail IDs
-/// - **Simple usage**: No configuration or arguments required
-/// - **Authentication handling**: Automatic OAuth2 authentication through GmailClient
+/// This structure represents the `labels` subcommand. Without a subcommand it
+/// lists the labels in the account, as `labels list` does.
 ///
 /// # Usage Context
 ///
 /// This command is typically used:
 /// 1. **Before creating queries**: To understand available labels for message filtering
 /// 2. **Before configuring rules**: To verify target labels exist
-/// 3. **For debugging**: To inspect label structure and IDs
-/// 4. **For exploration**: To understand Gmail organization structure
-///
-/// # Examples
-///
-/// ```rust,no_run
-/// use cull_gmail::cli::labels_cli::LabelsCli;
-/// use cull_gmail::GmailClient;
-///
-/// # async fn example(client: GmailClient) -> Result<(), cull_gmail::Error> {
-/// let labels_cli = LabelsCli {};
-/// labels_cli.run(client).await?;
-/// # Ok(())
-/// # }
-/// ```
+/// 3. **For housekeeping**: To rename, colour or remove retention labels
 #[derive(Debug, Parser)]
-pub struct LabelsCli {}
+pub struct LabelsCli {
+    #[command(subcommand)]
+    sub_command: Option<LabelsCmd>,
+}
 
 impl LabelsCli {
-    /// Executes the labels command to display Gmail label information.
-    ///
-    /// This method coordinates the label inspection workflow by utilizing the
-    /// Gmail client to retrieve and display all available labels in the user's account.
-    /// The output includes both system labels (like INBOX, SENT) and user-created labels.
+    /// Executes the labels command.
     ///
     /// # Arguments
     ///
     /// * `client` - Authenticated Gmail client for API communication
-    ///
-    /// # Returns
-    ///
-    /// Returns `Result<(), Error>` indicating success or failure of the operation.
-    ///
-    /// # Operation Details
-    ///
-    /// The function performs the following steps:
-    /// 1. **Label Retrieval**: Fetches all labels from the Gmail API
-    /// 2. **Format Processing**: Organizes labels for display
-    /// 3. **Display Output**: Shows labels with names and IDs
-    ///
-    /// # Output Format
-    ///
-    /// Labels are displayed in the format:
-    /// ```text
-    /// <Label Name>: <Label ID>
-    /// ```
+    /// * `rules_path` - Rules file to update when a label is renamed
     ///
     /// # Error Handling
     ///
     /// Possible errors include:
     /// - **Authentication failures**: OAuth2 token issues or expired credentials
-    /// - **API communication errors**: Network issues or Gmail API unavailability
-    /// - **Permission errors**: Insufficient OAuth2 scopes for label access
-    ///
-    /// # Side Effects
+    /// - **Unknown labels**: The named label does not exist
+    /// - **System labels**: Gmail's own labels cannot be renamed, deleted or coloured
+    /// - **API errors**: For example a name already in use or a colour outside the palette
+    pub async fn run(&self, client: &mut GmailClient, rules_path: Option<&Path>) -> Result<()> {
+        match &self.sub_command {
+            None => list(client, LabelsFormat::Table).await,
+            Some(LabelsCmd::List { format }) => list(client, *format).await,
+            Some(LabelsCmd::Create { name }) => {
+                let id = client.create_label(name).await?;
+                println!("Created label `{name}` ({id})");
+                Ok(())
+            }
+            Some(LabelsCmd::Rename { name, new_name }) => {
+                client.rename_label(name, new_name).await?;
+                println!("Renamed label `{name}` to `{new_name}`");
+                update_rules(rules_path, name, new_name)
+            }
+            Some(LabelsCmd::Delete { name }) => {
+                client.delete_label(name).await?;
+                println!("Deleted label `{name}`");
+                Ok(())
+            }
+            Some(LabelsCmd::SetColor {
+                name,
+                background,
+                text,
+            }) => {
+                client.set_label_color(name, background, text).await?;
+                println!("Set colour of label `{name}`");
+                Ok(())
+            }
+            Some(LabelsCmd::Sync { dry_run }) => {
+                let rules = rules_cli::get_rules_from(rules_path)?;
+                sync_rule_labels(client, &rules, !dry_run).await
+            }
+        }
+    }
+
+    /// Returns the OAuth2 scope needed for the selected subcommand.
     ///
-    /// This function produces output to stdout showing the label information.
-    /// No Gmail account modifications are performed.
-    pub async fn run(&self, client: GmailClient) -> Result<(), Error> {
-        client.show_label();
-        Ok(())
+    /// Listing and dry runs only read labels; every other subcommand changes them.
+    pub(crate) fn scope(&self) -> GmailScope {
+        match &self.sub_command {
+            None | Some(LabelsCmd::List { .. } | LabelsCmd::Sync { dry_run: true }) => {
+                GmailScope::ReadOnly
+            }
+            Some(_) => GmailScope::Modify,
+        }
+    }
+}
+
+/// Writes the labels in the account to stdout.
+async fn list(client: &GmailClient, format: LabelsFormat) -> Result<()> {
+    let labels = client.list_labels().await?;
+
+    let mut out = io::stdout().lock();
+    match format {
+        LabelsFormat::Table => write_labels(&mut out, &labels)?,
+        LabelsFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &labels)
+                .map_err(|e| Error::SerializationError(e.to_string()))?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the labels referenced by `rules` that are missing from Gmail.
+///
+/// When `create` is `false` the missing labels are only reported.
+pub(crate) async fn sync_rule_labels(
+    client: &mut GmailClient,
+    rules: &Rules,
+    create: bool,
+) -> Result<()> {
+    let labels = rules.labels();
+
+    if !create {
+        for label in client.missing_labels(&labels) {
+            println!("Would create label `{label}`");
+        }
+        return Ok(());
+    }
+
+    let created = client.create_missing_labels(&labels).await?;
+    if created.is_empty() {
+        log::info!("All labels used by the rules exist");
+    }
+    for label in created {
+        println!("Created label `{label}`");
+    }
+
+    Ok(())
+}
+
+/// Replaces a renamed label in the rules and saves them if any changed.
+fn update_rules(rules_path: Option<&Path>, name: &str, new_name: &str) -> Result<()> {
+    let mut rules = rules_cli::get_rules_from(rules_path)?;
+    let changed = rules.rename_label(name, new_name);
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    rules.save_to(rules_path)?;
+    let ids = changed
+        .iter()
+        .map(|id| format!("#{id}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    println!("Updated rule(s) {ids} to use `{new_name}`");
+
+    Ok(())
+}
+
+/// Writes the labels as an aligned table.
+fn write_labels<W: Write>(out: &mut W, labels: &[LabelInfo]) -> io::Result<()> {
+    let rows = labels
+        .iter()
+        .map(|l| {
+            vec![
+                l.name.clone(),
+                l.label_type.clone(),
+                l.messages_total.to_string(),
+                l.messages_unread.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let header = ["NAME", "TYPE", "MESSAGES", "UNREAD"].map(str::to_string);
+
+    write_table(out, &header, &rows, &[2, 3])
+}
+
+/// Validates a colour given as `#` followed by six hex digits.
+fn parse_color(value: &str) -> std::result::Result<String, String> {
+    let value = value.trim().to_lowercase();
+    let valid = value
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+    if valid {
+        Ok(value)
+    } else {
+        Err(format!(
+            "invalid colour `{value}` (expected # followed by six hex digits, e.g. #16a766)"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_labels() {
+        let labels = vec![
+            LabelInfo {
+                name: "INBOX".to_string(),
+                label_type: "system".to_string(),
+                messages_total: 1520,
+                messages_unread: 12,
+                ..Default::default()
+            },
+            LabelInfo {
+                name: "newsletters".to_string(),
+                label_type: "user".to_string(),
+                messages_total: 842,
+                messages_unread: 311,
+                ..Default::default()
+            },
+        ];
+
+        let mut out = Vec::new();
+        write_labels(&mut out, &labels).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "NAME         TYPE    MESSAGES  UNREAD\n\
+             INBOX        system      1520      12\n\
+             newsletters  user         842     311\n"
+        );
+    }
+
+    #[test]
+    fn test_update_rules_renames_rule_labels() {
+        use cull_gmail::{MessageAge, Retention};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("rules.toml");
+        let mut rules = Rules::new();
+        rules.add_rule(
+            Retention::new(MessageAge::Months(3), false),
+            Some("newsletters"),
+            false,
+        );
+        rules.save_to(Some(&path)).unwrap();
+
+        update_rules(Some(&path), "newsletters", "Newsletters/Weekly").unwrap();
+
+        let labels = Rules::load_from(Some(&path)).unwrap().labels();
+        assert!(labels.contains(&"Newsletters/Weekly".to_string()));
+        assert!(!labels.contains(&"newsletters".to_string()));
+    }
+
+    #[test]
+    fn test_update_rules_leaves_unrelated_rules_unsaved() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("rules.toml");
+        Rules::new().save_to(Some(&path)).unwrap();
+        let before = std::fs::read_to_string(&path).unwrap();
+
+        update_rules(Some(&path), "receipts", "Receipts").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#16A766"), Ok("#16a766".to_string()));
+        assert!(parse_color("16a766").is_err());
+        assert!(parse_color("#fff").is_err());
+        assert!(parse_color("#gggggg").is_err());
     }
 }
@@ -8,9 +8,10 @@
 //!
 //! The CLI is built around three main command categories:
 //!
-//! - **Labels**: List and inspect Gmail labels for message organization
+//! - **Labels**: List Gmail labels with message counts, and create, rename, delete or colour them
 //! - **Messages**: Query, filter, and perform batch operations on Gmail messages
 //! - **Rules**: Configure and execute automated message lifecycle management rules
+//! - **Stats**: Summarise mailbox storage to decide which rules to write
 //!
 //! ## Authentication
 //!
@@ -38,6 +39,7 @@
 //! 1. **`labels`**: List all available Gmail labels
 //! 2. **`messages`**: Query and operate on Gmail messages
 //! 3. **`rules`**: Configure and execute retention rules
+//! 4. **`stats`**: Show storage used by sender, domain, label, date and size
 //!
 //! ## Configuration File Format
 //!
@@ -52,14 +54,11 @@
 //!
 //! # Rules configuration file
 //! rules = "rules.toml"
-//!
-//! # Default execution mode (false = dry-run, true = execute)
-//! execute = false
 //! ```
 //!
 //! ## Safety Features
 //!
-//! - **Dry-run mode**: Default behaviour prevents accidental data loss
+//! - **Plan and apply**: Rules only change messages through a reviewed plan
 //! - **Comprehensive logging**: Detailed operation tracking with multiple verbosity levels
 //! - **Error handling**: Graceful error recovery with meaningful error messages
 //! - **Confirmation prompts**: For destructive operations
@@ -69,6 +68,7 @@
 //! ### List Gmail Labels
 //! ```bash
 //! cull-gmail labels
+//! cull-gmail labels rename newsletters "Newsletters/Weekly"
 //! ```
 //!
 //! ### Query Messages
@@ -80,13 +80,20 @@
 //! cull-gmail messages -Q "label:promotions older_than:1y" list
 //! ```
 //!
+//! ### Mailbox Statistics
+//! ```bash
+//! # Top 10 senders by storage used
+//! cull-gmail stats --by sender --top 10
+//! ```
+//!
 //! ### Execute Rules
 //! ```bash
-//! # Preview rule execution (dry-run)
+//! # Preview what the rules would do
 //! cull-gmail rules run
 //!
-//! # Execute rules for real
-//! cull-gmail rules run --execute
+//! # Record the messages each rule selects, then act on exactly those
+//! cull-gmail rules plan --out plan.json
+//! cull-gmail rules apply plan.json
 //! ```
 //!
 //! ## Error Handling
@@ -94,6 +101,11 @@
 //! The CLI returns the following exit codes:
 //! - **0**: Success
 //! - **101**: Error (check stderr and logs for details)
+//! - **102**: Gmail API rate limit or quota exceeded (retry later)
+//! - **103**: Authorisation expired or revoked (re-authenticate)
+//! - **104**: OAuth token lacks a required scope
+//! - **105**: Gmail resource not found
+//! - **106**: Gmail server error (retry later)
 //!
 //! ## Logging
 //!
@@ -111,20 +123,31 @@
 
 use clap::{Parser, Subcommand};
 
+mod explain_cli;
 mod init_cli;
 mod labels_cli;
 mod messages_cli;
+mod query_args;
+mod review;
 mod rules_cli;
+mod selection_args;
+mod stats_cli;
+mod table;
 mod token_cli;
 
 use config::Config;
-use cull_gmail::{ClientConfig, EolAction, GmailClient, MessageList, Result, RuleProcessor, Rules};
-use std::{env, error::Error as stdError};
+use cull_gmail::{
+    ClientConfig, EolAction, EolRule, Error, GmailClient, GmailScope, LabelManager, MessageList,
+    Plan, PlanEntry, Result, RuleProcessor, Rules,
+};
+use std::{collections::BTreeSet, env, error::Error as stdError};
 
+use explain_cli::ExplainCli;
 use init_cli::InitCli;
 use labels_cli::LabelsCli;
 use messages_cli::MessagesCli;
 use rules_cli::RulesCli;
+use stats_cli::StatsCli;
 use token_cli::{TokenCli, restore_tokens_from_string};
 
 use std::path::PathBuf;
@@ -142,9 +165,8 @@ use std::path::PathBuf;
 ///
 /// # Default behaviour
 ///
-/// When no subcommand is provided, the CLI executes the default rule processing
-/// workflow, loading rules from the configuration file and executing them
-/// according to the current execution mode (dry-run or live).
+/// When no subcommand is provided, the CLI previews the rules, loading them
+/// from the configuration file and showing the messages each would act on.
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
@@ -193,10 +215,11 @@ enum SubCmds {
     #[clap(name = "messages", display_order = 3, next_help_heading = "Labels")]
     Message(MessagesCli),
 
-    /// List and inspect available Gmail labels.
+    /// List and manage Gmail labels.
     ///
-    /// Displays all labels in your Gmail account with their internal IDs,
-    /// useful for understanding label structure before creating queries or rules.
+    /// Lists the labels in your Gmail account with their type and message
+    /// counts, and creates, renames, deletes or colours labels. Renaming a
+    /// label also updates the rules that use it.
     #[clap(name = "labels", display_order = 2, next_help_heading = "Rules")]
     Labels(LabelsCli),
 
@@ -207,6 +230,20 @@ enum SubCmds {
     #[clap(name = "rules", display_order = 2)]
     Rules(RulesCli),
 
+    /// Summarise mailbox storage by sender, domain, label, date and size.
+    ///
+    /// Aggregates the size estimates of the selected messages to show where
+    /// mailbox space goes, as ranked tables or JSON.
+    #[clap(name = "stats", display_order = 3)]
+    Stats(StatsCli),
+
+    /// Show which retention rules apply to a message and why.
+    ///
+    /// Checks the message's labels, sender and received date against every
+    /// rule, showing each rule's cutoff date and the action it would take.
+    #[clap(name = "explain", display_order = 3)]
+    Explain(ExplainCli),
+
     /// Export and import OAuth2 tokens for ephemeral environments.
     ///
     /// Supports token export to compressed strings and automatic import from
@@ -233,6 +270,7 @@ enum SubCmds {
 ///
 /// - **0**: Successful execution
 /// - **101**: Error occurred (details logged and printed to stderr)
+/// - **102-106**: Classified Gmail API failures, see [`exit_code`]
 ///
 /// # Error Reporting
 ///
@@ -258,11 +296,35 @@ async fn main() {
                 log::error!("{e}");
                 eprintln!("{e}");
             }
-            101
+            exit_code(&e)
         }
     });
 }
 
+/// Maps an error to the process exit code reported to the shell.
+///
+/// Gmail API failures that a scheduler can act on get distinct codes so that
+/// quota exhaustion can be retried while expired authorisation is escalated.
+///
+/// | Code | Error |
+/// |------|-------|
+/// | 102 | [`Error::RateLimited`] |
+/// | 103 | [`Error::AuthExpired`] |
+/// | 104 | [`Error::InsufficientScope`] |
+/// | 105 | [`Error::NotFound`] |
+/// | 106 | [`Error::ServerError`] |
+/// | 101 | Any other error |
+fn exit_code(error: &Error) -> i32 {
+    match error {
+        Error::RateLimited(_) => 102,
+        Error::AuthExpired(_) => 103,
+        Error::InsufficientScope(_) => 104,
+        Error::NotFound(_) => 105,
+        Error::ServerError(_, _) => 106,
+        _ => 101,
+    }
+}
+
 /// Main application logic dispatcher handling subcommand execution and default behaviour.
 ///
 /// This function orchestrates the core application workflow by:
@@ -280,9 +342,8 @@ async fn main() {
 ///
 /// # Default behaviour
 ///
-/// When no subcommand is specified, the function executes the default rule processing
-/// workflow, loading rules from configuration and executing them based on the
-/// current execution mode setting.
+/// When no subcommand is specified, the function previews the rules, showing the
+/// messages each would act on. Messages are only changed by `rules apply`.
 ///
 /// # Error Handling
 ///
@@ -298,28 +359,51 @@ async fn run(args: Cli) -> Result<()> {
         return init_cli.run().await;
     }
 
-    // Handle `rules validate` before loading config: it needs no Gmail credentials.
+    // Load the application config; credentials are only read when needed
+    let config = load_config()?;
+
+    // Get configured rules path
+    let rules_path = get_rules_path(&config)?;
+
+    // Commands that only work on the rules file need no Gmail client
     if let Some(SubCmds::Rules(ref rules_cli)) = args.sub_command
-        && let Some(result) = rules_cli.run_if_validate()
+        && let Some(result) = rules_cli.run_local(rules_path.as_deref())
     {
         return result;
     }
 
-    // For all other commands, load config normally
-    let (config, client_config) = get_config()?;
-
     // Check for token restoration before client initialization
-    restore_tokens_if_available(&config, &client_config)?;
+    let token_path = get_token_path(&config)?;
+    restore_tokens_if_available(&config, &token_path)?;
 
-    let mut client = GmailClient::new_with_config(client_config).await?;
+    // Token commands work on the cached token files, not through a client
+    if let Some(SubCmds::Token(ref token_cli)) = args.sub_command {
+        return token_cli.run(&token_path).await;
+    }
 
-    // Get configured rules path
-    let rules_path = get_rules_path(&config)?;
+    // Scheduled jobs relying on the removed `execute` setting must not pass silently
+    if args.sub_command.is_none() && config.get_bool("execute").unwrap_or(false) {
+        return Err(Error::ExecuteUnsupported);
+    }
+
+    // Message ID lists are read before authorising, so a bad list fails early
+    let message_ids = match args.sub_command {
+        Some(SubCmds::Message(ref messages_cli)) => messages_cli.read_ids()?,
+        _ => None,
+    };
+
+    let mut client_config = ClientConfig::new_from_configuration(config.clone())?;
+
+    // Request only the access the command needs
+    client_config.set_scope(get_scope(args.sub_command.as_ref())?);
+
+    let mut client = GmailClient::new_with_config(client_config).await?;
 
     let Some(sub_command) = args.sub_command else {
-        let rules = rules_cli::get_rules_from(rules_path.as_deref())?;
-        let execute = config.get_bool("execute").unwrap_or(false);
-        return run_rules(&mut client, rules, execute).await;
+        let mut rules = rules_cli::get_rules_from(rules_path.as_deref())?;
+        let plan = plan_rules(&mut client, &mut rules, false).await?;
+        print_plan(&plan);
+        return Ok(());
     };
 
     match sub_command {
@@ -327,18 +411,48 @@ async fn run(args: Cli) -> Result<()> {
             // This should never be reached due to early return above
             unreachable!("Init command should have been handled earlier");
         }
-        SubCmds::Message(messages_cli) => messages_cli.run(&mut client).await,
-        SubCmds::Labels(labels_cli) => labels_cli.run(client).await,
+        SubCmds::Message(messages_cli) => messages_cli.run(&mut client, message_ids).await,
+        SubCmds::Labels(labels_cli) => labels_cli.run(&mut client, rules_path.as_deref()).await,
+        SubCmds::Stats(stats_cli) => stats_cli.run(&mut client).await,
+        SubCmds::Explain(explain_cli) => {
+            let rules = rules_cli::get_rules_from(rules_path.as_deref())?;
+            explain_cli.run(&mut client, rules).await
+        }
         SubCmds::Rules(rules_cli) => {
             rules_cli
                 .run_with_rules_path(&mut client, rules_path.as_deref())
                 .await
         }
-        SubCmds::Token(token_cli) => {
-            // Token commands don't need an initialized client, just the config
-            // We need to get a fresh client_config since the original was moved
-            let (_, token_client_config) = get_config()?;
-            token_cli.run(&token_client_config).await
+        SubCmds::Token(_) => {
+            // This should never be reached due to early return above
+            unreachable!("Token command should have been handled earlier");
+        }
+    }
+}
+
+/// Determines the least privileged OAuth2 scope needed by a subcommand.
+///
+/// # Arguments
+///
+/// * `sub_command` - The selected subcommand, `None` for the default rule preview
+///
+/// # Scope Selection
+///
+/// - **Listing**: `messages list`, `labels list`, `stats` and `explain` only need read-only access
+/// - **Labels**: creating, renaming, deleting and colouring labels need modify access
+/// - **Preview**: the default rule preview, `rules run` and `rules plan` only need
+///   read-only access
+/// - **Trash**: `messages trash` and plans that trash need modify access
+/// - **Delete**: full access is requested only for `messages delete` or when
+///   applying a plan that deletes messages
+fn get_scope(sub_command: Option<&SubCmds>) -> Result<GmailScope> {
+    match sub_command {
+        None => Ok(GmailScope::ReadOnly),
+        Some(SubCmds::Message(messages_cli)) => Ok(messages_cli.scope()),
+        Some(SubCmds::Rules(rules_cli)) => rules_cli.scope(),
+        Some(SubCmds::Labels(labels_cli)) => Ok(labels_cli.scope()),
+        Some(SubCmds::Init(_) | SubCmds::Stats(_) | SubCmds::Explain(_) | SubCmds::Token(_)) => {
+            Ok(GmailScope::ReadOnly)
         }
     }
 }
@@ -398,9 +512,9 @@ fn get_logging(level: log::LevelFilter) -> env_logger::Builder {
 ///
 /// # Returns
 ///
-/// Returns a tuple containing:
-/// - **Config**: Raw configuration for general application settings
-/// - **ClientConfig**: Processed Gmail client configuration with OAuth2 setup
+/// Returns the raw configuration. The Gmail client configuration is built from
+/// it with [`ClientConfig::new_from_configuration`] only for commands that
+/// contact Gmail, as that reads the OAuth2 credentials.
 ///
 /// # Configuration Hierarchy
 ///
@@ -415,22 +529,18 @@ fn get_logging(level: log::LevelFilter) -> env_logger::Builder {
 /// - `credentials`: "credential.json" - OAuth2 credential file name
 /// - `config_root`: "h:.cull-gmail" - Configuration directory (home-relative)
 /// - `rules`: "rules.toml" - Rules configuration file name
-/// - `execute`: true - Default execution mode (can be overridden for safety)
 ///
 /// ## Environment Variables:
 /// - `APP_CREDENTIALS`: Override credential file name
 /// - `APP_CONFIG_ROOT`: Override configuration directory
 /// - `APP_RULES`: Override rules file name
-/// - `APP_EXECUTE`: Override execution mode (true/false)
 ///
 /// # Error Handling
 ///
 /// Configuration errors can occur due to:
 /// - Missing or inaccessible configuration files
 /// - Invalid TOML syntax in configuration files
-/// - Missing OAuth2 credential files
-/// - Invalid OAuth2 credential format or structure
-fn get_config() -> Result<(Config, ClientConfig)> {
+fn load_config() -> Result<Config> {
     let home_dir = env::home_dir().unwrap();
     let path = home_dir.join(".cull-gmail/cull-gmail.toml");
     log::info!("Loading config from {}", path.display());
@@ -439,7 +549,6 @@ fn get_config() -> Result<(Config, ClientConfig)> {
         .set_default("credential_file", "credential.json")?
         .set_default("config_root", "h:.cull-gmail")?
         .set_default("rules", "rules.toml")?
-        .set_default("execute", true)?
         .set_default("token_uri", "https://oauth2.googleapis.com/token")?
         .set_default("auth_uri", "https://accounts.google.com/o/oauth2/auth")?
         .set_default("token_cache_env", "CULL_GMAIL_TOKEN_CACHE")?;
@@ -453,127 +562,224 @@ fn get_config() -> Result<(Config, ClientConfig)> {
         .add_source(config::Environment::with_prefix("APP"))
         .build()?;
 
-    Ok((
-        configurations.clone(),
-        ClientConfig::new_from_configuration(configurations)?,
-    ))
+    Ok(configurations)
 }
 
-/// Executes automated message retention rules across Gmail labels by action.
+/// Number of sample subjects recorded for each label in a plan.
+const PLAN_SAMPLES: usize = 5;
+
+/// Resolves the retention rules into the messages each would act on.
 ///
-/// This function orchestrates the rule-based message processing workflow by:
-/// 1. Executing rules by action: `Delete` first, then `Trash`
-/// 2. Organizing rules by their target labels
-/// 3. Processing each label according to its configured rule
-/// 4. Executing or simulating actions based on execution mode
+/// This function runs the planning phase of rule execution by:
+/// 1. Reporting labels used by the rules that are missing from Gmail
+/// 2. Resolving rules by action: `Delete` first, then `Trash`
+/// 3. Listing the messages each rule selects for each of its labels
+///
+/// Nothing in Gmail is changed. The returned [`Plan`] is carried out by
+/// [`apply_plan`].
 ///
 /// # Arguments
 ///
 /// * `client` - Mutable Gmail client for API operations
 /// * `rules` - Loaded rules configuration containing all retention policies
-/// * `execute` - Whether to actually perform actions (true) or dry-run (false)
+/// * `review` - Whether to let the user deselect messages for each label
 ///
-/// # Returns
+/// # Review
 ///
-/// Returns `Result<()>` indicating success or failure of the rule processing.
-///
-/// # Rule Processing Flow
-///
-/// For each configured label:
-/// 1. **Rule Lookup**: Find the retention rule for the label
-/// 2. **Rule Application**: Apply rule criteria to find matching messages
-/// 3. **Action Determination**: Determine appropriate action (trash/delete)
-/// 4. **Execution**: Execute action or simulate for dry-run
-///
-/// # Safety Features
-///
-/// - **Dry-run mode**: When `execute` is false, actions are logged but not performed
-/// - **Error isolation**: Errors for individual labels don't stop processing of other labels
-/// - **Detailed logging**: Comprehensive logging of rule execution and results
+/// With `review`, the senders of deselected messages can be protected from the
+/// rule in future. That changes the rules, so the plan is fingerprinted after
+/// all labels have been reviewed.
 ///
 /// # Error Handling
 ///
 /// The function continues processing even if individual rules fail, logging
-/// warnings for missing rules, processing errors, or action failures.
-async fn run_rules(client: &mut GmailClient, rules: Rules, execute: bool) -> Result<()> {
-    run_rules_for_action(client, &rules, execute, EolAction::Delete).await?;
-    run_rules_for_action(client, &rules, execute, EolAction::Trash).await?;
+/// warnings for missing rules or labels with nothing to process.
+async fn plan_rules(client: &mut GmailClient, rules: &mut Rules, review: bool) -> Result<Plan> {
+    labels_cli::sync_rule_labels(client, rules, false).await?;
 
-    Ok(())
+    let mut entries = Vec::new();
+    for action in [EolAction::Delete, EolAction::Trash] {
+        plan_rules_for_action(client, rules, review, action, &mut entries).await?;
+    }
+
+    let mut plan = Plan::new(rules);
+    for entry in entries {
+        plan.add_entry(entry);
+    }
+    Ok(plan)
 }
 
-/// Executes automated message retention rules across Gmail labels for an action.
+/// Resolves the rules for an action into plan entries, one per label.
 ///
-/// This function orchestrates the rule-based message processing workflow by:
-/// 1. Organizing rules by their target labels
-/// 2. Processing each label according to its configured rule
-/// 3. Executing or simulating actions based on execution mode
-///
-/// # Arguments
-///
-/// * `client` - Mutable Gmail client for API operations
-/// * `rules` - Loaded rules configuration containing all retention policies
-/// * `execute` - Whether to actually perform actions (true) or dry-run (false)
-/// * `action` - The action the rule will execute
-///
-/// # Returns
-///
-/// Returns `Result<()>` indicating success or failure of the rule processing.
-///
-/// # Rule Processing Flow
-///
-/// For each configured label:
-/// 1. **Rule Lookup**: Find the retention rule for the label
-/// 2. **Rule Application**: Apply rule criteria to find matching messages
-/// 3. **Action Determination**: Determine appropriate action (trash/delete)
-/// 4. **Execution**: Execute action or simulate for dry-run
-///
-/// # Safety Features
-///
-/// - **Dry-run mode**: When `execute` is false, actions are logged but not performed
-/// - **Error isolation**: Errors for individual labels don't stop processing of other labels
-/// - **Detailed logging**: Comprehensive logging of rule execution and results
-///
-/// # Error Handling
-///
-/// The function continues processing even if individual rules fail, logging
-/// warnings for missing rules, processing errors, or action failures.
-async fn run_rules_for_action(
+/// Wildcard labels such as `Vendors/*` are expanded to the matching labels in
+/// Gmail. A matched label that has a rule of its own is left to that rule, and
+/// a label matched by several patterns is planned once. A rule's
+/// `max_messages` limit covers all of its labels together.
+async fn plan_rules_for_action(
     client: &mut GmailClient,
-    rules: &Rules,
-    execute: bool,
+    rules: &mut Rules,
+    review: bool,
     action: EolAction,
+    entries: &mut Vec<PlanEntry>,
 ) -> Result<()> {
     let rules_by_labels = rules.get_rules_by_label_for_action(action);
+    let rule_labels = rules.labels();
+    let mut processed = BTreeSet::new();
+    let mut labels_by_rule: Vec<(EolRule, Vec<String>)> = Vec::new();
 
-    for label in rules.labels() {
-        let Some(rule) = rules_by_labels.get(&label) else {
-            log::warn!("no rule found for label `{label}`");
+    for pattern in &rule_labels {
+        let Some(rule) = rules_by_labels.get(pattern) else {
+            log::warn!("no rule found for label `{pattern}`");
             continue;
         };
 
-        log::info!("Executing rule `#{}` for label `{label}`", rule.describe());
-        client.initialise_lists();
+        let labels = client.expand_label(pattern);
+        if labels.is_empty() {
+            log::warn!("no labels match `{pattern}`");
+        }
+        for label in labels {
+            if label != *pattern && rule_labels.contains(&label) {
+                log::info!("label `{label}` matched by `{pattern}` has a rule of its own");
+                continue;
+            }
+            if !processed.insert(label.clone()) {
+                continue;
+            }
+            match labels_by_rule.iter_mut().find(|(r, _)| r.id() == rule.id()) {
+                Some((_, rule_labels)) => rule_labels.push(label),
+                None => labels_by_rule.push((rule.clone(), vec![label])),
+            }
+        }
+    }
+
+    for (rule, labels) in labels_by_rule {
+        // Setting the rule once lets its labels share its message limit
         client.set_rule(rule.clone());
-        client.set_execute(execute);
-        if let Err(e) = client.find_rule_and_messages_for_label(&label).await {
-            log::warn!("Nothing to process for label `{label}` as {e}");
-            continue;
+        client.set_execute(false);
+        for label in labels {
+            if let Some(entry) = plan_rule_for_label(client, rules, &rule, &label, review).await? {
+                entries.push(entry);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the messages the client's current rule selects for a single label.
+///
+/// Returns `None` when there is nothing to process for the label.
+async fn plan_rule_for_label(
+    client: &mut GmailClient,
+    rules: &mut Rules,
+    rule: &EolRule,
+    label: &str,
+    review: bool,
+) -> Result<Option<PlanEntry>> {
+    log::info!("Planning rule `#{}` for label `{label}`", rule.describe());
+    client.initialise_lists();
+    if let Err(e) = client.find_rule_and_messages_for_label(label).await {
+        log::warn!("Nothing to process for label `{label}` as {e}");
+        return Ok(None);
+    }
+    let Some(action) = client.action() else {
+        log::warn!("no valid action specified for rule #{}", rule.id());
+        return Ok(None);
+    };
+
+    if review {
+        let kept_senders = review::review_messages(client, action).await?;
+        if review::confirm_protect(&kept_senders)? {
+            rules.add_protected_senders(rule.id(), &kept_senders)?;
         }
-        let Some(action) = client.action() else {
-            log::warn!("no valid action specified for rule #{}", rule.id());
+    } else if !client.messages().is_empty() {
+        client.fetch_metadata().await?;
+    }
+
+    let messages = client.messages();
+    Ok(Some(PlanEntry {
+        rule_id: rule.id(),
+        label: label.to_string(),
+        action,
+        count: messages.len(),
+        message_ids: messages.iter().map(|m| m.id().to_string()).collect(),
+        samples: messages
+            .iter()
+            .take(PLAN_SAMPLES)
+            .map(|m| m.subject().to_string())
+            .collect(),
+    }))
+}
+
+/// Prints the actions in a plan with their counts and sample subjects.
+fn print_plan(plan: &Plan) {
+    for entry in plan.entries() {
+        println!(
+            "Rule #{} would {} {} message(s) with label `{}`",
+            entry.rule_id, entry.action, entry.count, entry.label
+        );
+        for subject in &entry.samples {
+            println!("    {subject}");
+        }
+        if entry.count > entry.samples.len() {
+            println!("    ... and {} more", entry.count - entry.samples.len());
+        }
+    }
+    println!(
+        "Plan: {} message(s) in {} label(s)",
+        plan.message_count(),
+        plan.entries().iter().filter(|e| e.count > 0).count()
+    );
+}
+
+/// Carries out the actions recorded in a plan.
+///
+/// Each entry's action is applied to exactly the message IDs recorded in the
+/// plan; the rules are not evaluated again and no labels are created. The rule of each entry is
+/// still consulted for settings such as `export_before_action`.
+///
+/// # Error Handling
+///
+/// A failed entry is logged and the remaining entries are still applied. The
+/// first failure is then returned, so the process exits with its error code.
+async fn apply_plan(client: &mut GmailClient, rules: &Rules, plan: &Plan) -> Result<()> {
+    let mut first_error = None;
+
+    for entry in plan.entries() {
+        if entry.message_ids.is_empty() {
             continue;
+        }
+        let Some(rule) = rules.get_rule(entry.rule_id) else {
+            return Err(Error::RuleNotFound(entry.rule_id));
         };
 
-        if execute {
-            execute_action(action, client, &label).await;
-        } else {
-            client.log_messages("", "").await?;
-            log::warn!("Execution stopped for dry run");
+        client.initialise_lists();
+        client.set_rule(rule);
+        log::info!(
+            "***executing {} for label `{}`***",
+            entry.action,
+            entry.label
+        );
+        match client
+            .process_in_chunks(entry.message_ids.clone(), entry.action)
+            .await
+        {
+            Ok(()) => println!(
+                "Rule #{}: applied {} to {} message(s) with label `{}`",
+                entry.rule_id, entry.action, entry.count, entry.label
+            ),
+            Err(e) => {
+                log::warn!(
+                    "Applying {} failed for label `{}`: {e}",
+                    entry.action,
+                    entry.label
+                );
+                first_error.get_or_insert(e);
+            }
         }
     }
 
-    Ok(())
+    first_error.map_or(Ok(()), Err)
 }
 
 /// Restores OAuth2 tokens from environment variable if available.
@@ -585,7 +791,7 @@ async fn run_rules_for_action(
 /// # Arguments
 ///
 /// * `config` - Application configuration containing token environment variable name
-/// * `client_config` - Client configuration containing token persistence path
+/// * `token_path` - Directory the OAuth2 tokens are cached in
 ///
 /// # Returns
 ///
@@ -603,14 +809,14 @@ async fn run_rules_for_action(
 /// - Container deployments with injected token environment variables
 /// - CI/CD pipelines with stored token secrets
 /// - Ephemeral compute environments requiring periodic Gmail access
-fn restore_tokens_if_available(config: &Config, client_config: &ClientConfig) -> Result<()> {
+fn restore_tokens_if_available(config: &Config, token_path: &str) -> Result<()> {
     let token_env_var = config
         .get_string("token_cache_env")
         .unwrap_or_else(|_| "CULL_GMAIL_TOKEN_CACHE".to_string());
 
     if let Ok(token_data) = env::var(&token_env_var) {
         log::info!("Found {token_env_var} environment variable, restoring tokens");
-        restore_tokens_from_string(&token_data, client_config.persist_path())?;
+        restore_tokens_from_string(&token_data, token_path)?;
         log::info!("Tokens successfully restored from environment variable");
     } else {
         log::debug!(
@@ -621,6 +827,23 @@ fn restore_tokens_if_available(config: &Config, client_config: &ClientConfig) ->
     Ok(())
 }
 
+/// Gets the directory OAuth2 tokens are cached in from configuration.
+///
+/// This is the [`ClientConfig::persist_path`] for the configured root, found
+/// without reading the OAuth2 credentials so that token commands work without
+/// them.
+///
+/// # Arguments
+///
+/// * `config` - Application configuration
+fn get_token_path(config: &Config) -> Result<String> {
+    let root = config.get_string("config_root")?;
+    Ok(format!(
+        "{}/gmail1",
+        init_cli::parse_config_root(&root).display()
+    ))
+}
+
 /// Gets the rules file path from configuration.
 ///
 /// Reads the `rules` configuration value and resolves it using path prefixes.
@@ -647,55 +870,3 @@ fn get_rules_path(config: &Config) -> Result<Option<PathBuf>> {
     let path = init_cli::parse_config_root(&rules_config);
     Ok(Some(path))
 }
-
-/// Executes the specified end-of-life action on messages for a Gmail label.
-///
-/// This function performs the actual message operations (trash or delete) based on
-/// the rule configuration and execution mode. It handles both recoverable (trash)
-/// and permanent (delete) operations with appropriate logging and error handling.
-///
-/// # Arguments
-///
-/// * `action` - The end-of-life action to perform (Trash or Delete)
-/// * `client` - Gmail client configured with messages to process
-/// * `label` - Label name for context in logging and error reporting
-///
-/// # Actions
-///
-/// ## Trash
-/// - **Operation**: Moves messages to Gmail's Trash folder
-/// - **Reversibility**: Messages can be recovered from Trash for ~30 days
-/// - **Safety**: Relatively safe operation with recovery options
-///
-/// ## Delete
-/// - **Operation**: Permanently deletes messages from Gmail
-/// - **Reversibility**: **IRREVERSIBLE** - messages cannot be recovered
-/// - **Safety**: High-risk operation requiring careful consideration
-///
-/// # Error Handling
-///
-/// The function logs errors but does not propagate them, allowing rule processing
-/// to continue for other labels even if one action fails. Errors are reported through:
-/// - **Warning logs**: Structured logging for debugging
-/// - **Label context**: Error messages include label name for traceability
-///
-/// # Safety Considerations
-///
-/// This function should only be called when execute mode is enabled and after
-/// appropriate user confirmation for destructive operations.
-async fn execute_action(action: EolAction, client: &mut GmailClient, label: &str) {
-    match action {
-        EolAction::Trash => {
-            log::info!("***executing trash messages***");
-            if client.batch_trash().await.is_err() {
-                log::warn!("Move to trash failed for label `{label}`");
-            }
-        }
-        EolAction::Delete => {
-            log::info!("***executing final delete messages***");
-            if client.batch_delete().await.is_err() {
-                log::warn!("Delete failed for label `{label}`");
-            }
-        }
-    }
-}
@@ -22,7 +22,12 @@
 //! ### Available Actions
 //!
 //! - **`list`**: Display message information without modifications
+//! - **`export`**: Save messages to local mbox or EML files without modifications
+//! - **`label`**: Add (`--add`) and remove (`--remove`) labels, optionally creating them (`--create`)
+//! - **`unlabel`**: Remove labels from messages
 //! - **`trash`**: Move messages to Gmail's Trash folder (recoverable)
+//!   - `--review` (also on `delete`): deselect messages to keep in a checklist first
+//! - **`untrash`** (alias `restore`): Restore messages from the Trash to INBOX or a given label
 //! - **`delete`**: Permanently delete messages (irreversible)
 //!
 //! ### Filtering Options
@@ -31,6 +36,19 @@
 //! - **`-Q, --query`**: Advanced Gmail query string using Gmail search syntax
 //! - **`-m, --max-results`**: Maximum results per page (default: 200)
 //! - **`-p, --pages`**: Maximum number of pages to process (0 = all pages)
+//! - **`--limit`**: Stop after exactly this many messages, whichever of the
+//!   page and message limits is reached first
+//!
+//! ### Acting on Known Messages
+//!
+//! - **`--ids-from`**: Read message IDs from a file, or `-` for stdin, instead
+//!   of searching. Lines are bare IDs or JSON Lines from `list --format jsonl`.
+//! - **`--skip-unknown`**: Act on the IDs that exist and warn about the rest,
+//!   rather than stopping
+//!
+//! Typed filters such as `--from`, `--older-than 6m`, `--larger 5M` and
+//! `--exclude-label` are translated to Gmail operators and combined with
+//! `--query`; `--show-query` prints the final query string.
 //!
 //! ## Gmail Query Syntax
 //!
@@ -64,33 +82,102 @@
 //! cull-gmail messages -m 10 list
 //! ```
 //!
+//! ### Export Messages as JSON Lines
+//! ```bash
+//! cull-gmail messages -Q "older_than:1y" list --format jsonl --columns id,from,size
+//! ```
+//!
 //! ### Find Old Promotional Emails
 //! ```bash
 //! cull-gmail messages -Q "label:promotions older_than:1y" list
 //! ```
 //!
+//! ### Archive Before Deleting
+//! ```bash
+//! cull-gmail messages -Q "older_than:5y" export --format mbox --out ~/mail-archive
+//! cull-gmail messages -Q "older_than:5y" delete
+//! ```
+//!
 //! ### Batch Trash Operation
 //! ```bash
 //! cull-gmail messages -Q "label:newsletters older_than:6m" trash
 //! ```
 //!
+//! ### Trash an Exact Number of Messages
+//! ```bash
+//! cull-gmail messages -p 0 --limit 1200 -Q "older_than:2y" trash
+//! ```
+//!
+//! ### Tag Messages for a Rule
+//! ```bash
+//! cull-gmail messages -Q "from:news@example.com" -p 0 label --add retention/1-years --create
+//! ```
+//!
+//! ### Trash Messages Selected Elsewhere
+//! ```bash
+//! cull-gmail messages -Q "older_than:1y" list --format jsonl > review.jsonl
+//! # ...remove the lines to keep...
+//! cull-gmail messages --ids-from review.jsonl trash
+//! cut -d, -f1 ids.csv | cull-gmail messages --ids-from - label --add Review
+//! ```
+//!
+//! ### Pick Messages to Keep Before Trashing
+//! ```bash
+//! cull-gmail messages -Q "label:newsletters older_than:6m" trash --review
+//! ```
+//!
+//! ### Undo a Trash Operation
+//! ```bash
+//! cull-gmail messages -Q "label:newsletters older_than:6m" untrash
+//! cull-gmail messages -Q "from:billing@example.com" untrash --label Receipts
+//! ```
+//!
+//! ### Typed Filters
+//! ```bash
+//! cull-gmail messages --from news@example.com --older-than 6m --exclude-label Keep --show-query list
+//! ```
+//!
 //! ### Multi-Label Query
 //! ```bash
 //! cull-gmail messages -l "promotions" -l "newsletters" -Q "older_than:3m" list
 //! ```
 
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand};
-use cull_gmail::{GmailClient, MessageList, Result, RuleProcessor};
+use cull_gmail::{EolAction, Error, GmailClient, GmailScope, MessageList, Result, RuleProcessor};
+
+use crate::{
+    review::{ReviewArgs, review_messages},
+    selection_args::SelectionArgs,
+};
+
+mod export_cli;
+mod ids_from;
+mod label_cli;
+mod list_cli;
+mod untrash_cli;
+
+use export_cli::ExportCli;
+use label_cli::{LabelCli, UnlabelCli};
+use list_cli::ListCli;
+use untrash_cli::UntrashCli;
+
+/// Gmail label ID of the trash folder.
+const TRASH_LABEL: &str = "TRASH";
 
 /// Available actions for Gmail message operations.
 ///
-/// This enum defines the three primary operations that can be performed on Gmail messages
+/// This enum defines the operations that can be performed on Gmail messages
 /// through the CLI, each with different levels of safety and reversibility.
 ///
 /// # Action Safety Levels
 ///
 /// - **List**: Safe inspection operation with no modifications
+/// - **Export**: Safe copy to local files with no modifications
+/// - **Label/Unlabel**: Reversible label changes
 /// - **Trash**: Recoverable operation (messages can be restored for ~30 days)
+/// - **Untrash**: Reverses a trash operation
 /// - **Delete**: Permanent operation (irreversible)
 ///
 /// # Usage Context
@@ -108,7 +195,30 @@ enum MessageAction {
     /// - Date and size information  
     /// - Labels and threading information
     /// - Internal Gmail message IDs
-    List,
+    ///
+    /// Output is written to stdout as a table, JSON, JSON Lines or CSV.
+    List(ListCli),
+
+    /// Save a copy of messages to local mbox or EML files.
+    ///
+    /// This operation:
+    /// - Fetches the full message source (`format=raw`)
+    /// - Writes RFC 822 files to the output directory
+    /// - Leaves the messages in Gmail unchanged
+    Export(ExportCli),
+
+    /// Add and remove labels on messages.
+    ///
+    /// This operation:
+    /// - Applies labels given with `--add` and removes those given with `--remove`
+    /// - Optionally creates labels that do not exist yet
+    /// - Is reversible by running the opposite change
+    Label(LabelCli),
+
+    /// Remove labels from messages.
+    ///
+    /// Shorthand for `label --remove` with one or more labels.
+    Unlabel(UnlabelCli),
 
     /// Move messages to Gmail's Trash folder.
     ///
@@ -117,7 +227,18 @@ enum MessageAction {
     /// - Allows recovery for approximately 30 days
     /// - Is reversible through Gmail's web interface
     /// - Provides a safety buffer before permanent deletion
-    Trash,
+    ///
+    /// With `--review`, messages can be deselected in a checklist first.
+    Trash(ReviewArgs),
+
+    /// Restore messages from Gmail's Trash folder.
+    ///
+    /// This operation:
+    /// - Only selects messages that are in the Trash
+    /// - Removes the Trash label and puts back INBOX or a given label
+    /// - Reverses an earlier `trash` action
+    #[clap(alias = "restore")]
+    Untrash(UntrashCli),
 
     /// Permanently delete messages from Gmail.
     ///
@@ -126,7 +247,9 @@ enum MessageAction {
     /// - No recovery is possible after deletion
     /// - Use extreme caution and always test with list first
     /// - Consider using trash instead for safety
-    Delete,
+    ///
+    /// With `--review`, messages can be deselected in a checklist first.
+    Delete(ReviewArgs),
 }
 
 /// Command-line interface for Gmail message querying and batch operations.
@@ -173,55 +296,47 @@ enum MessageAction {
 /// - Test queries thoroughly before batch operations
 #[derive(Debug, Parser)]
 pub struct MessagesCli {
-    /// Maximum number of messages to retrieve per page.
-    ///
-    /// Controls the batch size for Gmail API requests. Larger values are more
-    /// efficient but may hit API rate limits. Smaller values provide more
-    /// granular control and progress feedback.
-    ///
-    /// **Range**: 1-500 (Gmail API limit)
-    /// **Performance**: 100-200 is typically optimal
-    #[arg(short, long,display_order = 1, help_heading = "Config", default_value = cull_gmail::DEFAULT_MAX_RESULTS)]
-    max_results: u32,
+    /// Labels, query and paging used to select messages.
+    #[command(flatten)]
+    selection: SelectionArgs,
 
-    /// Maximum number of pages to process.
-    ///
-    /// Limits the total number of API requests and messages processed.
-    /// Use 0 for unlimited pages (process all matching messages).
+    /// Maximum number of messages to process.
     ///
-    /// **Safety**: Start with 1-2 pages for testing destructive operations
-    /// **Performance**: Higher values process more messages but take longer
+    /// Paging stops as soon as this many messages have been collected and the
+    /// last page is trimmed, so exactly this many messages are acted on when
+    /// enough match. Applies together with `--pages`; whichever limit is
+    /// reached first wins. Use `--pages 0` to let `--limit` alone decide.
     #[arg(
-        short,
         long,
         display_order = 1,
         help_heading = "Config",
-        default_value = "1"
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..)
     )]
-    pages: u32,
+    limit: Option<u32>,
 
-    /// Gmail labels to filter messages (can be specified multiple times).
+    /// Read message IDs from a file, or `-` for stdin, instead of searching.
     ///
-    /// Filters messages to only those containing ALL specified labels.
-    /// Use `cull-gmail labels` to see available labels in your account.
-    ///
-    /// **Examples**:
-    /// - `-l "INBOX"` - Messages in inbox
-    /// - `-l "promotions" -l "unread"` - Unread promotional messages
-    #[arg(short, long, display_order = 1, help_heading = "Config")]
-    labels: Vec<String>,
+    /// Each line is a message ID or a JSON object with an `id` field, as
+    /// written by `list --format jsonl`. Every ID is checked before any
+    /// change is made.
+    #[arg(
+        long,
+        value_name = "FILE",
+        display_order = 1,
+        help_heading = "Config",
+        conflicts_with_all = ["labels", "query", "QueryArgs"]
+    )]
+    ids_from: Option<PathBuf>,
 
-    /// Gmail query string using Gmail's advanced search syntax.
-    ///
-    /// Supports the same query syntax as Gmail's web interface search box.
-    /// Can be combined with label filters for more precise targeting.
-    ///
-    /// **Examples**:
-    /// - `"older_than:1y"` - Messages older than 1 year
-    /// - `"from:noreply@example.com older_than:30d"` - Old automated emails
-    /// - `"has:attachment larger:10M"` - Large attachments
-    #[arg(short = 'Q', long, display_order = 1, help_heading = "Config")]
-    query: Option<String>,
+    /// With `--ids-from`, act on the IDs that exist and warn about the rest.
+    #[arg(
+        long,
+        requires = "ids_from",
+        display_order = 1,
+        help_heading = "Config"
+    )]
+    skip_unknown: bool,
 
     /// Action to perform on the filtered messages.
     ///
@@ -242,6 +357,7 @@ impl MessagesCli {
     /// # Arguments
     ///
     /// * `client` - Mutable Gmail client for API operations and state management
+    /// * `ids` - Message IDs read by [`MessagesCli::read_ids`], used instead of searching
     ///
     /// # Returns
     ///
@@ -279,29 +395,99 @@ impl MessagesCli {
     ///
     /// # Safety Features
     ///
-    /// - **Logging awareness**: List output adapts to logging verbosity
+    /// - **Scriptable output**: List output goes to stdout regardless of logging verbosity
     /// - **Error isolation**: Individual message failures don't stop batch processing
     /// - **Progress tracking**: Detailed logging for operation monitoring
-    pub(crate) async fn run(&self, client: &mut GmailClient) -> Result<()> {
-        self.set_parameters(client)?;
-
-        client.get_messages(self.pages).await?;
+    pub(crate) async fn run(
+        &self,
+        client: &mut GmailClient,
+        ids: Option<Vec<String>>,
+    ) -> Result<()> {
+        if let Some(ids) = ids {
+            self.add_ids(client, &ids).await?;
+            if let MessageAction::Untrash(_) = self.action {
+                untrash_cli::skip_untrashed(client);
+            }
+        } else {
+            self.set_parameters(client)?;
+            client.get_messages(self.selection.pages).await?;
+        }
 
-        match self.action {
-            MessageAction::List => {
-                if log::max_level() >= log::Level::Info {
-                    client.log_messages("", "").await
-                } else {
-                    Ok(())
+        match &self.action {
+            MessageAction::List(list_cli) => list_cli.run(client).await,
+            MessageAction::Export(export_cli) => export_cli.run(client).await,
+            MessageAction::Label(label_cli) => label_cli.run(client).await,
+            MessageAction::Unlabel(unlabel_cli) => unlabel_cli.run(client).await,
+            MessageAction::Trash(review) => {
+                if review.review {
+                    review_messages(client, EolAction::Trash).await?;
+                }
+                client.batch_trash().await
+            }
+            MessageAction::Untrash(untrash_cli) => untrash_cli.run(client).await,
+            MessageAction::Delete(review) => {
+                if review.review {
+                    review_messages(client, EolAction::Delete).await?;
                 }
+                client.batch_delete().await
             }
-            MessageAction::Trash => client.batch_trash().await,
-            MessageAction::Delete => client.batch_delete().await,
         }
 
         // Ok(())
     }
 
+    /// Returns the OAuth2 scope needed for the selected action.
+    ///
+    /// Listing and exporting only read messages, labelling, trashing and
+    /// restoring modify labels, and only permanent deletion requires full
+    /// mailbox access.
+    pub(crate) fn scope(&self) -> GmailScope {
+        match &self.action {
+            MessageAction::List(_) | MessageAction::Export(_) => GmailScope::ReadOnly,
+            MessageAction::Label(_)
+            | MessageAction::Unlabel(_)
+            | MessageAction::Trash(_)
+            | MessageAction::Untrash(_) => GmailScope::Modify,
+            MessageAction::Delete(_) => GmailScope::Full,
+        }
+    }
+
+    /// Reads the message IDs given with `--ids-from`, if any.
+    ///
+    /// The list is read before the Gmail client is built, so that a missing
+    /// or malformed file is reported without asking for authorisation.
+    pub(crate) fn read_ids(&self) -> Result<Option<Vec<String>>> {
+        let Some(source) = &self.ids_from else {
+            return Ok(None);
+        };
+
+        let ids = ids_from::read_ids(source)?;
+        log::info!(
+            "Read {} message ID(s) from `{}`",
+            ids.len(),
+            source.display()
+        );
+        Ok(Some(ids))
+    }
+
+    /// Adds the given messages to the client instead of searching.
+    ///
+    /// Unknown IDs stop the command before any change is made, unless
+    /// `--skip-unknown` is given.
+    async fn add_ids(&self, client: &mut GmailClient, ids: &[String]) -> Result<()> {
+        let unknown = client.add_message_ids(ids).await?;
+        if !unknown.is_empty() {
+            if !self.skip_unknown {
+                return Err(Error::UnknownMessageIds(unknown));
+            }
+            for id in &unknown {
+                log::warn!("Skipping unknown message ID `{id}`");
+            }
+        }
+
+        Ok(())
+    }
+
     /// Configures the Gmail client with filtering and pagination parameters.
     ///
     /// This method applies all user-specified configuration to the Gmail client,
@@ -346,60 +532,15 @@ impl MessagesCli {
     /// - **Trace**: Detailed parameter values for debugging
     /// - **Debug**: Configuration confirmation and validation results
     fn set_parameters(&self, client: &mut GmailClient) -> Result<()> {
-        if !self.labels().is_empty() {
-            client.add_labels(self.labels())?;
-        }
+        self.selection.apply(client)?;
 
-        if let Some(query) = self.query().as_ref() {
-            client.set_query(query)
+        // Only messages in the trash can be restored
+        if let MessageAction::Untrash(_) = self.action {
+            client.add_labels_ids(&[TRASH_LABEL.to_string()]);
         }
 
-        log::trace!("Max results: `{}`", self.max_results());
-        client.set_max_results(self.max_results());
-        log::debug!("List max results set to {}", client.max_results());
+        client.set_max_messages(self.limit);
 
         Ok(())
     }
-
-    /// Returns a reference to the configured Gmail labels for filtering.
-    ///
-    /// This accessor provides access to the list of labels that will be used
-    /// to filter messages. Labels are combined with AND logic, meaning messages
-    /// must have ALL specified labels to be included in results.
-    ///
-    /// # Returns
-    ///
-    /// Returns a reference to the vector of label names as configured by the user.
-    /// An empty vector indicates no label-based filtering will be applied.
-    pub(crate) fn labels(&self) -> &Vec<String> {
-        &self.labels
-    }
-
-    /// Returns a reference to the configured Gmail query string.
-    ///
-    /// This accessor provides access to the advanced query string that will be
-    /// applied to message filtering. The query uses Gmail's native search syntax
-    /// and can be combined with label filters for precise targeting.
-    ///
-    /// # Returns
-    ///
-    /// Returns a reference to the optional query string. `None` indicates
-    /// no advanced query filtering will be applied.
-    pub(crate) fn query(&self) -> &Option<String> {
-        &self.query
-    }
-
-    /// Returns the maximum number of messages to retrieve per page.
-    ///
-    /// This accessor provides the configured batch size for Gmail API requests.
-    /// The value determines how many messages are fetched in each API call,
-    /// affecting both performance and memory usage.
-    ///
-    /// # Returns
-    ///
-    /// Returns the maximum results per page as configured by the user or default value.
-    /// The value is guaranteed to be within Gmail API acceptable limits.
-    pub(crate) fn max_results(&self) -> u32 {
-        self.max_results
-    }
 }
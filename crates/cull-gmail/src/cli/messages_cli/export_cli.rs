@@ -0,0 +1,61 @@
+//! Export subcommand for Gmail messages.
+//!
+//! Fetches the full RFC 822 source of the selected messages and writes it to a
+//! local directory, so a copy is kept before messages are trashed or deleted.
+
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use cull_gmail::{ExportFormat, GmailClient, MessageExport, MessageList, Result};
+
+/// File formats for exported messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// A single mbox file containing every message
+    Mbox,
+    /// One .eml file per message, named after the message ID
+    Eml,
+}
+
+impl From<Format> for ExportFormat {
+    fn from(value: Format) -> Self {
+        match value {
+            Format::Mbox => ExportFormat::Mbox,
+            Format::Eml => ExportFormat::Eml,
+        }
+    }
+}
+
+/// Export messages matching the filters to local files.
+///
+/// The directory is created if it does not exist.
+#[derive(Debug, Parser)]
+pub struct ExportCli {
+    /// Output file format
+    #[arg(short, long, value_enum, default_value_t = Format::Mbox)]
+    format: Format,
+
+    /// Directory to write the exported messages to
+    #[arg(short, long)]
+    out: PathBuf,
+}
+
+impl ExportCli {
+    pub async fn run(&self, client: &GmailClient) -> Result<()> {
+        let ids = client.message_ids();
+        if ids.is_empty() {
+            log::info!("No messages to export");
+            return Ok(());
+        }
+
+        let files = client
+            .export_messages(&ids, self.format.into(), &self.out)
+            .await?;
+
+        for file in files {
+            println!("{}", file.display());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+//! Message ID lists for `--ids-from`.
+//!
+//! IDs are read from a file, or from stdin when the path is `-`. Each line is
+//! either a bare message ID or a JSON object with an `id` field, as written
+//! by `messages list --format jsonl`. Blank lines and lines starting with `#`
+//! are ignored.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use cull_gmail::{Error, Result};
+
+/// Reads message IDs from `source`, or from stdin if it is `-`.
+pub fn read_ids(source: &Path) -> Result<Vec<String>> {
+    let name = source.display().to_string();
+    if name == "-" {
+        parse_ids(io::stdin().lock(), &name)
+    } else {
+        let file = File::open(source)
+            .map_err(|e| Error::MessageIdListInvalid(name.clone(), e.to_string()))?;
+        parse_ids(BufReader::new(file), &name)
+    }
+}
+
+/// Parses one message ID per line, accepting bare IDs or JSON Lines.
+fn parse_ids(reader: impl BufRead, name: &str) -> Result<Vec<String>> {
+    let invalid = |line: usize, reason: String| {
+        Error::MessageIdListInvalid(name.to_string(), format!("line {line}: {reason}"))
+    };
+
+    let mut ids = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line_no = i + 1;
+        let line =
+            line.map_err(|e| Error::MessageIdListInvalid(name.to_string(), e.to_string()))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let id = if line.starts_with('{') {
+            let value: serde_json::Value =
+                serde_json::from_str(line).map_err(|e| invalid(line_no, e.to_string()))?;
+            match value["id"].as_str() {
+                Some(id) => id.to_string(),
+                None => return Err(invalid(line_no, "no `id` field".to_string())),
+            }
+        } else {
+            line.to_string()
+        };
+
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(invalid(line_no, format!("`{id}` is not a message ID")));
+        }
+        ids.push(id);
+    }
+
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_and_jsonl_ids() {
+        let input = "# exported from the spreadsheet\n\
+                     18c1f2a3b4c5d6e7\n\
+                     \n\
+                     {\"id\":\"18c1f2a3b4c5d6e8\",\"from\":\"a@example.com\"}\n  18c1f2a3b4c5d6e9  \n";
+
+        let ids = parse_ids(input.as_bytes(), "ids.txt").unwrap();
+
+        assert_eq!(
+            ids,
+            vec!["18c1f2a3b4c5d6e7", "18c1f2a3b4c5d6e8", "18c1f2a3b4c5d6e9"]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_lines() {
+        let err = parse_ids("abc\nnot an id\n".as_bytes(), "ids.txt").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+
+        let err = parse_ids("{\"subject\":\"hi\"}\n".as_bytes(), "ids.txt").unwrap_err();
+        assert!(err.to_string().contains("no `id` field"));
+    }
+}
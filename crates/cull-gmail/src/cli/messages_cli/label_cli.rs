@@ -0,0 +1,45 @@
+//! Label and unlabel subcommands for Gmail messages.
+//!
+//! Adds or removes labels on every message selected by the label and query
+//! filters, for example to tag a result set before writing a rule for it.
+
+use clap::Parser;
+use cull_gmail::{GmailClient, LabelManager, Result};
+
+/// Add and remove labels on messages matching the filters.
+#[derive(Debug, Parser)]
+pub struct LabelCli {
+    /// Label to add (can be specified multiple times)
+    #[arg(short, long, required_unless_present = "remove")]
+    add: Vec<String>,
+
+    /// Label to remove (can be specified multiple times)
+    #[arg(short, long)]
+    remove: Vec<String>,
+
+    /// Create labels given with --add that do not exist yet
+    #[arg(short, long)]
+    create: bool,
+}
+
+impl LabelCli {
+    pub async fn run(&self, client: &mut GmailClient) -> Result<()> {
+        client
+            .modify_labels(&self.add, &self.remove, self.create)
+            .await
+    }
+}
+
+/// Remove labels from messages matching the filters.
+#[derive(Debug, Parser)]
+pub struct UnlabelCli {
+    /// Labels to remove
+    #[arg(required = true)]
+    labels: Vec<String>,
+}
+
+impl UnlabelCli {
+    pub async fn run(&self, client: &mut GmailClient) -> Result<()> {
+        client.modify_labels(&[], &self.labels, false).await
+    }
+}
@@ -0,0 +1,337 @@
+//! List subcommand for Gmail messages.
+//!
+//! Fetches the metadata of the selected messages and writes it to stdout as an
+//! aligned table, JSON, JSON Lines or CSV. Output does not depend on the log
+//! level, so the command can be used in scripts and pipelines.
+
+use std::io::{self, Write};
+
+use chrono::DateTime;
+use clap::{Parser, ValueEnum};
+use cull_gmail::{GmailClient, MessageList, MessageSummary, Result};
+use serde_json::{Map, Value};
+
+use crate::table::write_table;
+
+/// Maximum width of free-text columns in table output.
+const MAX_TABLE_WIDTH: usize = 50;
+
+/// Output formats for the message list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for reading in a terminal
+    Table,
+    /// A single JSON array of objects
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// Comma separated values with a header row
+    Csv,
+}
+
+/// Columns that can be included in the message list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    /// Gmail message ID
+    Id,
+    /// Gmail thread ID
+    Thread,
+    /// Message date (RFC 3339)
+    Date,
+    /// From header
+    From,
+    /// Subject header
+    Subject,
+    /// Size estimate in bytes
+    Size,
+    /// Names of the labels applied to the message
+    Labels,
+}
+
+impl Column {
+    /// Returns the column name used for headers and JSON keys.
+    fn name(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Thread => "thread",
+            Column::Date => "date",
+            Column::From => "from",
+            Column::Subject => "subject",
+            Column::Size => "size",
+            Column::Labels => "labels",
+        }
+    }
+
+    /// Extracts the column value from a message summary.
+    fn value(&self, message: &MessageSummary, label_name: &impl Fn(&str) -> String) -> Value {
+        let optional = |s: Option<&str>| s.map_or(Value::Null, Value::from);
+
+        match self {
+            Column::Id => Value::from(message.id()),
+            Column::Thread => optional(message.thread_id()),
+            Column::Date => message
+                .date_time()
+                .map_or(Value::Null, |d| Value::from(d.to_rfc3339())),
+            Column::From => optional(message.from()),
+            Column::Subject => Value::from(message.subject()),
+            Column::Size => message.size_estimate().map_or(Value::Null, Value::from),
+            Column::Labels => Value::from(
+                message
+                    .label_ids()
+                    .iter()
+                    .map(|id| label_name(id))
+                    .collect::<Vec<_>>(),
+            ),
+        }
+    }
+
+    /// Renders a column value as plain text.
+    ///
+    /// Dates are shortened for table output; labels are joined with `separator`.
+    fn text(&self, value: &Value, format: OutputFormat, separator: &str) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::String(s) if *self == Column::Date && format == OutputFormat::Table => {
+                DateTime::parse_from_rfc3339(s)
+                    .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|_| s.clone())
+            }
+            Value::String(s) => s.clone(),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.text(item, format, separator))
+                .collect::<Vec<_>>()
+                .join(separator),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// List messages matching the filters and write them to stdout.
+///
+/// Metadata is fetched for every selected message, so large result sets take
+/// one API call per message.
+#[derive(Debug, Parser)]
+pub struct ListCli {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    /// Columns to include, separated by commas
+    #[arg(
+        short,
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [Column::Id, Column::Date, Column::From, Column::Subject]
+    )]
+    columns: Vec<Column>,
+}
+
+impl ListCli {
+    pub async fn run(&self, client: &mut GmailClient) -> Result<()> {
+        client.fetch_metadata().await?;
+
+        let label_name = |id: &str| client.get_label_name(id).unwrap_or_else(|| id.to_string());
+        let rows = client
+            .messages()
+            .iter()
+            .map(|message| {
+                self.columns
+                    .iter()
+                    .map(|column| column.value(message, &label_name))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        let mut out = io::stdout().lock();
+        self.write_rows(&mut out, &rows)?;
+        out.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes rows of column values in the selected format.
+    fn write_rows<W: Write>(&self, out: &mut W, rows: &[Vec<Value>]) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Table => self.write_table(out, rows),
+            OutputFormat::Json => {
+                let objects = rows.iter().map(|row| self.object(row)).collect();
+                serde_json::to_writer_pretty(&mut *out, &Value::Array(objects))?;
+                writeln!(out)
+            }
+            OutputFormat::Jsonl => {
+                for row in rows {
+                    serde_json::to_writer(&mut *out, &self.object(row))?;
+                    writeln!(out)?;
+                }
+                Ok(())
+            }
+            OutputFormat::Csv => {
+                let header = self.columns.iter().map(|c| csv_field(c.name()));
+                writeln!(out, "{}", header.collect::<Vec<_>>().join(","))?;
+                for row in rows {
+                    let fields = self
+                        .columns
+                        .iter()
+                        .zip(row)
+                        .map(|(c, v)| csv_field(&c.text(v, self.format, ";")));
+                    writeln!(out, "{}", fields.collect::<Vec<_>>().join(","))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes rows as aligned columns with an upper-case header.
+    fn write_table<W: Write>(&self, out: &mut W, rows: &[Vec<Value>]) -> io::Result<()> {
+        let header = self
+            .columns
+            .iter()
+            .map(|c| c.name().to_uppercase())
+            .collect::<Vec<_>>();
+        let cells = rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .zip(row)
+                    .map(|(c, v)| truncate(&c.text(v, self.format, ", "), MAX_TABLE_WIDTH))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        write_table(out, &header, &cells, &[])
+    }
+
+    /// Builds a JSON object keyed by column name.
+    fn object(&self, row: &[Value]) -> Value {
+        let map = self
+            .columns
+            .iter()
+            .zip(row)
+            .map(|(c, v)| (c.name().to_string(), v.clone()))
+            .collect::<Map<_, _>>();
+        Value::Object(map)
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Shortens text to at most `max` characters, marking the cut with an ellipsis.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let mut short = text.chars().take(max - 1).collect::<String>();
+        short.push('…');
+        short
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn list_cli(format: OutputFormat, columns: &[Column]) -> ListCli {
+        ListCli {
+            format,
+            columns: columns.to_vec(),
+        }
+    }
+
+    fn sample_rows() -> Vec<Vec<Value>> {
+        vec![
+            vec![
+                json!("m1"),
+                json!("2024-03-01T09:15:00+00:00"),
+                json!("News, Inc <news@example.com>"),
+                json!(2048),
+                json!(["INBOX", "Newsletters"]),
+            ],
+            vec![
+                json!("m2"),
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                json!([]),
+            ],
+        ]
+    }
+
+    const COLUMNS: [Column; 5] = [
+        Column::Id,
+        Column::Date,
+        Column::From,
+        Column::Size,
+        Column::Labels,
+    ];
+
+    fn render(cli: &ListCli) -> String {
+        let mut out = Vec::new();
+        cli.write_rows(&mut out, &sample_rows()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_jsonl_output() {
+        let output = render(&list_cli(OutputFormat::Jsonl, &COLUMNS));
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        let first: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["id"], "m1");
+        assert_eq!(first["size"], 2048);
+        assert_eq!(first["labels"], json!(["INBOX", "Newsletters"]));
+        let second: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["from"], Value::Null);
+    }
+
+    #[test]
+    fn test_json_output() {
+        let output = render(&list_cli(OutputFormat::Json, &[Column::Id]));
+        let value: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value, json!([{ "id": "m1" }, { "id": "m2" }]));
+    }
+
+    #[test]
+    fn test_csv_output_quotes_fields() {
+        let output = render(&list_cli(OutputFormat::Csv, &COLUMNS));
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "id,date,from,size,labels");
+        assert_eq!(
+            lines[1],
+            "m1,2024-03-01T09:15:00+00:00,\"News, Inc <news@example.com>\",2048,INBOX;Newsletters"
+        );
+        assert_eq!(lines[2], "m2,,,,");
+    }
+
+    #[test]
+    fn test_table_output_is_aligned() {
+        let output = render(&list_cli(OutputFormat::Table, &COLUMNS));
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert!(lines[0].starts_with("ID  DATE "));
+        assert_eq!(
+            lines[1],
+            "m1  2024-03-01 09:15  News, Inc <news@example.com>  2048  INBOX, Newsletters"
+        );
+        assert_eq!(lines[0].find("LABELS"), lines[1].find("INBOX"));
+        assert_eq!(lines[2], "m2");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("a long subject line", 6), "a lon…");
+    }
+}
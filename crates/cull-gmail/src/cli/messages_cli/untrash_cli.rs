@@ -0,0 +1,64 @@
+//! Untrash subcommand for Gmail messages.
+//!
+//! Restores messages from the Gmail trash, undoing an earlier `messages trash`.
+//! Only messages in `TRASH` are selected; the usual label and query filters
+//! narrow the selection further. Messages given with `--ids-from` that are not
+//! in the trash are skipped with a warning.
+
+use clap::Parser;
+use cull_gmail::{GmailClient, MessageList, MessageSummary, Result, RuleProcessor};
+
+use super::TRASH_LABEL;
+
+/// Restore messages matching the filters from the trash.
+///
+/// Restored messages get `INBOX` back, or the label given with `--label`.
+#[derive(Debug, Parser)]
+pub struct UntrashCli {
+    /// Label to restore messages to instead of INBOX
+    #[arg(short = 'L', long)]
+    label: Option<String>,
+}
+
+impl UntrashCli {
+    pub async fn run(&self, client: &mut GmailClient) -> Result<()> {
+        client.batch_untrash(self.label.as_deref()).await
+    }
+}
+
+/// Removes the messages given by ID that are not in the trash.
+///
+/// Searches are restricted to `TRASH`, but `--ids-from` lists are not, and
+/// restoring an archived message would add `INBOX` to it.
+pub fn skip_untrashed(client: &mut GmailClient) {
+    let outside = not_in_trash(client.messages());
+    for id in &outside {
+        log::warn!("Skipping message `{id}`, which is not in the trash");
+    }
+    client.remove_messages(&outside);
+}
+
+/// Returns the IDs of the messages that are not in the trash.
+fn not_in_trash(messages: &[MessageSummary]) -> Vec<String> {
+    messages
+        .iter()
+        .filter(|m| !m.label_ids().iter().any(|l| l == TRASH_LABEL))
+        .map(|m| m.id().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_in_trash() {
+        let messages = vec![
+            MessageSummary::new("trashed").with_labels(&["TRASH", "Label_1"]),
+            MessageSummary::new("archived").with_labels(&["Label_1"]),
+            MessageSummary::new("inbox").with_labels(&["INBOX"]),
+        ];
+
+        assert_eq!(not_in_trash(&messages), vec!["archived", "inbox"]);
+    }
+}
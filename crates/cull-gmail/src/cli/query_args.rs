@@ -0,0 +1,268 @@
+//! # Query Filter Arguments
+//!
+//! Typed filter flags that are translated into Gmail search operators and
+//! combined with a raw `--query` string into a single `q` parameter.
+//!
+//! | Flag | Gmail operator |
+//! |------|----------------|
+//! | `--from <ADDR>` | `from:` (repeated flags are ORed) |
+//! | `--to <ADDR>` | `to:` (repeated flags are ORed) |
+//! | `--older-than <AGE>` | `older_than:` |
+//! | `--newer-than <AGE>` | `newer_than:` |
+//! | `--larger <SIZE>` | `larger:` |
+//! | `--has-attachment` | `has:attachment` |
+//! | `--unread` | `is:unread` |
+//! | `--category <CATEGORY>` | `category:` |
+//! | `--exclude-label <LABEL>` | `-label:` |
+//!
+//! Ages are a number followed by `d`, `w`, `m` or `y`; weeks are converted to
+//! days because Gmail does not support them. Sizes are a number of bytes,
+//! optionally followed by `K` or `M`.
+
+use std::fmt;
+
+use clap::{Args, ValueEnum};
+
+/// Gmail inbox categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Category {
+    /// Messages from people and anything not in another category
+    Primary,
+    /// Messages from social networks and media-sharing sites
+    Social,
+    /// Deals, offers and other marketing messages
+    Promotions,
+    /// Notifications such as confirmations, receipts, bills and statements
+    Updates,
+    /// Messages from online groups, discussion boards and mailing lists
+    Forums,
+    /// Travel and event bookings
+    Reservations,
+    /// Orders, receipts and shipping updates
+    Purchases,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default();
+        write!(f, "{name}")
+    }
+}
+
+/// Filter flags that build a Gmail search query.
+#[derive(Debug, Default, Args)]
+#[command(next_help_heading = "Filters")]
+pub struct QueryArgs {
+    /// Only messages from this sender (can be specified multiple times)
+    #[arg(long, value_name = "ADDRESS", value_parser = parse_term)]
+    from: Vec<String>,
+
+    /// Only messages to this recipient (can be specified multiple times)
+    #[arg(long, value_name = "ADDRESS", value_parser = parse_term)]
+    to: Vec<String>,
+
+    /// Only messages older than this age, e.g. 30d, 6m or 2y
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    older_than: Option<String>,
+
+    /// Only messages newer than this age, e.g. 7d or 1y
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    newer_than: Option<String>,
+
+    /// Only messages larger than this size in bytes, e.g. 500K or 5M
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    larger: Option<String>,
+
+    /// Only messages with attachments
+    #[arg(long)]
+    has_attachment: bool,
+
+    /// Only unread messages
+    #[arg(long)]
+    unread: bool,
+
+    /// Only messages in this inbox category
+    #[arg(long, value_enum)]
+    category: Option<Category>,
+
+    /// Leave out messages with this label (can be specified multiple times)
+    #[arg(long, value_name = "LABEL", value_parser = parse_term)]
+    exclude_label: Vec<String>,
+
+    /// Print the final Gmail query string to stderr
+    #[arg(long)]
+    show_query: bool,
+}
+
+impl QueryArgs {
+    /// Combines the raw `query` with the filter flags into one Gmail query.
+    ///
+    /// Returns `None` if neither a query nor any filter was given.
+    pub fn build(&self, query: Option<&str>) -> Option<String> {
+        let mut terms = Vec::new();
+
+        if let Some(query) = query.map(str::trim).filter(|q| !q.is_empty()) {
+            terms.push(query.to_string());
+        }
+        terms.extend(any_of("from", &self.from));
+        terms.extend(any_of("to", &self.to));
+        if let Some(age) = &self.older_than {
+            terms.push(format!("older_than:{age}"));
+        }
+        if let Some(age) = &self.newer_than {
+            terms.push(format!("newer_than:{age}"));
+        }
+        if let Some(size) = &self.larger {
+            terms.push(format!("larger:{size}"));
+        }
+        if self.has_attachment {
+            terms.push("has:attachment".to_string());
+        }
+        if self.unread {
+            terms.push("is:unread".to_string());
+        }
+        if let Some(category) = self.category {
+            terms.push(format!("category:{category}"));
+        }
+        for label in &self.exclude_label {
+            terms.push(format!("-label:{}", quote(label)));
+        }
+
+        (!terms.is_empty()).then(|| terms.join(" "))
+    }
+
+    /// Returns `true` if the final query should be printed to stderr.
+    pub fn show_query(&self) -> bool {
+        self.show_query
+    }
+}
+
+/// Builds a single term, or a `{...}` group that matches any of the values.
+fn any_of(operator: &str, values: &[String]) -> Option<String> {
+    let terms = values
+        .iter()
+        .map(|v| format!("{operator}:{}", quote(v)))
+        .collect::<Vec<_>>();
+    match terms.len() {
+        0 => None,
+        1 => terms.into_iter().next(),
+        _ => Some(format!("{{{}}}", terms.join(" "))),
+    }
+}
+
+/// Quotes a value that contains whitespace.
+///
+/// Values are checked by [`parse_term`], so they contain no `"`.
+fn quote(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{value}\"")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Validates an address or label, which Gmail cannot match if it contains `"`.
+fn parse_term(value: &str) -> Result<String, String> {
+    if value.contains('"') {
+        Err(format!(
+            "invalid value `{value}` (Gmail search cannot match a double quote)"
+        ))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Validates an age such as `30d`, `6m` or `2y`, converting weeks to days.
+fn parse_age(value: &str) -> Result<String, String> {
+    let value = value.trim().to_lowercase();
+    let error = || format!("invalid age `{value}` (expected a number followed by d, w, m or y)");
+
+    let (index, unit) = value.char_indices().last().ok_or_else(error)?;
+    let count = value[..index].parse::<u32>().map_err(|_| error())?;
+    match unit {
+        'd' | 'm' | 'y' => Ok(format!("{count}{unit}")),
+        'w' => count
+            .checked_mul(7)
+            .map(|days| format!("{days}d"))
+            .ok_or_else(error),
+        _ => Err(error()),
+    }
+}
+
+/// Validates a size in bytes with an optional `K` or `M` suffix.
+fn parse_size(value: &str) -> Result<String, String> {
+    let value = value.trim().to_uppercase();
+    let digits = value.strip_suffix(['K', 'M']).unwrap_or(&value);
+    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        Ok(value)
+    } else {
+        Err(format!(
+            "invalid size `{value}` (expected bytes, optionally followed by K or M)"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_without_filters() {
+        let args = QueryArgs::default();
+        assert_eq!(args.build(None), None);
+        assert_eq!(args.build(Some("  ")), None);
+        assert_eq!(args.build(Some("in:inbox")), Some("in:inbox".to_string()));
+    }
+
+    #[test]
+    fn test_build_combines_query_and_filters() {
+        let args = QueryArgs {
+            from: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+            to: vec!["me@example.com".to_string()],
+            older_than: Some("6m".to_string()),
+            larger: Some("5M".to_string()),
+            has_attachment: true,
+            unread: true,
+            category: Some(Category::Promotions),
+            exclude_label: vec!["Keep Forever".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args.build(Some("subject:invoice")).unwrap(),
+            "subject:invoice {from:a@example.com from:b@example.com} to:me@example.com \
+             older_than:6m larger:5M has:attachment is:unread category:promotions \
+             -label:\"Keep Forever\""
+        );
+    }
+
+    #[test]
+    fn test_parse_term_rejects_quotes() {
+        assert_eq!(parse_term("Keep Forever"), Ok("Keep Forever".to_string()));
+        assert!(parse_term("Keep \"Forever\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d"), Ok("30d".to_string()));
+        assert_eq!(parse_age("2Y"), Ok("2y".to_string()));
+        assert_eq!(parse_age("3w"), Ok("21d".to_string()));
+        assert!(parse_age("6").is_err());
+        assert!(parse_age("m").is_err());
+        assert!(parse_age("6h").is_err());
+        assert!(parse_age("6é").is_err());
+        assert!(parse_age("").is_err());
+        assert!(parse_age("999999999w").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("5m"), Ok("5M".to_string()));
+        assert_eq!(parse_size("1048576"), Ok("1048576".to_string()));
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("5G").is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! # Interactive Review
+//!
+//! Shows the messages selected for trash or delete in a multi-select list
+//! (date, sender, subject) so individual messages can be kept before the action
+//! runs. All messages start selected; deselected messages are removed from the
+//! client's message list and left untouched.
+
+use clap::Args;
+use cull_gmail::{EolAction, Error, GmailClient, MessageList, MessageSummary, Result};
+use dialoguer::{Confirm, MultiSelect};
+
+/// Number of lines shown at once in the review list.
+const PAGE_LENGTH: usize = 20;
+
+/// Options for reviewing messages before a destructive action.
+#[derive(Debug, Default, Args)]
+pub struct ReviewArgs {
+    /// Review the messages in a checklist and deselect any to keep
+    #[arg(long)]
+    pub review: bool,
+}
+
+/// Lets the user deselect messages before `action` is applied.
+///
+/// Returns the addresses of the senders whose messages were kept. Cancelling
+/// the review with `Esc` keeps every message.
+pub async fn review_messages(client: &mut GmailClient, action: EolAction) -> Result<Vec<String>> {
+    if client.messages().is_empty() {
+        return Ok(Vec::new());
+    }
+    client.fetch_metadata().await?;
+
+    let items = review_items(client.messages());
+
+    let selection = MultiSelect::new()
+        .with_prompt(format!(
+            "Messages to {action} (space deselects a message to keep it, enter confirms, esc cancels)"
+        ))
+        .items(&items)
+        .defaults(&vec![true; items.len()])
+        .max_length(PAGE_LENGTH)
+        .interact_opt()
+        .map_err(|e| Error::PromptFailed(e.to_string()))?;
+
+    let Some(selection) = selection else {
+        log::warn!("Review cancelled, no messages will be changed");
+        client.remove_messages(&client.message_ids());
+        return Ok(Vec::new());
+    };
+
+    let (kept_ids, kept_senders) = kept_messages(client.messages(), &selection);
+
+    log::info!(
+        "Keeping {} of {} reviewed message(s)",
+        kept_ids.len(),
+        items.len()
+    );
+    client.remove_messages(&kept_ids);
+
+    Ok(kept_senders)
+}
+
+/// Formats a review line (date, sender, subject) for each message.
+fn review_items(messages: &[MessageSummary]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|m| {
+            let date = m
+                .date_time()
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| m.date().to_string());
+            let sender = m.from().unwrap_or("(unknown sender)");
+            format!("{date}  {sender}  {}", m.subject())
+        })
+        .collect()
+}
+
+/// Returns the IDs of the messages not in `selection`, and their distinct senders.
+fn kept_messages(messages: &[MessageSummary], selection: &[usize]) -> (Vec<String>, Vec<String>) {
+    let kept = messages
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !selection.contains(i))
+        .map(|(_, m)| m)
+        .collect::<Vec<_>>();
+
+    let ids = kept.iter().map(|m| m.id().to_string()).collect();
+    let senders = unique_senders(kept.iter().filter_map(|m| m.sender_address()));
+    (ids, senders)
+}
+
+/// Asks whether the senders of kept messages should be protected in future.
+pub fn confirm_protect(senders: &[String]) -> Result<bool> {
+    if senders.is_empty() {
+        return Ok(false);
+    }
+    Confirm::new()
+        .with_prompt(format!(
+            "Protect {} sender(s) from this rule in future? ({})",
+            senders.len(),
+            senders.join(", ")
+        ))
+        .default(false)
+        .interact()
+        .map_err(|e| Error::PromptFailed(e.to_string()))
+}
+
+/// Returns the distinct sender addresses in order of first appearance.
+fn unique_senders(addresses: impl Iterator<Item = String>) -> Vec<String> {
+    let mut senders: Vec<String> = Vec::new();
+    for address in addresses {
+        if !senders.contains(&address) {
+            senders.push(address);
+        }
+    }
+    senders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_senders() {
+        let from = [
+            "Boss <Boss@example.com>",
+            "hr@example.com",
+            "\"The Boss\" <boss@example.com>",
+        ];
+
+        assert_eq!(
+            unique_senders(
+                from.into_iter()
+                    .filter_map(|f| MessageSummary::new("m").with_from(f).sender_address())
+            ),
+            vec!["boss@example.com", "hr@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_review_items() {
+        let messages = [
+            MessageSummary::new("a").with_from("News <news@example.com>"),
+            MessageSummary::new("b"),
+        ];
+
+        let items = review_items(&messages);
+        assert_eq!(items.len(), 2);
+        assert!(items[0].contains("  News <news@example.com>  "));
+        assert!(items[1].contains("(unknown sender)"));
+    }
+
+    #[test]
+    fn test_kept_messages_are_the_deselected_ones() {
+        let messages = [
+            MessageSummary::new("a").with_from("boss@example.com"),
+            MessageSummary::new("b").with_from("news@example.com"),
+            MessageSummary::new("c").with_from("Boss <BOSS@example.com>"),
+            MessageSummary::new("d"),
+        ];
+
+        let (ids, senders) = kept_messages(&messages, &[1]);
+        assert_eq!(ids, vec!["a", "c", "d"]);
+        assert_eq!(senders, vec!["boss@example.com"]);
+
+        let (ids, senders) = kept_messages(&messages, &[0, 1, 2, 3]);
+        assert!(ids.is_empty() && senders.is_empty());
+    }
+
+    #[test]
+    fn test_confirm_protect_without_senders_does_not_prompt() {
+        assert!(!confirm_protect(&[]).unwrap());
+    }
+}
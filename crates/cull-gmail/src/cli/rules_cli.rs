@@ -28,7 +28,15 @@
 //! ### Available Subcommands
 //!
 //! - **`config`**: Configure retention rules, labels, and actions
-//! - **`run`**: Execute configured rules with optional safety controls
+//! - **`run`**: Preview the messages each rule would act on
+//! - **`plan`**: Resolve the rules into message IDs, optionally saved for `apply`
+//! - **`apply`**: Act on exactly the messages recorded in a plan
+//! - **`validate`**: Check a rules file without contacting Gmail
+//! - **`test`**: Check the rules treat fixture messages as expected, offline
+//! - **`filters`**: Keep Gmail filters that label incoming mail in step with the rules
+//! - **`export`**: Write the rules as TOML or Gmail filters XML
+//! - **`import`**: Add rules for the labels applied by Gmail filters XML
+//! - **`explain`**: Show the labels each rule applies to, expanding wildcards
 //!
 //! ## Rule Configuration
 //!
@@ -62,7 +70,8 @@
 //!
 //! ## Safety Features
 //!
-//! - **Dry-run mode**: Default execution mode prevents accidental data loss
+//! - **Plan and apply**: Messages are only changed by applying a reviewed plan
+//! - **Stale plan checks**: Old plans and plans for changed rules are refused
 //! - **Rule validation**: Configuration validation before execution
 //! - **Comprehensive logging**: Detailed operation tracking
 //! - **Error isolation**: Individual rule failures don't stop processing
@@ -83,14 +92,12 @@
 //!
 //! ### Execute Rules
 //! ```bash
-//! # Dry-run (safe preview)
+//! # Preview
 //! cull-gmail rules run
 //!
-//! # Execute for real
-//! cull-gmail rules run --execute
-//!
-//! # Execute only specific action types
-//! cull-gmail rules run --execute --skip-delete
+//! # Record the messages to act on, review them, then act
+//! cull-gmail rules plan --out plan.json
+//! cull-gmail rules apply plan.json
 //! ```
 //!
 //! ## Integration
@@ -104,14 +111,28 @@
 use clap::{Parser, Subcommand};
 use std::path::{Path, PathBuf};
 
+mod apply_cli;
 mod config_cli;
+mod explain_cli;
+mod export_cli;
+mod filters_cli;
+mod import_cli;
+mod plan_cli;
 mod run_cli;
+mod test_cli;
 mod validate_cli;
 
-use cull_gmail::{GmailClient, Result, Rules};
+use cull_gmail::{GmailClient, GmailScope, Result, Rules};
 
+use apply_cli::ApplyCli;
 use config_cli::ConfigCli;
+use explain_cli::ExplainCli;
+use export_cli::ExportCli;
+use filters_cli::FiltersCli;
+use import_cli::ImportCli;
+use plan_cli::PlanCli;
 use run_cli::RunCli;
+use test_cli::TestCli;
 use validate_cli::ValidateCli;
 
 /// Available subcommands for rules management and execution.
@@ -129,8 +150,8 @@ use validate_cli::ValidateCli;
 ///
 /// Typical usage follows this pattern:
 /// 1. Use `config` to set up rules, labels, and actions
-/// 2. Use `run` to execute rules with dry-run testing
-/// 3. Use `run --execute` for live rule execution
+/// 2. Use `run` to preview the messages the rules select
+/// 3. Use `plan --out` and `apply` to act on them
 #[derive(Subcommand, Debug)]
 enum SubCmds {
     /// Configure Gmail message retention rules, labels, and actions.
@@ -147,28 +168,90 @@ enum SubCmds {
     #[clap(name = "config")]
     Config(ConfigCli),
 
-    /// Execute configured retention rules with optional safety controls.
-    ///
-    /// Provides rule execution functionality with comprehensive safety features:
-    /// - **Dry-run mode**: Preview rule effects without making changes
-    /// - **Selective execution**: Skip specific action types (trash/delete)
-    /// - **Error handling**: Continue processing despite individual failures
-    /// - **Progress tracking**: Detailed logging of rule execution
+    /// Preview the messages each rule would act on.
     ///
-    /// The run subcommand is the primary interface for automated message
-    /// lifecycle management based on configured retention policies.
+    /// Shows, for each label, the action and the number of messages the rule
+    /// selects with sample subjects. Nothing is changed; use `plan` and
+    /// `apply` to act on the messages.
     #[clap(name = "run")]
     Run(RunCli),
 
+    /// Resolve the rules into the messages they would act on.
+    ///
+    /// Lists the messages each rule selects per label, with counts and
+    /// sample subjects. With `--out` the message IDs are saved as a JSON
+    /// plan for `apply`. `--review` lets messages be deselected first.
+    #[clap(name = "plan")]
+    Plan(PlanCli),
+
+    /// Carry out the actions in a plan written by `plan --out`.
+    ///
+    /// Acts on exactly the messages recorded in the plan. Plans older than
+    /// `--max-age` hours, or made from rules that have changed since, are
+    /// refused.
+    #[clap(name = "apply")]
+    Apply(ApplyCli),
+
     /// Validate a rules file without executing any actions.
     ///
     /// Checks each rule for a non-empty label set, a valid retention period,
     /// and a valid action. Also reports duplicate labels across rules.
     ///
+    /// With `--online` the labels must also exist in the mailbox, and rules
+    /// on system labels such as `SENT`, rules matching no messages and rules
+    /// matching more than `--max-share` percent of the mailbox are warned
+    /// about.
+    ///
     /// Exits 0 if all rules are valid, non-zero otherwise. Does not require
-    /// Gmail API credentials.
+    /// Gmail API credentials unless `--online` is given.
     #[clap(name = "validate")]
     Validate(ValidateCli),
+
+    /// Check the rules against fixture messages without contacting Gmail.
+    ///
+    /// Runs the synthetic messages in a TOML fixture file through the rules
+    /// as of the file's `now` date, and compares each outcome (`keep`,
+    /// `trash` or `delete`) with the one expected.
+    ///
+    /// Exits 0 if every message has the expected outcome, non-zero
+    /// otherwise. Does not require Gmail API credentials.
+    #[clap(name = "test")]
+    Test(TestCli),
+
+    /// Manage the Gmail filters that label incoming mail for rules.
+    ///
+    /// Rules with sender or query criteria get a filter that applies their
+    /// labels to matching incoming messages. `sync` creates, updates and
+    /// removes those filters; `list` and `remove` only touch filters created
+    /// by `sync`.
+    #[clap(name = "filters")]
+    Filters(FiltersCli),
+
+    /// Export the rules as TOML or as Gmail filters XML.
+    ///
+    /// `--format gmail-filters` writes a `mailFilters.xml` feed with a filter
+    /// for each label of each rule with sender or query criteria, ready to be
+    /// imported in Gmail's settings.
+    #[clap(name = "export")]
+    Export(ExportCli),
+
+    /// Add rules for the labels applied by filters in Gmail filters XML.
+    ///
+    /// Each filter that applies a label becomes a rule for that label with
+    /// the given retention period, keeping the filter's criteria. Labels
+    /// that already have a rule are skipped.
+    #[clap(name = "import")]
+    Import(ImportCli),
+
+    /// Show each rule and the Gmail labels it applies to.
+    ///
+    /// Wildcard labels (`Vendors/*` for one level, `Vendors/**` for any
+    /// depth) are listed with the labels they expand to. Matched labels with
+    /// a rule of their own are marked, as that rule applies to them instead.
+    /// With `--fixtures` the labels of the messages in a `test` fixture file
+    /// are used instead of Gmail's.
+    #[clap(name = "explain")]
+    Explain(ExplainCli),
 }
 
 /// Command-line interface for Gmail message retention rule management.
@@ -279,20 +362,63 @@ impl RulesCli {
         self.run_with_rules_path(client, None).await
     }
 
-    /// If the selected subcommand is `validate`, runs it and returns `Some(result)`.
+    /// Runs the subcommand if it only needs the rules file and returns
+    /// `Some(result)`.
     ///
-    /// Returns `None` for all other subcommands so the caller can proceed with
-    /// normal config/client initialisation. Validate is the only subcommand that
-    /// does not require a Gmail client or application config.
-    pub fn run_if_validate(&self) -> Option<Result<()>> {
-        if let SubCmds::Validate(validate_cli) = &self.sub_command {
-            let mut rules_path: Option<&Path> = None;
-            if let Some(p) = &self.rules {
-                rules_path = Some(p.as_path());
+    /// `config`, `export`, `import`, `test`, `validate` without `--online` and
+    /// `explain --fixtures` work on the rules file alone, so they need no
+    /// Gmail client, credentials or network access. Returns `None` for all
+    /// other subcommands so the caller can proceed with client initialisation.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules_path` - Optional path to the rules file, overridden by the
+    ///   path given on the command line
+    pub fn run_local(&self, rules_path: Option<&Path>) -> Option<Result<()>> {
+        let rules_path = self.rules.as_deref().or(rules_path);
+
+        // Validate and test must not fall back to creating default rules when
+        // the file is missing.
+        let result = match &self.sub_command {
+            SubCmds::Validate(validate_cli) if !validate_cli.online() => {
+                validate_cli.run(rules_path)
+            }
+            SubCmds::Test(test_cli) => test_cli.run(rules_path),
+            SubCmds::Config(config_cli) => {
+                get_rules_from(rules_path).and_then(|r| config_cli.run(r))
+            }
+            SubCmds::Export(export_cli) => {
+                get_rules_from(rules_path).and_then(|r| export_cli.run(r))
             }
-            Some(validate_cli.run(rules_path))
-        } else {
-            None
+            SubCmds::Import(import_cli) => {
+                get_rules_from(rules_path).and_then(|r| import_cli.run(r, rules_path))
+            }
+            SubCmds::Explain(explain_cli) => {
+                let fixtures = explain_cli.fixtures()?;
+                get_rules_from(rules_path).and_then(|r| explain_cli.run_with_fixtures(fixtures, r))
+            }
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Returns the OAuth2 scope needed to run the rules command.
+    ///
+    /// Only `apply` touches messages. Its scope is derived from the actions in
+    /// the plan: full access only when the plan permanently deletes messages.
+    /// `filters sync` needs modify access to create missing labels.
+    pub fn scope(&self) -> Result<GmailScope> {
+        match &self.sub_command {
+            SubCmds::Apply(apply_cli) => apply_cli.scope(),
+            SubCmds::Filters(filters_cli) => Ok(filters_cli.scope()),
+            SubCmds::Config(_)
+            | SubCmds::Run(_)
+            | SubCmds::Plan(_)
+            | SubCmds::Validate(_)
+            | SubCmds::Test(_)
+            | SubCmds::Export(_)
+            | SubCmds::Import(_)
+            | SubCmds::Explain(_) => Ok(GmailScope::ReadOnly),
         }
     }
 
@@ -313,18 +439,29 @@ impl RulesCli {
         }
         log::info!("Rules path: {rules_path:?}");
 
-        // Validate does not need a GmailClient and must not fall back to
-        // creating default rules when the file is missing.
+        if let Some(result) = self.run_local(rules_path) {
+            return result;
+        }
+
+        // Only `validate --online` is left of the commands that must not fall
+        // back to creating default rules.
         if let SubCmds::Validate(validate_cli) = &self.sub_command {
-            return validate_cli.run(rules_path);
+            return validate_cli.run_online(client, rules_path).await;
         }
 
         let rules = get_rules_from(rules_path)?;
 
         match &self.sub_command {
-            SubCmds::Config(config_cli) => config_cli.run(rules),
             SubCmds::Run(run_cli) => run_cli.run(client, rules).await,
-            SubCmds::Validate(_) => unreachable!("handled above"),
+            SubCmds::Plan(plan_cli) => plan_cli.run(client, rules).await,
+            SubCmds::Apply(apply_cli) => apply_cli.run(client, rules).await,
+            SubCmds::Filters(filters_cli) => filters_cli.run(client, rules, rules_path).await,
+            SubCmds::Explain(explain_cli) => explain_cli.run(client.label_map(), rules),
+            SubCmds::Config(_)
+            | SubCmds::Export(_)
+            | SubCmds::Import(_)
+            | SubCmds::Test(_)
+            | SubCmds::Validate(_) => unreachable!("handled above"),
         }
     }
 }
@@ -405,9 +542,8 @@ pub fn get_rules_from(path: Option<&Path>) -> Result<Rules> {
         Ok(c) => Ok(c),
         Err(_) => {
             log::warn!("Configuration not found, creating default config.");
-            let rules = Rules::new();
-            rules.save_to(path)?;
-            Ok(rules)
+            Rules::new().save_to(path)?;
+            Rules::load_from(path)
         }
     }
 }
@@ -0,0 +1,45 @@
+//! Apply subcommand for Gmail retention rules.
+//!
+//! Carries out a plan written by `rules plan --out`. The plan is refused if
+//! it is older than `--max-age` or the rules have changed since it was made,
+//! as its message lists may no longer match what the rules select.
+
+use std::{fs, path::PathBuf};
+
+use chrono::TimeDelta;
+use clap::Parser;
+use cull_gmail::{Error, GmailClient, GmailScope, Plan, Result, Rules};
+
+use crate::{apply_plan, print_plan};
+
+/// Carry out the actions in a plan written by `rules plan --out`.
+#[derive(Debug, Parser)]
+pub struct ApplyCli {
+    /// Plan file written by `rules plan --out`
+    #[arg(value_name = "FILE")]
+    plan: PathBuf,
+    /// Refuse plans made more than this many hours ago
+    #[arg(short, long, value_name = "HOURS", default_value_t = 24)]
+    max_age: u32,
+}
+
+impl ApplyCli {
+    pub async fn run(&self, client: &mut GmailClient, rules: Rules) -> Result<()> {
+        let plan = self.load_plan()?;
+        plan.check(&rules, TimeDelta::hours(self.max_age.into()))?;
+
+        print_plan(&plan);
+        apply_plan(client, &rules, &plan).await
+    }
+
+    /// Returns the OAuth2 scope needed to apply the plan.
+    pub(crate) fn scope(&self) -> Result<GmailScope> {
+        Ok(self.load_plan()?.scope())
+    }
+
+    fn load_plan(&self) -> Result<Plan> {
+        let json = fs::read_to_string(&self.plan)
+            .map_err(|e| Error::FileIo(format!("{}: {e}", self.plan.display())))?;
+        Plan::from_json(&json)
+    }
+}
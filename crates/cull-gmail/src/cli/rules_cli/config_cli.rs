@@ -3,6 +3,9 @@ use clap::{Parser, Subcommand};
 mod action_rule_cli;
 mod add_label_cli;
 mod add_rule_cli;
+mod export_rule_cli;
+mod filter_rule_cli;
+mod limit_rule_cli;
 mod list_label_cli;
 mod remove_label_cli;
 mod rm_rule_cli;
@@ -10,6 +13,9 @@ mod rm_rule_cli;
 use action_rule_cli::ActionRuleCli;
 use add_label_cli::AddLabelCli;
 use cull_gmail::{Result, Rules};
+use export_rule_cli::ExportRuleCli;
+use filter_rule_cli::FilterRuleCli;
+use limit_rule_cli::LimitRuleCli;
 use list_label_cli::ListLabelCli;
 use remove_label_cli::RemoveLabelCli;
 
@@ -34,6 +40,13 @@ enum SubCmds {
     // #[clap(name = "set-action-on-rule", subcommand_help_heading = "Rules")]
     #[clap(name = "set-action-on-rule")]
     ActionRule(ActionRuleCli),
+    #[clap(name = "set-export-on-rule")]
+    ExportRule(ExportRuleCli),
+    #[clap(name = "set-limit-on-rule")]
+    LimitRule(LimitRuleCli),
+    /// Set the senders or query of the Gmail filter that labels mail for a rule
+    #[clap(name = "set-filter-on-rule")]
+    FilterRule(FilterRuleCli),
     /// List the labels associated with a rule
     // #[clap(name = "list-labels", subcommand_help_heading = "Label")]
     #[clap(name = "list-labels")]
@@ -62,6 +75,9 @@ impl ConfigCli {
     pub fn run(&self, rules: Rules) -> Result<()> {
         match &self.sub_command {
             SubCmds::ActionRule(action_cli) => action_cli.run(rules),
+            SubCmds::ExportRule(export_cli) => export_cli.run(rules),
+            SubCmds::LimitRule(limit_cli) => limit_cli.run(rules),
+            SubCmds::FilterRule(filter_cli) => filter_cli.run(rules),
             SubCmds::ListRules => rules.list_rules(),
             SubCmds::AddRule(add_cli) => add_cli.run(rules),
             SubCmds::RemoveRule(rm_cli) => rm_cli.run(rules),
@@ -0,0 +1,25 @@
+use clap::Parser;
+use cull_gmail::{Error, Result, Rules};
+
+#[derive(Debug, Parser)]
+pub struct ExportRuleCli {
+    /// Id of the rule on which the export applies
+    #[clap(short, long)]
+    id: usize,
+    /// Directory to archive messages to (as EML files) before they are deleted
+    #[clap(short, long, required_unless_present = "off", conflicts_with = "off")]
+    dir: Option<String>,
+    /// Stop exporting messages before they are deleted
+    #[clap(long)]
+    off: bool,
+}
+
+impl ExportRuleCli {
+    pub fn run(&self, mut config: Rules) -> Result<()> {
+        if config.get_rule(self.id).is_none() {
+            return Err(Error::RuleNotFound(self.id));
+        }
+
+        config.set_export_on_rule(self.id, self.dir.as_deref())
+    }
+}
@@ -0,0 +1,31 @@
+use clap::Parser;
+use cull_gmail::{Error, Result, Rules};
+
+#[derive(Debug, Parser)]
+pub struct FilterRuleCli {
+    /// Id of the rule whose labels the filter applies
+    #[clap(short, long)]
+    id: usize,
+    /// Sender whose incoming mail gets the rule's labels (repeatable)
+    #[clap(short = 'f', long = "from", value_name = "ADDRESS")]
+    senders: Vec<String>,
+    /// Gmail search query incoming mail must match
+    #[clap(long, allow_hyphen_values = true)]
+    query: Option<String>,
+    /// Remove the filter criteria from the rule
+    #[clap(long, conflicts_with_all = ["senders", "query"])]
+    off: bool,
+}
+
+impl FilterRuleCli {
+    pub fn run(&self, mut config: Rules) -> Result<()> {
+        if config.get_rule(self.id).is_none() {
+            return Err(Error::RuleNotFound(self.id));
+        }
+        if !self.off && self.senders.is_empty() && self.query.is_none() {
+            return Err(Error::NoFilterCriteria);
+        }
+
+        config.set_filter_on_rule(self.id, &self.senders, self.query.as_deref())
+    }
+}
@@ -0,0 +1,31 @@
+use clap::Parser;
+use cull_gmail::{Error, Result, Rules};
+
+#[derive(Debug, Parser)]
+pub struct LimitRuleCli {
+    /// Id of the rule on which the limit applies
+    #[clap(short, long)]
+    id: usize,
+    /// Maximum number of messages the rule processes in one run
+    #[clap(
+        short,
+        long,
+        value_parser = clap::value_parser!(u32).range(1..),
+        required_unless_present = "off",
+        conflicts_with = "off"
+    )]
+    max_messages: Option<u32>,
+    /// Remove the limit so the rule processes all matching messages
+    #[clap(long)]
+    off: bool,
+}
+
+impl LimitRuleCli {
+    pub fn run(&self, mut config: Rules) -> Result<()> {
+        if config.get_rule(self.id).is_none() {
+            return Err(Error::RuleNotFound(self.id));
+        }
+
+        config.set_max_messages_on_rule(self.id, self.max_messages)
+    }
+}
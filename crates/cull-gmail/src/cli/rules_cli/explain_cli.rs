@@ -0,0 +1,141 @@
+//! Explain subcommand for Gmail retention rules.
+//!
+//! Prints each rule with the labels it acts on. Wildcard labels such as
+//! `Vendors/*` are shown with the Gmail labels they expand to, so the effect
+//! of a pattern can be checked before `rules run`.
+//!
+//! With `--fixtures` the labels of the messages in a `rules test` fixture file
+//! stand in for the mailbox's labels, and Gmail is not contacted.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use cull_gmail::{Result, Rules, expand_label_pattern};
+
+use super::test_cli::fixture_labels;
+
+/// Show each rule and the Gmail labels it applies to.
+#[derive(Debug, Parser)]
+pub struct ExplainCli {
+    /// Only explain the rule with this id
+    #[arg(short, long)]
+    id: Option<usize>,
+    /// Expand labels against the messages in a `rules test` fixture file instead of Gmail
+    #[arg(short, long, value_name = "FILE")]
+    fixtures: Option<PathBuf>,
+}
+
+impl ExplainCli {
+    /// Returns the fixture file to explain against, if the mailbox is not used.
+    pub fn fixtures(&self) -> Option<&Path> {
+        self.fixtures.as_deref()
+    }
+
+    /// Explains the rules against the labels in the fixture file.
+    pub fn run_with_fixtures(&self, fixtures: &Path, rules: Rules) -> Result<()> {
+        self.run(&fixture_labels(fixtures)?, rules)
+    }
+
+    /// Explains the rules against a map of label names to label IDs.
+    pub fn run(&self, label_map: &BTreeMap<String, String>, rules: Rules) -> Result<()> {
+        if let Some(id) = self.id
+            && rules.get_rule(id).is_none()
+        {
+            return Err(cull_gmail::Error::RuleNotFound(id));
+        }
+
+        let all_rules = rules.all_rules();
+        let label_rules = all_rules
+            .iter()
+            .flat_map(|rule| rule.labels().into_iter().map(|label| (label, rule.id())))
+            .collect::<BTreeMap<_, _>>();
+
+        for rule in all_rules {
+            if self.id.is_some_and(|id| id != rule.id()) {
+                continue;
+            }
+
+            println!("{}", rule.describe());
+            for label in rule.labels() {
+                let expanded = expand_label_pattern(label_map, &label);
+                let exists = label_map.contains_key(&label);
+                println!(
+                    "  {}",
+                    describe_label(&label, &expanded, exists, &label_rules)
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes the Gmail labels a rule label stands for.
+///
+/// `expanded` holds the labels matched by `label`; `label_rules` maps each
+/// label named in the rules to the id of its rule.
+fn describe_label(
+    label: &str,
+    expanded: &[String],
+    exists: bool,
+    label_rules: &BTreeMap<String, usize>,
+) -> String {
+    if expanded == [label] {
+        return if exists {
+            label.to_string()
+        } else {
+            format!("{label} (not in Gmail)")
+        };
+    }
+    if expanded.is_empty() {
+        return format!("{label} -> no matching labels");
+    }
+
+    let matches = expanded
+        .iter()
+        .map(|name| match label_rules.get(name) {
+            Some(id) => format!("{name} (own rule #{id})"),
+            None => name.clone(),
+        })
+        .collect::<Vec<_>>();
+    format!("{label} -> {}", matches.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_label() {
+        let label_rules = BTreeMap::from([
+            ("Vendors/*".to_string(), 1),
+            ("Vendors/Acme".to_string(), 2),
+        ]);
+        let expanded = ["Vendors/Acme", "Vendors/Globex"].map(str::to_string);
+
+        assert_eq!(
+            describe_label("Vendors/*", &expanded, false, &label_rules),
+            "Vendors/* -> Vendors/Acme (own rule #2), Vendors/Globex"
+        );
+        assert_eq!(
+            describe_label("Clients/*", &[], false, &label_rules),
+            "Clients/* -> no matching labels"
+        );
+        assert_eq!(
+            describe_label(
+                "Vendors/Acme",
+                &["Vendors/Acme".to_string()],
+                true,
+                &label_rules
+            ),
+            "Vendors/Acme"
+        );
+        assert_eq!(
+            describe_label("Archive", &["Archive".to_string()], false, &label_rules),
+            "Archive (not in Gmail)"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Export subcommand for Gmail retention rules.
+//!
+//! Writes the rules file as TOML, or the rules with sender or query criteria
+//! as a Gmail filters feed (`mailFilters.xml`) that can be imported in Gmail's
+//! settings.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::{Parser, ValueEnum};
+use cull_gmail::{Error, Result, Rules};
+
+/// Formats the rules can be exported in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum RulesFormat {
+    /// The rules file itself
+    #[default]
+    Toml,
+    /// Gmail filters XML (`mailFilters.xml`)
+    GmailFilters,
+}
+
+/// Export the rules to stdout or a file.
+#[derive(Debug, Parser)]
+pub struct ExportCli {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = RulesFormat::Toml)]
+    format: RulesFormat,
+    /// File to write instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl ExportCli {
+    pub fn run(&self, rules: Rules) -> Result<()> {
+        let content = match self.format {
+            RulesFormat::Toml => {
+                toml::to_string(&rules).map_err(|e| Error::SerializationError(e.to_string()))?
+            }
+            RulesFormat::GmailFilters => rules.to_mail_filters_xml(),
+        };
+
+        match &self.output {
+            Some(path) => {
+                fs::write(path, content)?;
+                log::info!("Rules exported to `{}`", path.display());
+            }
+            None => io::stdout().lock().write_all(content.as_bytes())?,
+        }
+
+        Ok(())
+    }
+}
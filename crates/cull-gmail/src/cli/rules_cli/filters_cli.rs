@@ -0,0 +1,345 @@
+//! Gmail filter subcommands for retention rules.
+//!
+//! Rules with sender or query criteria (see `rules config set-filter-on-rule`)
+//! get a Gmail filter that applies the rule's labels to incoming mail. The ID
+//! of each filter is recorded on its rule, so only filters created here are
+//! ever updated or removed. A rule whose filter already exists in Gmail, for
+//! example one added by `rules import`, adopts that filter instead of creating
+//! a duplicate.
+
+use std::{fmt, path::Path};
+
+use clap::{Parser, Subcommand};
+use cull_gmail::{FilterManager, GmailClient, GmailScope, LabelManager, Result, RuleFilter, Rules};
+
+/// Filter management subcommands.
+#[derive(Debug, Subcommand)]
+enum FiltersCmd {
+    /// Create, update or remove Gmail filters to match the rules
+    Sync {
+        /// Show the changes without making them
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+    /// List the Gmail filters managed for the rules
+    List,
+    /// Remove the Gmail filters managed for the rules
+    Remove {
+        /// Only remove the filter of this rule
+        #[arg(short, long)]
+        id: Option<usize>,
+    },
+}
+
+/// Manage the Gmail filters that label incoming mail for rules.
+#[derive(Debug, Parser)]
+pub struct FiltersCli {
+    #[command(subcommand)]
+    sub_command: FiltersCmd,
+}
+
+/// What `sync` does with a rule's filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FilterChange {
+    Keep,
+    Create,
+    Update,
+    Remove,
+    /// Record the ID of an identical filter that already exists in Gmail
+    Adopt(String),
+}
+
+impl fmt::Display for FilterChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterChange::Keep => write!(f, "keep"),
+            FilterChange::Create => write!(f, "create"),
+            FilterChange::Update => write!(f, "update"),
+            FilterChange::Remove => write!(f, "remove"),
+            FilterChange::Adopt(id) => write!(f, "adopt existing filter {id} as the"),
+        }
+    }
+}
+
+impl FiltersCli {
+    pub async fn run(
+        &self,
+        client: &mut GmailClient,
+        mut rules: Rules,
+        rules_path: Option<&Path>,
+    ) -> Result<()> {
+        let mut changed = false;
+        let result = match &self.sub_command {
+            FiltersCmd::Sync { dry_run } => sync(client, &mut rules, !dry_run, &mut changed).await,
+            FiltersCmd::List => list(client, &rules).await,
+            FiltersCmd::Remove { id } => remove(client, &mut rules, *id, &mut changed).await,
+        };
+
+        // Record the filters changed so far, even if a later one failed.
+        if changed {
+            rules.save_to(rules_path)?;
+        }
+        result
+    }
+
+    /// Returns the OAuth2 scope needed for the selected subcommand.
+    ///
+    /// Filters themselves need Gmail's settings scope, which is requested
+    /// when they are first accessed. `sync` also creates missing labels.
+    pub(crate) fn scope(&self) -> GmailScope {
+        match self.sub_command {
+            FiltersCmd::Sync { dry_run: false } => GmailScope::Modify,
+            _ => GmailScope::ReadOnly,
+        }
+    }
+}
+
+/// Brings the Gmail filters in line with the rules' criteria.
+async fn sync(
+    client: &mut GmailClient,
+    rules: &mut Rules,
+    apply: bool,
+    changed: &mut bool,
+) -> Result<()> {
+    let all_rules = rules.all_rules();
+
+    if apply {
+        let labels = all_rules
+            .iter()
+            .filter_map(|rule| rule.filter())
+            .flat_map(|filter| filter.labels)
+            .collect::<Vec<_>>();
+        for label in client.create_missing_labels(&labels).await? {
+            println!("Created label `{label}`");
+        }
+    }
+
+    // Filters that already exist are adopted rather than created again
+    let existing = client.list_filters().await?;
+
+    for rule in all_rules {
+        let id = rule.id();
+        let current = match rule.filter_id() {
+            Some(filter_id) => client.get_filter(filter_id).await?,
+            None => None,
+        };
+        let desired = rule.filter();
+        let Some(change) = filter_change(
+            desired.as_ref(),
+            current.as_ref(),
+            rule.filter_id().is_some(),
+            &existing,
+        ) else {
+            continue;
+        };
+
+        if change == FilterChange::Keep {
+            log::info!("Filter for rule #{id} is up to date");
+            continue;
+        }
+        if !apply {
+            println!("Would {change} filter for rule #{id}");
+            continue;
+        }
+
+        let new_id = match (&change, &desired) {
+            (FilterChange::Adopt(filter_id), _) => Some(filter_id.clone()),
+            (_, Some(filter)) => Some(client.create_filter(filter).await?),
+            (_, None) => None,
+        };
+        if let Some(old_id) = rule.filter_id()
+            && new_id.as_deref() != Some(old_id)
+        {
+            client.delete_filter(old_id).await?;
+        }
+        if new_id.as_deref() != rule.filter_id() {
+            rules.set_filter_id(id, new_id.as_deref())?;
+            *changed = true;
+        }
+        match change {
+            FilterChange::Adopt(filter_id) => {
+                println!("Filter for rule #{id}: adopted existing filter {filter_id}")
+            }
+            change => println!("Filter for rule #{id}: {change}d"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Decides what to do with a rule's filter.
+///
+/// `recorded` tells whether the rule holds a filter ID; `current` is the
+/// filter with that ID, or `None` if it no longer exists in Gmail. Instead of
+/// creating a filter, an identical one among `existing` is adopted.
+fn filter_change(
+    desired: Option<&RuleFilter>,
+    current: Option<&RuleFilter>,
+    recorded: bool,
+    existing: &[(String, RuleFilter)],
+) -> Option<FilterChange> {
+    match (desired, current) {
+        (Some(desired), Some(current)) if desired == current => Some(FilterChange::Keep),
+        (Some(desired), _) => {
+            let identical = existing.iter().find(|(_, filter)| filter == desired);
+            Some(match (identical, current) {
+                (Some((filter_id, _)), _) => FilterChange::Adopt(filter_id.clone()),
+                (None, Some(_)) => FilterChange::Update,
+                (None, None) => FilterChange::Create,
+            })
+        }
+        (None, _) if recorded => Some(FilterChange::Remove),
+        (None, _) => None,
+    }
+}
+
+/// Prints the filters recorded on the rules.
+async fn list(client: &GmailClient, rules: &Rules) -> Result<()> {
+    let mut found = false;
+    for rule in rules.all_rules() {
+        let Some(filter_id) = rule.filter_id() else {
+            continue;
+        };
+        found = true;
+
+        let id = rule.id();
+        match client.get_filter(filter_id).await? {
+            Some(filter) => println!("Rule #{id}: filter {filter_id} {}", describe(&filter)),
+            None => println!(
+                "Rule #{id}: filter {filter_id} is missing from Gmail (run `rules filters sync`)"
+            ),
+        }
+    }
+
+    if !found {
+        println!("No filters are managed for the rules");
+    }
+
+    Ok(())
+}
+
+/// Removes the filters recorded on the rules, or on the rule `id`.
+///
+/// The rules keep their criteria, so `sync` creates the filters again.
+async fn remove(
+    client: &GmailClient,
+    rules: &mut Rules,
+    id: Option<usize>,
+    changed: &mut bool,
+) -> Result<()> {
+    if let Some(id) = id
+        && rules.get_rule(id).is_none()
+    {
+        return Err(cull_gmail::Error::RuleNotFound(id));
+    }
+
+    for rule in rules.all_rules() {
+        if id.is_some_and(|id| id != rule.id()) {
+            continue;
+        }
+        let Some(filter_id) = rule.filter_id() else {
+            continue;
+        };
+
+        client.delete_filter(filter_id).await?;
+        rules.set_filter_id(rule.id(), None)?;
+        *changed = true;
+        println!("Removed filter for rule #{}", rule.id());
+    }
+
+    Ok(())
+}
+
+/// Describes the criteria and labels of a filter.
+fn describe(filter: &RuleFilter) -> String {
+    let mut criteria = Vec::new();
+    if let Some(from) = &filter.from {
+        criteria.push(format!("from `{from}`"));
+    }
+    if let Some(query) = &filter.query {
+        criteria.push(format!("matching `{query}`"));
+    }
+    format!(
+        "labels mail {} as `{}`",
+        criteria.join(" "),
+        filter.labels.join("`, `")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(from: &str) -> RuleFilter {
+        RuleFilter {
+            from: Some(from.to_string()),
+            query: None,
+            labels: vec!["retention/1-years".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_filter_change() {
+        let news = filter("news@example.com");
+        let deals = filter("deals@example.com");
+
+        assert_eq!(
+            filter_change(Some(&news), Some(&news), true, &[]),
+            Some(FilterChange::Keep)
+        );
+        assert_eq!(
+            filter_change(Some(&news), Some(&deals), true, &[]),
+            Some(FilterChange::Update)
+        );
+        assert_eq!(
+            filter_change(Some(&news), None, true, &[]),
+            Some(FilterChange::Create)
+        );
+        assert_eq!(
+            filter_change(Some(&news), None, false, &[]),
+            Some(FilterChange::Create)
+        );
+        assert_eq!(
+            filter_change(None, Some(&news), true, &[]),
+            Some(FilterChange::Remove)
+        );
+        assert_eq!(filter_change(None, None, false, &[]), None);
+    }
+
+    #[test]
+    fn test_filter_change_adopts_existing_filter() {
+        let news = filter("news@example.com");
+        let deals = filter("deals@example.com");
+        let existing = vec![
+            ("f-deals".to_string(), deals.clone()),
+            ("f-news".to_string(), news.clone()),
+        ];
+
+        // An imported rule whose filter is already in Gmail
+        assert_eq!(
+            filter_change(Some(&news), None, false, &existing),
+            Some(FilterChange::Adopt("f-news".to_string()))
+        );
+        // A recorded filter that changed to match another existing filter
+        assert_eq!(
+            filter_change(Some(&news), Some(&deals), true, &existing),
+            Some(FilterChange::Adopt("f-news".to_string()))
+        );
+        assert_eq!(
+            filter_change(Some(&news), Some(&news), true, &existing),
+            Some(FilterChange::Keep)
+        );
+        assert_eq!(
+            filter_change(Some(&filter("new@example.com")), None, false, &existing),
+            Some(FilterChange::Create)
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            describe(&filter("news@example.com")),
+            "labels mail from `news@example.com` as `retention/1-years`"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! Import subcommand for Gmail retention rules.
+//!
+//! Reads a Gmail filters feed (`mailFilters.xml`, exported from Gmail's
+//! settings) and adds a rule for each filter that applies a label, so
+//! existing filters can be turned into retention rules in one step.
+
+use std::{fs, path::Path, path::PathBuf};
+
+use clap::Parser;
+use cull_gmail::{Error, MessageAge, Result, Rules};
+
+/// Add rules for the labels applied by Gmail filters.
+#[derive(Debug, Parser)]
+pub struct ImportCli {
+    /// Gmail filters XML file exported from Gmail's settings
+    file: PathBuf,
+    /// Retention period of the new rules, e.g. `m:6` or `y:1`
+    #[arg(short, long, default_value = "y:1", value_parser = parse_age)]
+    retention: MessageAge,
+    /// Permanently delete messages instead of moving them to trash
+    #[arg(long)]
+    delete: bool,
+}
+
+impl ImportCli {
+    pub fn run(&self, mut rules: Rules, rules_path: Option<&Path>) -> Result<()> {
+        let xml = fs::read_to_string(&self.file)
+            .map_err(|e| Error::FileIo(format!("{}: {e}", self.file.display())))?;
+
+        let ids = rules.import_mail_filters(&xml, self.retention.clone(), self.delete)?;
+        if ids.is_empty() {
+            println!("No new rules found in `{}`", self.file.display());
+            return Ok(());
+        }
+
+        rules.save_to(rules_path)?;
+        for id in ids {
+            if let Some(rule) = rules.get_rule(id) {
+                println!("Added rule #{id} for `{}`", rule.labels().join("`, `"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a retention period such as `m:6`.
+fn parse_age(value: &str) -> std::result::Result<MessageAge, String> {
+    MessageAge::try_from(value).map_err(|e| e.to_string())
+}
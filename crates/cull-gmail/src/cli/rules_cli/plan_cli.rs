@@ -0,0 +1,42 @@
+//! Plan subcommand for Gmail retention rules.
+//!
+//! Resolves every rule into the IDs of the messages it would act on and
+//! prints them per label with sample subjects. With `--out` the plan is
+//! written as JSON for `rules apply`, which acts on exactly those messages.
+
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use cull_gmail::{Error, GmailClient, Result, Rules};
+
+use crate::{plan_rules, print_plan};
+
+/// Resolve the rules into the messages they would act on.
+#[derive(Debug, Parser)]
+pub struct PlanCli {
+    /// Write the plan as JSON to this file
+    #[arg(short, long, value_name = "FILE")]
+    out: Option<PathBuf>,
+    /// Review each rule's messages in a checklist and deselect any to keep
+    #[arg(short, long)]
+    review: bool,
+}
+
+impl PlanCli {
+    pub async fn run(&self, client: &mut GmailClient, mut rules: Rules) -> Result<()> {
+        let plan = plan_rules(client, &mut rules, self.review).await?;
+        print_plan(&plan);
+
+        if let Some(out) = &self.out {
+            fs::write(out, plan.to_json()?)
+                .map_err(|e| Error::FileIo(format!("{}: {e}", out.display())))?;
+            println!(
+                "Plan saved to `{}`: run `cull-gmail rules apply {}` to carry it out",
+                out.display(),
+                out.display()
+            );
+        }
+
+        Ok(())
+    }
+}
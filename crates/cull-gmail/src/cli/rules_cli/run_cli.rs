@@ -1,13 +1,14 @@
 use clap::Parser;
 use cull_gmail::{GmailClient, Result, Rules};
 
-use crate::run_rules;
+use crate::{plan_rules, print_plan};
 
+/// Preview the messages each rule would act on.
+///
+/// Nothing is changed. Use `rules plan --out` and `rules apply` to act on the
+/// messages.
 #[derive(Debug, Parser)]
 pub struct RunCli {
-    /// Execute the action
-    #[clap(short, long, display_order = 1, help_heading = "Action")]
-    execute: bool,
     /// Skip any rules that apply the action `trash`
     #[clap(short = 't', long, display_order = 2, help_heading = "Skip Action")]
     skip_trash: bool,
@@ -17,7 +18,12 @@ pub struct RunCli {
 }
 
 impl RunCli {
-    pub async fn run(&self, client: &mut GmailClient, rules: Rules) -> Result<()> {
-        run_rules(client, rules, self.execute).await
+    pub async fn run(&self, client: &mut GmailClient, mut rules: Rules) -> Result<()> {
+        let plan = plan_rules(client, &mut rules, false).await?;
+        print_plan(&plan);
+        println!(
+            "Nothing was changed: run `rules plan --out <FILE>` and `rules apply <FILE>` to act"
+        );
+        Ok(())
     }
 }
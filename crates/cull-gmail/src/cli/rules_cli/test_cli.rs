@@ -0,0 +1,330 @@
+//! Test subcommand for Gmail retention rules.
+//!
+//! Runs synthetic messages from a fixture file through the rules offline and
+//! compares the outcome with the one expected. Exits with a non-zero status if
+//! any message is not treated as expected, so a rules change that would start
+//! trashing mail it should keep can be caught in CI.
+//!
+//! ## Fixture File
+//!
+//! ```toml
+//! # "Today" for every message, so the outcomes do not change over time
+//! now = "2025-09-15"
+//!
+//! [[messages]]
+//! name = "receipts are kept for seven years"
+//! labels = ["receipts"]
+//! from = "Shop <orders@shop.example>"
+//! received = "2020-03-01"
+//! expect = "keep"
+//!
+//! [[messages]]
+//! name = "old newsletters are trashed"
+//! labels = ["newsletters"]
+//! received = "2024-01-10T08:30:00"
+//! expect = "trash"
+//! ```
+//!
+//! Labels are given by name. Dates are local time, as `YYYY-MM-DD`,
+//! `YYYY-MM-DDTHH:MM:SS` or RFC 3339. `expect` is `keep`, `trash` or `delete`.
+
+use std::{collections::BTreeMap, fmt, fs, path::Path, path::PathBuf};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use clap::Parser;
+use serde::Deserialize;
+
+use cull_gmail::{EolAction, Error, MessageSummary, Rules};
+
+use crate::Result;
+
+/// Check the rules against fixture messages without contacting Gmail.
+///
+/// Each message in the fixture file is evaluated as of the file's `now` and
+/// the outcome (`keep`, `trash` or `delete`) compared with its `expect`.
+///
+/// Exits 0 if every message has the expected outcome, non-zero otherwise.
+#[derive(Debug, Parser)]
+pub struct TestCli {
+    /// TOML file describing the messages and their expected outcomes
+    #[arg(value_name = "FILE")]
+    fixtures: PathBuf,
+}
+
+/// A fixture file: the date to test at and the messages to test.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fixtures {
+    now: String,
+    #[serde(default)]
+    messages: Vec<Fixture>,
+}
+
+/// A synthetic message and the outcome expected for it.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fixture {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    received: Option<String>,
+    expect: Outcome,
+}
+
+/// What happens to a message when the rules are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Keep,
+    Trash,
+    Delete,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Keep => write!(f, "keep"),
+            Outcome::Trash => write!(f, "trash"),
+            Outcome::Delete => write!(f, "delete"),
+        }
+    }
+}
+
+/// The outcome for a fixture and the rule that decided it.
+#[derive(Debug, PartialEq, Eq)]
+struct Verdict {
+    outcome: Outcome,
+    rule_id: Option<usize>,
+    label: Option<String>,
+}
+
+impl TestCli {
+    pub fn run(&self, rules_path: Option<&Path>) -> Result<()> {
+        let rules = Rules::load_from(rules_path)?;
+        let fixtures = read_fixtures(&self.fixtures)?;
+        let now = parse_time(&fixtures.now).ok_or_else(|| {
+            Error::FixtureInvalid(format!("invalid `now` date `{}`", fixtures.now))
+        })?;
+
+        let mut failed = 0;
+        for (index, fixture) in fixtures.messages.iter().enumerate() {
+            let name = fixture
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("message {}", index + 1));
+            let verdict = outcome(&rules, fixture, now)?;
+            let decided_by = match (verdict.rule_id, &verdict.label) {
+                (Some(id), Some(label)) => format!(" (rule #{id} on `{label}`)"),
+                _ => String::new(),
+            };
+
+            if verdict.outcome == fixture.expect {
+                println!("ok    {name}: {}{decided_by}", verdict.outcome);
+            } else {
+                failed += 1;
+                println!(
+                    "FAIL  {name}: expected {}, got {}{decided_by}",
+                    fixture.expect, verdict.outcome
+                );
+            }
+        }
+
+        let total = fixtures.messages.len();
+        println!();
+        println!("{} passed, {failed} failed", total - failed);
+
+        if failed == 0 {
+            Ok(())
+        } else {
+            Err(Error::FixturesFailed(failed, total))
+        }
+    }
+}
+
+/// Reads and parses a fixture file.
+fn read_fixtures(path: &Path) -> Result<Fixtures> {
+    let text =
+        fs::read_to_string(path).map_err(|e| Error::FileIo(format!("{}: {e}", path.display())))?;
+    Ok(toml::from_str(&text)?)
+}
+
+/// Returns the label names used by the messages in a fixture file.
+///
+/// The names stand in for a mailbox's labels when the rules are explained
+/// without Gmail.
+pub(super) fn fixture_labels(path: &Path) -> Result<BTreeMap<String, String>> {
+    let fixtures = read_fixtures(path)?;
+    Ok(fixtures
+        .messages
+        .into_iter()
+        .flat_map(|message| message.labels)
+        .map(|label| (label.clone(), label))
+        .collect())
+}
+
+/// Evaluates the rules for a fixture message as of `now`.
+///
+/// Permanent deletion takes precedence over trash, as rules that delete are
+/// applied first.
+fn outcome(rules: &Rules, fixture: &Fixture, now: DateTime<Local>) -> Result<Verdict> {
+    let mut message = MessageSummary::new("fixture").with_labels(
+        &fixture
+            .labels
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+    );
+    if let Some(from) = &fixture.from {
+        message = message.with_from(from);
+    }
+    if let Some(received) = &fixture.received {
+        let date = parse_time(received).ok_or_else(|| {
+            Error::FixtureInvalid(format!("invalid `received` date `{received}`"))
+        })?;
+        message = message.with_internal_date(date);
+    }
+
+    // Fixture labels are names, so wildcard rules expand against them
+    let label_map = fixture
+        .labels
+        .iter()
+        .map(|label| (label.clone(), label.clone()))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut verdict = Verdict {
+        outcome: Outcome::Keep,
+        rule_id: None,
+        label: None,
+    };
+    for evaluation in rules.evaluate(&message, &label_map, now) {
+        if !evaluation.applies() {
+            continue;
+        }
+        let outcome = match rules.get_rule(evaluation.rule_id).and_then(|r| r.action()) {
+            Some(EolAction::Delete) => Outcome::Delete,
+            Some(EolAction::Trash) => Outcome::Trash,
+            None => continue,
+        };
+        if verdict.outcome == Outcome::Keep || outcome == Outcome::Delete {
+            verdict = Verdict {
+                outcome,
+                rule_id: Some(evaluation.rule_id),
+                label: evaluation.label,
+            };
+        }
+    }
+
+    Ok(verdict)
+}
+
+/// Parses a fixture date: RFC 3339, or a local date with optional time.
+fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Local));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|d| d.and_time(NaiveTime::MIN))
+        })
+        .ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+[rules."1"]
+id = 1
+retention = "y:1"
+labels = ["newsletters"]
+action = "Trash"
+protected_senders = ["boss@example.com"]
+
+[rules."2"]
+id = 2
+retention = "d:30"
+labels = ["Vendors/*"]
+action = "Delete"
+"#;
+
+    fn fixture(labels: &[&str], from: Option<&str>, received: &str) -> Fixture {
+        Fixture {
+            name: None,
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            from: from.map(str::to_string),
+            received: Some(received.to_string()),
+            expect: Outcome::Keep,
+        }
+    }
+
+    #[test]
+    fn test_outcome() {
+        let rules: Rules = toml::from_str(RULES).unwrap();
+        let now = parse_time("2025-09-15").unwrap();
+        let outcome_of = |fixture: Fixture| outcome(&rules, &fixture, now).unwrap().outcome;
+
+        assert_eq!(
+            outcome_of(fixture(&["newsletters"], None, "2024-09-14")),
+            Outcome::Trash
+        );
+        assert_eq!(
+            outcome_of(fixture(&["newsletters"], None, "2024-09-15")),
+            Outcome::Keep
+        );
+        assert_eq!(
+            outcome_of(fixture(
+                &["newsletters"],
+                Some("Boss <boss@example.com>"),
+                "2020-01-01"
+            )),
+            Outcome::Keep
+        );
+        assert_eq!(
+            outcome(
+                &rules,
+                &fixture(&["newsletters", "Vendors/Acme"], None, "2020-01-01"),
+                now
+            )
+            .unwrap(),
+            Verdict {
+                outcome: Outcome::Delete,
+                rule_id: Some(2),
+                label: Some("Vendors/Acme".to_string()),
+            }
+        );
+        assert_eq!(
+            outcome_of(fixture(&["receipts"], None, "2000-01-01")),
+            Outcome::Keep
+        );
+    }
+
+    #[test]
+    fn test_parse_fixtures() {
+        let fixtures: Fixtures = toml::from_str(
+            r#"
+now = "2025-09-15T12:00:00Z"
+
+[[messages]]
+labels = ["receipts"]
+received = "2020-03-01 08:30:00"
+expect = "keep"
+"#,
+        )
+        .unwrap();
+        assert!(parse_time(&fixtures.now).is_some());
+        assert_eq!(fixtures.messages[0].expect, Outcome::Keep);
+        assert!(parse_time(fixtures.messages[0].received.as_deref().unwrap()).is_some());
+        assert!(parse_time("15/09/2025").is_none());
+
+        let unknown =
+            toml::from_str::<Fixtures>("now = \"2025-09-15\"\n[[messages]]\nexpect = \"archive\"");
+        assert!(unknown.is_err());
+    }
+}
@@ -1,13 +1,19 @@
 //! Validation subcommand for Gmail retention rules.
 //!
 //! Loads a rules file and checks each rule for correctness without
-//! executing any actions or making API calls. Exits with a non-zero
-//! status if any issues are found.
+//! executing any actions. Exits with a non-zero status if any issues are
+//! found.
+//!
+//! With `--online` the rules are also checked against the mailbox: labels
+//! must exist, and rules on sensitive system labels, rules matching no
+//! messages and rules matching a large share of the mailbox are reported as
+//! warnings.
 
+use chrono::Local;
 use clap::Parser;
 use std::path::Path;
 
-use cull_gmail::Rules;
+use cull_gmail::{EolRule, GmailClient, LabelManager, Rules, ValidationIssue};
 
 use crate::Result;
 
@@ -22,24 +28,104 @@ use crate::Result;
 ///
 /// Exits 0 if all rules are valid, non-zero otherwise.
 #[derive(Debug, Parser)]
-pub struct ValidateCli {}
+pub struct ValidateCli {
+    /// Also check the rules against the mailbox (requires Gmail credentials)
+    #[arg(long)]
+    online: bool,
+    /// Warn about rules matching more than this percentage of the mailbox
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        default_value_t = 25,
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        requires = "online"
+    )]
+    max_share: u8,
+}
 
 impl ValidateCli {
+    /// Returns `true` if the rules are checked against the mailbox.
+    pub fn online(&self) -> bool {
+        self.online
+    }
+
     pub fn run(&self, rules_path: Option<&Path>) -> Result<()> {
         let rules = Rules::load_from(rules_path)?;
-        let issues = rules.validate();
+        report(rules.validate())
+    }
+
+    /// Validates the rules, then checks them against the mailbox.
+    pub async fn run_online(&self, client: &GmailClient, rules_path: Option<&Path>) -> Result<()> {
+        let rules = Rules::load_from(rules_path)?;
+        let mut issues = rules.validate();
+        issues.extend(rules.validate_labels(client.label_map()));
 
-        if issues.is_empty() {
+        let total = client.messages_total().await? as usize;
+        let owned = rules.labels();
+        for rule in rules.all_rules() {
+            // Rules without a valid retention select nothing and are reported above
+            if rule.cutoff_for_date(Local::now()).is_none() {
+                continue;
+            }
+            let count = count_messages(client, &rule, &owned).await?;
+            log::info!("rule #{} matches {count} message(s)", rule.id());
+            if count == 0 {
+                issues.push(ValidationIssue::NoMatches { rule_id: rule.id() });
+            } else if count * 100 > total * usize::from(self.max_share) {
+                issues.push(ValidationIssue::LargeShare {
+                    rule_id: rule.id(),
+                    count,
+                    total,
+                });
+            }
+        }
+
+        report(issues)
+    }
+}
+
+/// Estimates the messages a rule currently selects across its labels.
+///
+/// As when the rules are run, labels matched by a wildcard that have a rule
+/// of their own are left to that rule, and labels that do not exist are
+/// skipped. The rule's `max_messages` limit is ignored, so the count is of
+/// every message the rule could act on.
+async fn count_messages(client: &GmailClient, rule: &EolRule, owned: &[String]) -> Result<usize> {
+    let mut count = 0;
+    for pattern in rule.labels() {
+        for label in client.expand_label(&pattern) {
+            if (label != pattern && owned.contains(&label)) || client.get_label_id(&label).is_none()
+            {
+                continue;
+            }
+            count += client.estimate_rule_messages(rule, &label).await? as usize;
+        }
+    }
+    Ok(count)
+}
+
+/// Prints the issues, failing if any is not a warning.
+fn report(issues: Vec<ValidationIssue>) -> Result<()> {
+    let (warnings, errors): (Vec<_>, Vec<_>) = issues.iter().partition(|i| i.is_warning());
+
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    for error in &errors {
+        eprintln!("{error}");
+    }
+
+    if errors.is_empty() {
+        if warnings.is_empty() {
             println!("Rules are valid.");
-            Ok(())
         } else {
-            for issue in &issues {
-                eprintln!("{issue}");
-            }
-            Err(cull_gmail::Error::FileIo(format!(
-                "{} validation issue(s) found",
-                issues.len()
-            )))
+            println!("Rules are valid, with {} warning(s).", warnings.len());
         }
+        Ok(())
+    } else {
+        Err(cull_gmail::Error::FileIo(format!(
+            "{} validation issue(s) found",
+            errors.len()
+        )))
     }
 }
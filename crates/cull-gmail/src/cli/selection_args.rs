@@ -0,0 +1,101 @@
+//! # Message Selection Arguments
+//!
+//! The label, query and paging flags shared by the commands that search for
+//! messages (`messages` and `stats`), together with the typed filter flags
+//! from [`QueryArgs`].
+
+use clap::Args;
+use cull_gmail::{GmailClient, MessageList, Result};
+
+use crate::query_args::QueryArgs;
+
+/// Flags that select the messages a command works on.
+///
+/// Commands flatten this into their own arguments. `--pages` defaults to one
+/// page; a command can change the default with `mut_arg`.
+#[derive(Debug, Args)]
+pub struct SelectionArgs {
+    /// Maximum number of messages to retrieve per page.
+    ///
+    /// Controls the batch size for Gmail API requests. Larger values are more
+    /// efficient but may hit API rate limits. Smaller values provide more
+    /// granular control and progress feedback.
+    ///
+    /// **Range**: 1-500 (Gmail API limit)
+    /// **Performance**: 100-200 is typically optimal
+    #[arg(short, long, display_order = 1, help_heading = "Config", default_value = cull_gmail::DEFAULT_MAX_RESULTS)]
+    pub max_results: u32,
+
+    /// Maximum number of pages to process.
+    ///
+    /// Limits the total number of API requests and messages processed.
+    /// Use 0 for unlimited pages (process all matching messages).
+    ///
+    /// **Safety**: Start with 1-2 pages for testing destructive operations
+    /// **Performance**: Higher values process more messages but take longer
+    #[arg(
+        short,
+        long,
+        display_order = 1,
+        help_heading = "Config",
+        default_value = "1"
+    )]
+    pub pages: u32,
+
+    /// Gmail labels to filter messages (can be specified multiple times).
+    ///
+    /// Filters messages to only those containing ALL specified labels.
+    /// Use `cull-gmail labels` to see available labels in your account.
+    ///
+    /// **Examples**:
+    /// - `-l "INBOX"` - Messages in inbox
+    /// - `-l "promotions" -l "unread"` - Unread promotional messages
+    #[arg(short, long, display_order = 1, help_heading = "Config")]
+    pub labels: Vec<String>,
+
+    /// Gmail query string using Gmail's advanced search syntax.
+    ///
+    /// Supports the same query syntax as Gmail's web interface search box.
+    /// Can be combined with label filters for more precise targeting.
+    ///
+    /// **Examples**:
+    /// - `"older_than:1y"` - Messages older than 1 year
+    /// - `"from:noreply@example.com older_than:30d"` - Old automated emails
+    /// - `"has:attachment larger:10M"` - Large attachments
+    #[arg(short = 'Q', long, display_order = 1, help_heading = "Config")]
+    pub query: Option<String>,
+
+    /// Typed filters combined with the query string.
+    #[command(flatten)]
+    pub filters: QueryArgs,
+}
+
+impl SelectionArgs {
+    /// Applies the label filters, query and page size to the client.
+    ///
+    /// With `--show-query` the final query is printed to stderr.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LabelNotFoundInMailbox`](cull_gmail::Error::LabelNotFoundInMailbox)
+    ///   if a label does not exist
+    pub fn apply(&self, client: &mut GmailClient) -> Result<()> {
+        if !self.labels.is_empty() {
+            client.add_labels(&self.labels)?;
+        }
+
+        let query = self.filters.build(self.query.as_deref());
+        if self.filters.show_query() {
+            eprintln!("{}", query.as_deref().unwrap_or_default());
+        }
+        if let Some(query) = query {
+            client.set_query(&query)
+        }
+
+        log::trace!("Max results: `{}`", self.max_results);
+        client.set_max_results(self.max_results);
+        log::debug!("List max results set to {}", client.max_results());
+
+        Ok(())
+    }
+}
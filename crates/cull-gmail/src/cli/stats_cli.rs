@@ -0,0 +1,507 @@
+//! # Mailbox Statistics CLI Module
+//!
+//! This module provides the `stats` subcommand, which shows where mailbox
+//! storage goes so that retention rules can be targeted at the messages that
+//! matter.
+//!
+//! ## Overview
+//!
+//! Messages matching the label and query filters are listed, their metadata is
+//! fetched, and the Gmail size estimate of each message is aggregated along one
+//! or more dimensions:
+//!
+//! - **sender**: the sender's email address
+//! - **domain**: the domain of the sender's address
+//! - **label**: each label applied to the message
+//! - **year** / **month**: when the message was sent
+//! - **size**: fixed size buckets from under 10 KB to over 10 MB
+//!
+//! Senders, domains and labels are ranked by total size (or message count) and
+//! limited with `--top`. Years, months and size buckets are shown in full, in
+//! their natural order, as a distribution.
+//!
+//! ## Usage
+//!
+//! ```bash
+//! # Where does the space go in the whole mailbox?
+//! cull-gmail stats
+//!
+//! # Top 10 senders of old promotional mail, as JSON
+//! cull-gmail stats -l CATEGORY_PROMOTIONS -Q "older_than:1y" --by sender --top 10 --format json
+//! ```
+//!
+//! ## Notes
+//!
+//! - A message with several labels counts towards each of them, so label
+//!   totals can add up to more than the mailbox total.
+//! - Metadata is fetched for every message, one API call per message, so
+//!   large mailboxes take a while. Use `--pages` to sample a subset.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use clap::{Parser, ValueEnum};
+use cull_gmail::{GmailClient, MessageList, MessageSummary, Result};
+use serde_json::{Map, Value, json};
+
+use crate::{selection_args::SelectionArgs, table::write_table};
+
+/// Size buckets as `(upper bound in bytes, name)`, smallest first.
+const SIZE_BUCKETS: [(u64, &str); 5] = [
+    (10 * KB, "< 10 KB"),
+    (100 * KB, "10-100 KB"),
+    (MB, "100 KB-1 MB"),
+    (10 * MB, "1-10 MB"),
+    (u64::MAX, ">= 10 MB"),
+];
+
+const KB: u64 = 1024;
+const MB: u64 = 1024 * KB;
+const GB: u64 = 1024 * MB;
+
+/// Key used when a message has no value for a dimension.
+const UNKNOWN: &str = "(unknown)";
+
+/// Dimensions along which messages can be aggregated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Dimension {
+    /// Sender email address
+    Sender,
+    /// Domain of the sender email address
+    Domain,
+    /// Each label applied to the message
+    Label,
+    /// Year the message was sent
+    Year,
+    /// Year and month the message was sent
+    Month,
+    /// Size bucket of the message
+    Size,
+}
+
+impl Dimension {
+    /// Returns the dimension name used for headings and JSON keys.
+    fn name(&self) -> &'static str {
+        match self {
+            Dimension::Sender => "sender",
+            Dimension::Domain => "domain",
+            Dimension::Label => "label",
+            Dimension::Year => "year",
+            Dimension::Month => "month",
+            Dimension::Size => "size",
+        }
+    }
+
+    /// Returns `true` if groups are shown as a distribution in natural order
+    /// rather than ranked.
+    fn is_distribution(&self) -> bool {
+        matches!(self, Dimension::Year | Dimension::Month | Dimension::Size)
+    }
+
+    /// Returns the keys a message is counted under for this dimension.
+    fn keys(&self, message: &MessageSummary, label_name: &impl Fn(&str) -> String) -> Vec<String> {
+        let key = match self {
+            Dimension::Sender => message.sender_address(),
+            Dimension::Domain => message
+                .sender_address()
+                .and_then(|address| sender_domain(&address)),
+            Dimension::Label => {
+                return if message.label_ids().is_empty() {
+                    vec!["(none)".to_string()]
+                } else {
+                    message
+                        .label_ids()
+                        .iter()
+                        .map(|id| label_name(id))
+                        .collect()
+                };
+            }
+            Dimension::Year => message.date_time().map(|d| d.format("%Y").to_string()),
+            Dimension::Month => message.date_time().map(|d| d.format("%Y-%m").to_string()),
+            Dimension::Size => Some(size_bucket(message_size(message)).to_string()),
+        };
+
+        vec![key.unwrap_or_else(|| UNKNOWN.to_string())]
+    }
+}
+
+/// Output formats for the statistics report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    /// Ranked tables for reading in a terminal
+    Table,
+    /// A single JSON object
+    Json,
+}
+
+/// Order of ranked groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+    /// Largest total size first
+    Size,
+    /// Most messages first
+    Count,
+}
+
+/// Totals for a single key within a dimension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Group {
+    key: String,
+    messages: u64,
+    size: u64,
+}
+
+/// Command-line interface for mailbox storage statistics.
+///
+/// Lists the messages selected by the label and query filters and reports how
+/// many messages and bytes fall under each sender, domain, label, period or
+/// size bucket. Unlike `messages`, all pages are read unless `--pages` is given.
+#[derive(Debug, Parser)]
+#[command(mut_arg("pages", |arg| arg.default_value("0")))]
+pub struct StatsCli {
+    /// Labels, query and paging used to select messages
+    #[command(flatten)]
+    selection: SelectionArgs,
+
+    /// Dimensions to aggregate by, separated by commas
+    #[arg(
+        short,
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [
+            Dimension::Sender,
+            Dimension::Domain,
+            Dimension::Label,
+            Dimension::Year,
+            Dimension::Month,
+            Dimension::Size,
+        ]
+    )]
+    by: Vec<Dimension>,
+
+    /// Number of senders, domains or labels to show (0 = all)
+    #[arg(short, long, default_value = "20")]
+    top: usize,
+
+    /// Rank senders, domains and labels by total size or message count
+    #[arg(short, long, value_enum, default_value_t = SortBy::Size)]
+    sort: SortBy,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = StatsFormat::Table)]
+    format: StatsFormat,
+}
+
+impl StatsCli {
+    /// Lists the selected messages, aggregates them and writes the report to
+    /// stdout.
+    pub(crate) async fn run(&self, client: &mut GmailClient) -> Result<()> {
+        self.selection.apply(client)?;
+
+        client.get_messages(self.selection.pages).await?;
+        client.fetch_metadata().await?;
+
+        let label_name = |id: &str| client.get_label_name(id).unwrap_or_else(|| id.to_string());
+        let messages = client.messages();
+        let total = Group {
+            key: "total".to_string(),
+            messages: messages.len() as u64,
+            size: messages.iter().map(message_size).sum(),
+        };
+        let report = self
+            .by
+            .iter()
+            .map(|dimension| {
+                let entries = messages
+                    .iter()
+                    .map(|m| (dimension.keys(m, &label_name), message_size(m)));
+                (*dimension, self.rank(*dimension, aggregate(entries)))
+            })
+            .collect::<Vec<_>>();
+
+        let mut out = io::stdout().lock();
+        match self.format {
+            StatsFormat::Table => write_tables(&mut out, &total, &report)?,
+            StatsFormat::Json => write_json(&mut out, &total, &report)?,
+        }
+        out.flush()?;
+
+        Ok(())
+    }
+
+    /// Orders the groups of a dimension and applies the `--top` limit to
+    /// ranked dimensions.
+    fn rank(&self, dimension: Dimension, mut groups: Vec<Group>) -> Vec<Group> {
+        if dimension == Dimension::Size {
+            groups.sort_by_key(|g| SIZE_BUCKETS.iter().position(|(_, name)| *name == g.key));
+            return groups;
+        }
+        if dimension.is_distribution() {
+            groups.sort_by(|a, b| (a.key == UNKNOWN, &a.key).cmp(&(b.key == UNKNOWN, &b.key)));
+            return groups;
+        }
+
+        match self.sort {
+            SortBy::Size => groups
+                .sort_by(|a, b| (b.size, b.messages, &a.key).cmp(&(a.size, a.messages, &b.key))),
+            SortBy::Count => groups
+                .sort_by(|a, b| (b.messages, b.size, &a.key).cmp(&(a.messages, a.size, &b.key))),
+        }
+        if self.top > 0 {
+            groups.truncate(self.top);
+        }
+        groups
+    }
+}
+
+/// Sums message counts and sizes per key.
+///
+/// Each entry holds the keys a message is counted under and its size.
+fn aggregate(entries: impl Iterator<Item = (Vec<String>, u64)>) -> Vec<Group> {
+    let mut groups: HashMap<String, Group> = HashMap::new();
+    for (keys, size) in entries {
+        for key in keys {
+            let group = groups.entry(key.clone()).or_insert(Group {
+                key,
+                messages: 0,
+                size: 0,
+            });
+            group.messages += 1;
+            group.size += size;
+        }
+    }
+    groups.into_values().collect()
+}
+
+/// Returns the size estimate of a message, treating a missing estimate as zero.
+fn message_size(message: &MessageSummary) -> u64 {
+    message.size_estimate().map(u64::from).unwrap_or_default()
+}
+
+/// Returns the domain part of an email address.
+fn sender_domain(address: &str) -> Option<String> {
+    address
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.to_string())
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Returns the name of the size bucket a message of `size` bytes falls into.
+fn size_bucket(size: u64) -> &'static str {
+    SIZE_BUCKETS
+        .iter()
+        .find(|(limit, _)| size < *limit)
+        .map_or(SIZE_BUCKETS[SIZE_BUCKETS.len() - 1].1, |(_, name)| name)
+}
+
+/// Formats a byte count with a binary unit.
+fn human_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= GB => format!("{:.1} GB", b as f64 / GB as f64),
+        b if b >= MB => format!("{:.1} MB", b as f64 / MB as f64),
+        b if b >= KB => format!("{:.1} KB", b as f64 / KB as f64),
+        b => format!("{b} B"),
+    }
+}
+
+/// Writes one aligned table per dimension, preceded by the mailbox totals.
+fn write_tables<W: Write>(
+    out: &mut W,
+    total: &Group,
+    report: &[(Dimension, Vec<Group>)],
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{} messages, {}",
+        total.messages,
+        human_size(total.size)
+    )?;
+
+    for (dimension, groups) in report {
+        let rows = groups
+            .iter()
+            .map(|g| {
+                let share = if total.size == 0 {
+                    0.0
+                } else {
+                    g.size as f64 * 100.0 / total.size as f64
+                };
+                vec![
+                    g.key.clone(),
+                    g.messages.to_string(),
+                    human_size(g.size),
+                    format!("{share:.1}%"),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let header = [
+            dimension.name().to_uppercase(),
+            "MESSAGES".to_string(),
+            "SIZE".to_string(),
+            "SHARE".to_string(),
+        ];
+
+        writeln!(out)?;
+        write_table(out, &header, &rows, &[1, 2, 3])?;
+    }
+
+    Ok(())
+}
+
+/// Writes the report as a JSON object keyed by dimension name.
+fn write_json<W: Write>(
+    out: &mut W,
+    total: &Group,
+    report: &[(Dimension, Vec<Group>)],
+) -> io::Result<()> {
+    let by = report
+        .iter()
+        .map(|(dimension, groups)| {
+            let groups = groups
+                .iter()
+                .map(|g| json!({ "key": g.key, "messages": g.messages, "size": g.size }))
+                .collect();
+            (dimension.name().to_string(), Value::Array(groups))
+        })
+        .collect::<Map<_, _>>();
+
+    let value = json!({
+        "messages": total.messages,
+        "size": total.size,
+        "by": by,
+    });
+    serde_json::to_writer_pretty(&mut *out, &value)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query_args::QueryArgs;
+
+    fn stats_cli(sort: SortBy, top: usize) -> StatsCli {
+        StatsCli {
+            selection: SelectionArgs {
+                max_results: 200,
+                pages: 0,
+                labels: Vec::new(),
+                query: None,
+                filters: QueryArgs::default(),
+            },
+            by: vec![Dimension::Sender],
+            top,
+            sort,
+            format: StatsFormat::Table,
+        }
+    }
+
+    fn group(key: &str, messages: u64, size: u64) -> Group {
+        Group {
+            key: key.to_string(),
+            messages,
+            size,
+        }
+    }
+
+    fn sample_groups() -> Vec<Group> {
+        let entries = [
+            (vec!["a@example.com".to_string()], 100),
+            (vec!["b@example.com".to_string()], 5000),
+            (vec!["a@example.com".to_string()], 200),
+            (vec!["a@example.com".to_string()], 300),
+        ];
+        aggregate(entries.into_iter())
+    }
+
+    #[test]
+    fn test_stats_reads_all_pages_by_default() {
+        let cli = StatsCli::parse_from(["stats", "-l", "INBOX"]);
+        assert_eq!(cli.selection.pages, 0);
+        assert_eq!(cli.selection.labels, vec!["INBOX"]);
+    }
+
+    #[test]
+    fn test_sender_domain() {
+        assert_eq!(
+            sender_domain("news@example.com"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(sender_domain("undisclosed"), None);
+    }
+
+    #[test]
+    fn test_size_bucket() {
+        assert_eq!(size_bucket(0), "< 10 KB");
+        assert_eq!(size_bucket(10 * KB), "10-100 KB");
+        assert_eq!(size_bucket(MB - 1), "100 KB-1 MB");
+        assert_eq!(size_bucket(5 * MB), "1-10 MB");
+        assert_eq!(size_bucket(u64::MAX), ">= 10 MB");
+    }
+
+    #[test]
+    fn test_rank_by_size_and_count() {
+        let by_size = stats_cli(SortBy::Size, 0).rank(Dimension::Sender, sample_groups());
+        assert_eq!(
+            by_size,
+            vec![
+                group("b@example.com", 1, 5000),
+                group("a@example.com", 3, 600)
+            ]
+        );
+
+        let by_count = stats_cli(SortBy::Count, 1).rank(Dimension::Sender, sample_groups());
+        assert_eq!(by_count, vec![group("a@example.com", 3, 600)]);
+    }
+
+    #[test]
+    fn test_distributions_keep_natural_order() {
+        let cli = stats_cli(SortBy::Size, 1);
+
+        let months = vec![
+            group(UNKNOWN, 1, 1),
+            group("2024-02", 1, 900),
+            group("2023-11", 1, 10),
+        ];
+        let keys = cli
+            .rank(Dimension::Month, months)
+            .into_iter()
+            .map(|g| g.key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["2023-11", "2024-02", UNKNOWN]);
+
+        let sizes = vec![group(">= 10 MB", 1, 20 * MB), group("< 10 KB", 5, KB)];
+        let keys = cli
+            .rank(Dimension::Size, sizes)
+            .into_iter()
+            .map(|g| g.key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["< 10 KB", ">= 10 MB"]);
+    }
+
+    #[test]
+    fn test_write_tables_and_json() {
+        let total = group("total", 4, 5600);
+        let report = vec![(
+            Dimension::Sender,
+            stats_cli(SortBy::Size, 0).rank(Dimension::Sender, sample_groups()),
+        )];
+
+        let mut out = Vec::new();
+        write_tables(&mut out, &total, &report).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "4 messages, 5.5 KB");
+        assert_eq!(lines[2], "SENDER         MESSAGES    SIZE  SHARE");
+        assert_eq!(lines[3], "b@example.com         1  4.9 KB  89.3%");
+
+        let mut out = Vec::new();
+        write_json(&mut out, &total, &report).unwrap();
+        let value: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["size"], 5600);
+        assert_eq!(value["by"]["sender"][1]["key"], "a@example.com");
+        assert_eq!(value["by"]["sender"][1]["messages"], 3);
+    }
+}
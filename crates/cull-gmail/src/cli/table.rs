@@ -0,0 +1,71 @@
+//! # Table Output
+//!
+//! Writes rows of text as aligned columns for reading in a terminal. Columns
+//! are separated by two spaces and sized to their widest cell; trailing spaces
+//! are trimmed from each line.
+
+use std::io::{self, Write};
+
+/// Writes a header and rows as aligned columns.
+///
+/// Columns are left-aligned except those whose index is in `right_aligned`,
+/// which suits numbers.
+pub fn write_table<W: Write>(
+    out: &mut W,
+    header: &[String],
+    rows: &[Vec<String>],
+    right_aligned: &[usize],
+) -> io::Result<()> {
+    let widths = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header[i].chars().count()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    for row in [header].into_iter().chain(rows.iter().map(Vec::as_slice)) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| {
+                if right_aligned.contains(&i) {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(out, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_table_aligns_columns() {
+        let header = ["NAME", "COUNT", "NOTE"].map(str::to_string);
+        let rows = vec![
+            vec!["inbox".to_string(), "1520".to_string(), String::new()],
+            vec!["café".to_string(), "7".to_string(), "new".to_string()],
+        ];
+
+        let mut out = Vec::new();
+        write_table(&mut out, &header, &rows, &[1]).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "NAME   COUNT  NOTE\n\
+             inbox   1520\n\
+             café       7  new\n"
+        );
+    }
+}
@@ -57,7 +57,7 @@
 //! - Token metadata and expiration
 //! - Encoded as base64 for environment variable compatibility
 
-use crate::{ClientConfig, Result};
+use crate::Result;
 use base64::{Engine as _, engine::general_purpose::STANDARD as Base64Engine};
 use clap::Subcommand;
 use cull_gmail::Error;
@@ -161,7 +161,7 @@ impl TokenCli {
     ///
     /// # Arguments
     ///
-    /// * `client_config` - Client configuration containing token storage paths
+    /// * `token_path` - Directory the OAuth2 tokens are cached in
     ///
     /// # Returns
     ///
@@ -172,10 +172,10 @@ impl TokenCli {
     /// - File I/O errors when reading or writing token files
     /// - Serialization errors when processing token data
     /// - Environment variable errors during import operations
-    pub async fn run(&self, client_config: &ClientConfig) -> Result<()> {
+    pub async fn run(&self, token_path: &str) -> Result<()> {
         match &self.command {
-            TokenCommand::Export => export_tokens(client_config).await,
-            TokenCommand::Import => import_tokens(client_config).await,
+            TokenCommand::Export => export_tokens(token_path).await,
+            TokenCommand::Import => import_tokens(token_path).await,
         }
     }
 }
@@ -187,7 +187,7 @@ impl TokenCli {
 ///
 /// # Arguments
 ///
-/// * `config` - Client configuration containing token persistence path
+/// * `token_path` - Directory the OAuth2 tokens are cached in
 ///
 /// # Returns
 ///
@@ -207,8 +207,8 @@ impl TokenCli {
 /// - `Error::TokenNotFound` - No token cache directory or files found
 /// - I/O errors reading token files
 /// - Serialization errors processing token data
-async fn export_tokens(config: &ClientConfig) -> Result<()> {
-    let token_path = Path::new(config.persist_path());
+async fn export_tokens(token_path: &str) -> Result<()> {
+    let token_path = Path::new(token_path);
     let mut token_data = std::collections::HashMap::new();
 
     if token_path.is_file() {
@@ -287,7 +287,7 @@ async fn export_tokens(config: &ClientConfig) -> Result<()> {
 ///
 /// # Arguments
 ///
-/// * `config` - Client configuration containing token persistence path
+/// * `token_path` - Directory the OAuth2 tokens are restored to
 ///
 /// # Returns
 ///
@@ -308,12 +308,12 @@ async fn export_tokens(config: &ClientConfig) -> Result<()> {
 /// - `Error::TokenNotFound` - Environment variable not set
 /// - Decoding/decompression errors for malformed token data
 /// - I/O errors creating token files
-pub async fn import_tokens(config: &ClientConfig) -> Result<()> {
+pub async fn import_tokens(token_path: &str) -> Result<()> {
     let token_env = std::env::var("CULL_GMAIL_TOKEN_CACHE").map_err(|_| {
         Error::TokenNotFound("CULL_GMAIL_TOKEN_CACHE environment variable not set".to_string())
     })?;
 
-    restore_tokens_from_string(&token_env, config.persist_path())?;
+    restore_tokens_from_string(&token_env, token_path)?;
 
     log::info!("Tokens successfully imported from environment variable");
     Ok(())
@@ -463,7 +463,7 @@ mod tests {
 
         // Export tokens (this would normally print to stdout)
         // We'll test the internal function instead
-        let result = tokio_test::block_on(export_tokens(&config));
+        let result = tokio_test::block_on(export_tokens(config.persist_path()));
         assert!(result.is_ok(), "Export should succeed");
 
         // For full integration test, we would capture stdout and test import
@@ -513,7 +513,7 @@ mod tests {
             .with_config_path(temp_dir.path().join("nonexistent").to_str().unwrap())
             .build();
 
-        let result = tokio_test::block_on(export_tokens(&config));
+        let result = tokio_test::block_on(export_tokens(config.persist_path()));
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), Error::TokenNotFound(_)));
     }
@@ -57,9 +57,10 @@
 //! use cull_gmail::ClientConfig;
 //!
 //! let config = ClientConfig::builder()
-//!     .with_credential_file("client_secret.json")
 //!     .with_config_path("~/.cull-gmail")
+//!     .with_credential_file("client_secret.json")?
 //!     .build();
+//! # Ok::<(), cull_gmail::Error>(())
 //! ```
 //!
 //! ### Builder Pattern with Direct OAuth2 Parameters
@@ -101,7 +102,7 @@
 //!
 //! # async fn example() -> cull_gmail::Result<()> {
 //! let config = ClientConfig::builder()
-//!     .with_credential_file("client_secret.json")
+//!     .with_credential_file("client_secret.json")?
 //!     .build();
 //!
 //! let client = GmailClient::new_with_config(config).await?;
@@ -134,12 +135,12 @@
 //! file I/O operations are synchronous and should be performed during application
 //! initialization rather than in performance-critical paths.
 
-use std::{fs, path::PathBuf};
+use std::{fs, path::Path};
 
 use config::Config;
 use google_gmail1::yup_oauth2::{ApplicationSecret, ConsoleApplicationSecret};
 
-use crate::Result;
+use crate::{Error, GmailScope, Result};
 
 mod config_root;
 
@@ -193,6 +194,10 @@ pub struct ClientConfig {
     /// Full path where OAuth2 tokens should be persisted.
     /// Typically resolves to something like `~/.cull-gmail/gmail1`.
     persist_path: String,
+
+    /// OAuth2 scope requested when authenticating.
+    /// Defaults to read-only access and should be raised only as far as the task requires.
+    scope: GmailScope,
 }
 
 impl ClientConfig {
@@ -325,12 +330,7 @@ impl ClientConfig {
             log::info!("root: {config_root}");
             let path = config_root.full_path().join(credential_file);
             log::info!("path: {}", path.display());
-            let json_str = fs::read_to_string(path).expect("could not read path");
-
-            let console: ConsoleApplicationSecret =
-                serde_json::from_str(&json_str).expect("could not convert to struct");
-
-            console.installed.unwrap()
+            read_application_secret(&path)?
         };
 
         let persist_path = format!("{}/gmail1", config_root.full_path().display());
@@ -339,6 +339,7 @@ impl ClientConfig {
             config_root,
             secret,
             persist_path,
+            scope: GmailScope::default(),
         })
     }
 
@@ -394,6 +395,38 @@ impl ClientConfig {
         &self.persist_path
     }
 
+    /// Returns the OAuth2 scope requested when authenticating.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cull_gmail::{ClientConfig, GmailScope};
+    ///
+    /// let config = ClientConfig::builder().build();
+    /// assert_eq!(config.scope(), GmailScope::ReadOnly);
+    /// ```
+    pub fn scope(&self) -> GmailScope {
+        self.scope
+    }
+
+    /// Sets the OAuth2 scope requested when authenticating.
+    ///
+    /// Use the least privileged scope that covers the intended operations, for
+    /// example [`GmailScope::for_rules`] when running retention rules.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cull_gmail::{ClientConfig, GmailScope};
+    ///
+    /// let mut config = ClientConfig::builder().build();
+    /// config.set_scope(GmailScope::Modify);
+    /// assert_eq!(config.scope(), GmailScope::Modify);
+    /// ```
+    pub fn set_scope(&mut self, scope: GmailScope) {
+        self.scope = scope;
+    }
+
     /// Returns a reference to the configuration root path resolver.
     ///
     /// The `ConfigRoot` handles path resolution with support for different base directories
@@ -464,9 +497,10 @@ impl ClientConfig {
 /// use cull_gmail::ClientConfig;
 ///
 /// let config = ClientConfig::builder()
-///     .with_credential_file("client_secret.json")
 ///     .with_config_path(".cull-gmail")
+///     .with_credential_file("client_secret.json")?
 ///     .build();
+/// # Ok::<(), cull_gmail::Error>(())
 /// ```
 ///
 /// ## Direct OAuth2 Configuration
@@ -490,10 +524,11 @@ impl ClientConfig {
 /// use cull_gmail::ClientConfig;
 ///
 /// let config = ClientConfig::builder()
-///     .with_credential_file("base_credentials.json")
+///     .with_credential_file("base_credentials.json")?
 ///     .add_redirect_uri("http://localhost:3000")  // Additional redirect URI
 ///     .with_project_id("override-project-id")    // Override from file
 ///     .build();
+/// # Ok::<(), cull_gmail::Error>(())
 /// ```
 #[derive(Debug)]
 pub struct ConfigBuilder {
@@ -504,6 +539,9 @@ pub struct ConfigBuilder {
     /// Configuration root path resolver for determining base directories.
     /// Used to resolve relative paths in credential files and token storage.
     config_root: ConfigRoot,
+
+    /// OAuth2 scope requested when authenticating.
+    scope: GmailScope,
 }
 
 impl Default for ConfigBuilder {
@@ -530,6 +568,7 @@ impl Default for ConfigBuilder {
         Self {
             secret,
             config_root: Default::default(),
+            scope: GmailScope::default(),
         }
     }
 }
@@ -545,16 +584,17 @@ impl ConfigBuilder {
         self
     }
 
-    pub fn with_credential_file(&mut self, credential_file: &str) -> &mut Self {
-        let path = PathBuf::from(self.config_root.to_string()).join(credential_file);
+    /// Loads the OAuth2 client from a credential file relative to the configuration root.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read, is not valid JSON, or contains neither
+    /// an `installed` nor a `web` client section.
+    pub fn with_credential_file(&mut self, credential_file: &str) -> Result<&mut Self> {
+        let path = self.config_root.full_path().join(credential_file);
         log::info!("path: {}", path.display());
-        let json_str = fs::read_to_string(path).expect("could not read path");
-
-        let console: ConsoleApplicationSecret =
-            serde_json::from_str(&json_str).expect("could not convert to struct");
-
-        self.secret = console.installed.unwrap();
-        self
+        self.secret = read_application_secret(&path)?;
+        Ok(self)
     }
 
     pub fn with_client_id(&mut self, value: &str) -> &mut Self {
@@ -600,6 +640,11 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn with_scope(&mut self, value: GmailScope) -> &mut Self {
+        self.scope = value;
+        self
+    }
+
     fn full_path(&self) -> String {
         self.config_root.full_path().display().to_string()
     }
@@ -611,10 +656,29 @@ impl ConfigBuilder {
             secret: self.secret.clone(),
             config_root: self.config_root.clone(),
             persist_path,
+            scope: self.scope,
         }
     }
 }
 
+/// Reads an OAuth2 application secret from a Google Cloud Console credential file.
+///
+/// Both `installed` (Desktop application) and `web` client sections are
+/// accepted; `installed` is preferred when both are present.
+fn read_application_secret(path: &Path) -> Result<ApplicationSecret> {
+    let display = path.display().to_string();
+    let json_str = fs::read_to_string(path)
+        .map_err(|e| Error::CredentialFileUnreadable(display.clone(), e))?;
+
+    let console: ConsoleApplicationSecret = serde_json::from_str(&json_str)
+        .map_err(|e| Error::CredentialFileInvalid(display.clone(), e))?;
+
+    console
+        .installed
+        .or(console.web)
+        .ok_or(Error::CredentialFileMissingClient(display))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -827,7 +891,6 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "could not read path")]
     fn test_config_invalid_credential_file() {
         let app_config = Config::builder()
             .set_default("credential_file", "nonexistent.json")
@@ -837,12 +900,14 @@ mod tests {
             .build()
             .unwrap();
 
-        // This should panic with "could not read path" since the code uses .expect()
-        let _result = ClientConfig::new_from_configuration(app_config);
+        let result = ClientConfig::new_from_configuration(app_config);
+        let err = result.unwrap_err();
+        assert!(matches!(err, Error::CredentialFileUnreadable(_, _)));
+        assert!(err.to_string().contains("nonexistent.json"));
+        assert!(err.to_string().contains("permission"));
     }
 
     #[test]
-    #[should_panic(expected = "could not convert to struct")]
     fn test_config_malformed_credential_file() {
         get_test_logger();
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -857,12 +922,13 @@ mod tests {
             .build()
             .unwrap();
 
-        // This should panic with "could not convert to struct" since the code uses .expect()
-        let _result = ClientConfig::new_from_configuration(app_config);
+        let result = ClientConfig::new_from_configuration(app_config);
+        let err = result.unwrap_err();
+        assert!(matches!(err, Error::CredentialFileInvalid(_, _)));
+        assert!(err.to_string().contains("malformed.json"));
     }
 
     #[test]
-    #[should_panic(expected = "called `Option::unwrap()` on a `None` value")]
     fn test_config_credential_file_wrong_structure() {
         get_test_logger();
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -878,8 +944,45 @@ mod tests {
             .build()
             .unwrap();
 
-        // This should panic with unwrap on None since console.installed is None
-        let _result = ClientConfig::new_from_configuration(app_config);
+        let result = ClientConfig::new_from_configuration(app_config);
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::CredentialFileMissingClient(path) if path.ends_with("wrong.json")
+        ));
+    }
+
+    #[test]
+    fn test_config_web_credential_file() {
+        get_test_logger();
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let web_credential = sample_valid_credential().replacen("installed", "web", 1);
+        let _cred_file = create_test_credential_file(&temp_dir, "web.json", &web_credential);
+
+        let config_root = format!("c:{}", temp_dir.path().display());
+        let app_config = Config::builder()
+            .set_default("credential_file", "web.json")
+            .unwrap()
+            .set_default("config_root", config_root.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let config = ClientConfig::new_from_configuration(app_config).unwrap();
+        assert!(!config.secret().client_id.is_empty());
+    }
+
+    #[test]
+    fn test_builder_with_missing_credential_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let result = ClientConfig::builder()
+            .with_config_path(temp_dir.path().to_str().unwrap())
+            .with_credential_file("missing.json")
+            .map(|builder| builder.build());
+
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::CredentialFileUnreadable(_, _)
+        ));
     }
 
     #[test]
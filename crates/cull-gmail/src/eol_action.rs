@@ -69,6 +69,8 @@
 
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
 /// Represents the action to take on Gmail messages that meet end-of-life criteria.
 ///
 /// This enum defines the two possible actions for handling messages when they
@@ -101,7 +103,8 @@ use std::fmt;
 /// // Converting to string for logging/display
 /// println!("Action: {}", delete); // Prints: "delete"
 /// ```
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EolAction {
     /// Move the message to Gmail's trash folder.
     ///
@@ -1,5 +1,15 @@
+use google_gmail1::common;
+use serde_json::Value;
 use thiserror::Error;
 
+/// Error reasons Gmail reports when a quota or rate limit is exhausted.
+const RATE_LIMIT_REASONS: [&str; 4] = [
+    "rateLimitExceeded",
+    "userRateLimitExceeded",
+    "quotaExceeded",
+    "dailyLimitExceeded",
+];
+
 /// Error messages for cull-gmail
 #[derive(Debug, Error)]
 pub enum Error {
@@ -27,6 +37,9 @@ pub enum Error {
     /// No label found in the mailbox
     #[error("Label {0} not found in the mailbox")]
     LabelNotFoundInMailbox(String),
+    /// System labels cannot be changed
+    #[error("Label `{0}` is a system label and cannot be changed")]
+    SystemLabel(String),
     /// Rule not found for ID
     #[error("No rule for id {0}")]
     RuleNotFound(usize),
@@ -36,9 +49,24 @@ pub enum Error {
     /// Directory creation failed for `{0}`
     #[error("Directory creation failed for `{0:?}`")]
     DirectoryCreationFailed((String, Box<std::io::Error>)),
+    /// Gmail API rate limit or quota exhausted
+    #[error("Gmail API rate limit exceeded: {0}")]
+    RateLimited(String),
+    /// OAuth2 token expired, was revoked, or could not be obtained
+    #[error("Gmail authorisation expired or revoked: {0}")]
+    AuthExpired(String),
+    /// OAuth2 token lacks the scope required by the request
+    #[error("Insufficient OAuth scope for request: {0}")]
+    InsufficientScope(String),
+    /// Requested Gmail resource does not exist
+    #[error("Gmail resource not found: {0}")]
+    NotFound(String),
+    /// Gmail API returned a server-side error
+    #[error("Gmail server error {0}: {1}")]
+    ServerError(u16, String),
     /// Error from the google_gmail1 crate
     #[error(transparent)]
-    GoogleGmail1(#[from] Box<google_gmail1::Error>),
+    GoogleGmail1(Box<google_gmail1::Error>),
     /// Error from std::io
     #[error(transparent)]
     StdIO(#[from] std::io::Error),
@@ -60,4 +88,232 @@ pub enum Error {
     /// Serialization/deserialization error
     #[error("Serialization error: {0}")]
     SerializationError(String),
+    /// Credential file could not be read
+    #[error(
+        "Could not read OAuth2 credentials file `{0}` (check the file exists and you have permission to read it, or run `cull-gmail init --credential-file <PATH>` to configure it)"
+    )]
+    CredentialFileUnreadable(String, #[source] std::io::Error),
+    /// Credential file is not valid JSON
+    #[error(
+        "OAuth2 credentials file `{0}` is not valid JSON (download the client JSON again from Google Cloud Console)"
+    )]
+    CredentialFileInvalid(String, #[source] serde_json::Error),
+    /// Credential file has neither an `installed` nor a `web` client section
+    #[error(
+        "OAuth2 credentials file `{0}` contains neither an `installed` nor a `web` client (create a Desktop application OAuth2 client in Google Cloud Console)"
+    )]
+    CredentialFileMissingClient(String),
+    /// Native TLS root certificates could not be loaded
+    #[error(
+        "Could not load native TLS root certificates (check the system CA certificate bundle is installed)"
+    )]
+    TlsRootsUnavailable(#[source] std::io::Error),
+    /// OAuth2 authenticator could not be built
+    #[error(
+        "Could not set up OAuth2 authentication with token cache `{0}` (check the directory exists and is writable)"
+    )]
+    AuthenticatorFailed(String, #[source] std::io::Error),
+    /// Gmail returned no raw source for a message
+    #[error("Gmail returned no raw content for message `{0}`")]
+    RawMessageMissing(String),
+    /// Filter criteria were requested without a sender or query
+    #[error("No filter criteria specified (i.e. --from or --query, or --off to remove them)")]
+    NoFilterCriteria,
+    /// Gmail filters XML could not be parsed
+    #[error("Could not read Gmail filters XML: {0}")]
+    MailFiltersInvalid(String),
+    /// Gmail created a label without returning its ID
+    #[error("Gmail returned no ID for the created label `{0}`")]
+    LabelIdMissing(String),
+    /// Gmail created a filter without returning its ID
+    #[error("Gmail returned no ID for the created filter")]
+    FilterIdMissing,
+    /// Exported messages could not be written
+    #[error("Could not write export `{0}` (check the directory exists and is writable)")]
+    ExportFailed(String, #[source] std::io::Error),
+    /// Export stopped part way; the messages written so far were kept
+    #[error("Export stopped after {1} message(s), which were kept in `{0}`: {2}")]
+    ExportIncomplete(String, usize, String),
+    /// Message ID list could not be read or parsed
+    #[error("Could not read message IDs from `{0}`: {1}")]
+    MessageIdListInvalid(String, String),
+    /// Message IDs that do not exist in the mailbox
+    #[error(
+        "{} message ID(s) not found in the mailbox: {} (use --skip-unknown to act on the rest)",
+        .0.len(),
+        .0.join(", ")
+    )]
+    UnknownMessageIds(Vec<String>),
+    /// Rule plan could not be read
+    #[error("Could not read plan: {0}")]
+    PlanInvalid(String),
+    /// Rule plan is older than allowed
+    #[error(
+        "Plan was made {0} hour(s) ago, more than the {1} hour(s) allowed (run `rules plan` again)"
+    )]
+    PlanStale(i64, i64),
+    /// Rules changed after the plan was made
+    #[error("The rules have changed since the plan was made (run `rules plan` again)")]
+    PlanRulesChanged,
+    /// The removed `execute` setting is still configured
+    #[error(
+        "The `execute` setting is no longer supported (remove it, and run `cull-gmail rules plan --out plan.json` then `cull-gmail rules apply plan.json` to change messages)"
+    )]
+    ExecuteUnsupported,
+    /// Rules test fixture has an invalid value
+    #[error("Invalid rules test fixture: {0}")]
+    FixtureInvalid(String),
+    /// Rules test fixture messages were not treated as expected
+    #[error("{0} of {1} fixture message(s) not treated as expected")]
+    FixturesFailed(usize, usize),
+    /// Interactive prompt could not read the user's answer
+    #[error("Interactive prompt failed: {0}")]
+    PromptFailed(String),
+    /// Rules file could not be written
+    #[error("Could not save rules to `{0}` (check the directory exists and is writable)")]
+    RulesSaveFailed(String, #[source] std::io::Error),
+    /// Rules file was changed by another process after it was loaded
+    #[error(
+        "The rules file `{0}` was changed by another process after it was loaded (run the command again)"
+    )]
+    RulesChanged(String),
+    /// Another process holds the rules file lock
+    #[error(
+        "The rules are being saved by another process (remove `{0}` if no other cull-gmail is running)"
+    )]
+    RulesLocked(String),
+}
+
+impl From<Box<google_gmail1::Error>> for Error {
+    /// Classifies a Gmail API error by HTTP status and error reason.
+    ///
+    /// Errors that do not match a typed variant are kept as
+    /// [`Error::GoogleGmail1`].
+    fn from(error: Box<google_gmail1::Error>) -> Self {
+        match error.as_ref() {
+            common::Error::BadRequest(value) => {
+                let (status, reason, message) = api_error_details(value);
+                classify(status, &reason, message).unwrap_or(Error::GoogleGmail1(error))
+            }
+            common::Error::Failure(response) => {
+                let status = response.status();
+                let message = status.canonical_reason().unwrap_or_default().to_string();
+                classify(Some(status.as_u16()), "", message).unwrap_or(Error::GoogleGmail1(error))
+            }
+            common::Error::MissingToken(e) => Error::AuthExpired(e.to_string()),
+            _ => Error::GoogleGmail1(error),
+        }
+    }
+}
+
+/// Extracts the HTTP status, first error reason and message from a Google API
+/// error body of the form `{"error": {"code": 403, "message": "...", "errors": [{"reason": "..."}]}}`.
+fn api_error_details(value: &Value) -> (Option<u16>, String, String) {
+    let error = &value["error"];
+    let status = error["code"].as_u64().and_then(|c| u16::try_from(c).ok());
+    let reason = error["errors"][0]["reason"]
+        .as_str()
+        .or_else(|| error["status"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    let message = error["message"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| value.to_string());
+
+    (status, reason, message)
+}
+
+/// Maps an HTTP status and Google error reason to a typed error variant.
+fn classify(status: Option<u16>, reason: &str, message: String) -> Option<Error> {
+    if status == Some(429) || RATE_LIMIT_REASONS.contains(&reason) {
+        return Some(Error::RateLimited(message));
+    }
+
+    match status? {
+        401 => Some(Error::AuthExpired(message)),
+        403 if reason == "insufficientPermissions"
+            || message.contains("insufficient authentication scopes") =>
+        {
+            Some(Error::InsufficientScope(message))
+        }
+        404 => Some(Error::NotFound(message)),
+        code @ 500..=599 => Some(Error::ServerError(code, message)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bad_request(code: u16, reason: &str, message: &str) -> Error {
+        let value = json!({
+            "error": {
+                "code": code,
+                "message": message,
+                "errors": [{ "reason": reason, "domain": "global", "message": message }]
+            }
+        });
+        Error::from(Box::new(common::Error::BadRequest(value)))
+    }
+
+    #[test]
+    fn test_rate_limited_by_status() {
+        let err = bad_request(429, "rateLimitExceeded", "Too many requests");
+        assert!(matches!(err, Error::RateLimited(m) if m == "Too many requests"));
+    }
+
+    #[test]
+    fn test_rate_limited_by_reason() {
+        let err = bad_request(403, "userRateLimitExceeded", "User rate limit exceeded");
+        assert!(matches!(err, Error::RateLimited(_)));
+    }
+
+    #[test]
+    fn test_auth_expired() {
+        let err = bad_request(401, "authError", "Invalid Credentials");
+        assert!(matches!(err, Error::AuthExpired(_)));
+
+        let err = Error::from(Box::new(common::Error::MissingToken(
+            "invalid_grant".into(),
+        )));
+        assert!(matches!(err, Error::AuthExpired(m) if m == "invalid_grant"));
+    }
+
+    #[test]
+    fn test_insufficient_scope() {
+        let err = bad_request(
+            403,
+            "insufficientPermissions",
+            "Request had insufficient authentication scopes.",
+        );
+        assert!(matches!(err, Error::InsufficientScope(_)));
+    }
+
+    #[test]
+    fn test_not_found() {
+        let err = bad_request(404, "notFound", "Requested entity was not found.");
+        assert!(matches!(err, Error::NotFound(_)));
+    }
+
+    #[test]
+    fn test_server_error() {
+        let err = bad_request(503, "backendError", "Backend Error");
+        assert!(matches!(err, Error::ServerError(503, _)));
+        assert_eq!(err.to_string(), "Gmail server error 503: Backend Error");
+    }
+
+    #[test]
+    fn test_unclassified_errors_are_preserved() {
+        let err = bad_request(400, "invalidArgument", "Invalid query");
+        assert!(matches!(err, Error::GoogleGmail1(_)));
+
+        let err = Error::from(Box::new(common::Error::BadRequest(json!("not an object"))));
+        assert!(matches!(err, Error::GoogleGmail1(_)));
+
+        let err = Error::from(Box::new(common::Error::Cancelled));
+        assert!(matches!(err, Error::GoogleGmail1(_)));
+    }
 }
@@ -0,0 +1,216 @@
+//! # Filter Manager Module
+//!
+//! This module provides the [`FilterManager`] trait for creating, reading and
+//! deleting the Gmail filters that label incoming mail for retention rules.
+//!
+//! ## Overview
+//!
+//! A rule with sender or query criteria describes a [`RuleFilter`]: incoming
+//! messages matching the criteria are given the rule's labels, so the rule
+//! picks them up once they are old enough. Gmail filters cannot be changed in
+//! place, so a filter is updated by deleting it and creating a new one.
+//!
+//! Filter labels are referred to by name and resolved through the client's
+//! label map, so the labels must exist before a filter is created.
+//!
+//! ## Usage Examples
+//!
+//! ```rust,no_run
+//! use cull_gmail::{ClientConfig, FilterManager, GmailClient, RuleFilter};
+//!
+//! # async fn example(config: ClientConfig) -> cull_gmail::Result<()> {
+//! let client = GmailClient::new_with_config(config).await?;
+//! let filter = RuleFilter {
+//!     from: Some("news@example.com".to_string()),
+//!     query: None,
+//!     labels: vec!["retention/1-years".to_string()],
+//! };
+//! let id = client.create_filter(&filter).await?;
+//! assert_eq!(client.get_filter(&id).await?, Some(filter));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Gmail API Requirements
+//!
+//! Gmail only allows filters to be managed with the
+//! `https://www.googleapis.com/auth/gmail.settings.basic` scope, which is
+//! separate from the message scopes. It is requested on first use, so a
+//! browser consent may be shown even when a token is cached.
+
+use std::future::Future;
+
+use google_gmail1::api::{Filter, FilterAction, FilterCriteria};
+use serde::Serialize;
+
+use crate::{Error, GmailClient, Result};
+
+/// OAuth2 scope required to create and delete Gmail filters.
+const FILTER_SCOPE: &str = "https://www.googleapis.com/auth/gmail.settings.basic";
+
+/// A Gmail filter that applies labels to incoming mail.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RuleFilter {
+    /// Senders to match, separated by ` OR `
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Gmail search query to match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Names of the labels applied to matching messages, sorted
+    pub labels: Vec<String>,
+}
+
+/// Operations for managing the Gmail filters created for rules.
+pub trait FilterManager {
+    /// Fetches a filter by ID.
+    ///
+    /// Label IDs are translated to names; IDs of unknown labels are kept as
+    /// they are. Returns `None` if the filter no longer exists.
+    ///
+    /// # Errors
+    ///
+    /// * Gmail API errors other than the filter not being found
+    fn get_filter(&self, id: &str) -> impl Future<Output = Result<Option<RuleFilter>>> + Send;
+
+    /// Lists all filters in the mailbox with their IDs.
+    ///
+    /// Label IDs are translated to names as for [`get_filter`](Self::get_filter).
+    ///
+    /// # Errors
+    ///
+    /// * Gmail API errors
+    fn list_filters(&self) -> impl Future<Output = Result<Vec<(String, RuleFilter)>>> + Send;
+
+    /// Creates a filter and returns its ID.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LabelNotFoundInMailbox`] if one of the labels does not exist
+    /// * Gmail API errors, for example when an identical filter already exists
+    fn create_filter(&self, filter: &RuleFilter) -> impl Future<Output = Result<String>> + Send;
+
+    /// Deletes a filter. A filter that no longer exists is not an error.
+    ///
+    /// # Errors
+    ///
+    /// * Gmail API errors other than the filter not being found
+    fn delete_filter(&self, id: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl GmailClient {
+    /// Converts a Gmail filter to a [`RuleFilter`], naming its labels.
+    fn rule_filter_from(&self, filter: Filter) -> RuleFilter {
+        let criteria = filter.criteria.unwrap_or_default();
+        let mut labels = filter
+            .action
+            .and_then(|a| a.add_label_ids)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|id| {
+                self.label_map
+                    .iter()
+                    .find(|(_, label_id)| **label_id == id)
+                    .map_or(id, |(name, _)| name.clone())
+            })
+            .collect::<Vec<_>>();
+        labels.sort();
+
+        RuleFilter {
+            from: criteria.from,
+            query: criteria.query,
+            labels,
+        }
+    }
+}
+
+impl FilterManager for GmailClient {
+    async fn get_filter(&self, id: &str) -> Result<Option<RuleFilter>> {
+        let result = self
+            .hub()
+            .users()
+            .settings_filters_get("me", id)
+            .add_scope(FILTER_SCOPE)
+            .doit()
+            .await
+            .map_err(|e| Error::from(Box::new(e)));
+
+        match result {
+            Ok((_res, filter)) => Ok(Some(self.rule_filter_from(filter))),
+            Err(Error::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn list_filters(&self) -> Result<Vec<(String, RuleFilter)>> {
+        let (_res, list) = self
+            .hub()
+            .users()
+            .settings_filters_list("me")
+            .add_scope(FILTER_SCOPE)
+            .doit()
+            .await
+            .map_err(Box::new)?;
+
+        Ok(list
+            .filter
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|filter| {
+                let id = filter.id.clone()?;
+                Some((id, self.rule_filter_from(filter)))
+            })
+            .collect())
+    }
+
+    async fn create_filter(&self, filter: &RuleFilter) -> Result<String> {
+        let label_ids = filter
+            .labels
+            .iter()
+            .map(|name| {
+                self.get_label_id(name)
+                    .ok_or_else(|| Error::LabelNotFoundInMailbox(name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let request = Filter {
+            criteria: Some(FilterCriteria {
+                from: filter.from.clone(),
+                query: filter.query.clone(),
+                ..Default::default()
+            }),
+            action: Some(FilterAction {
+                add_label_ids: Some(label_ids),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let (_res, created) = self
+            .hub()
+            .users()
+            .settings_filters_create(request, "me")
+            .add_scope(FILTER_SCOPE)
+            .doit()
+            .await
+            .map_err(Box::new)?;
+
+        created.id.ok_or(Error::FilterIdMissing)
+    }
+
+    async fn delete_filter(&self, id: &str) -> Result<()> {
+        let result = self
+            .hub()
+            .users()
+            .settings_filters_delete("me", id)
+            .add_scope(FILTER_SCOPE)
+            .doit()
+            .await
+            .map_err(|e| Error::from(Box::new(e)));
+
+        match result {
+            Ok(_) | Err(Error::NotFound(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -96,7 +96,10 @@
 //! [`ClientConfig`]: crate::ClientConfig
 //! [`Error`]: crate::Error
 
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
 
 use google_gmail1::{
     Gmail,
@@ -110,9 +113,12 @@ use google_gmail1::{
 
 mod message_summary;
 
-pub(crate) use message_summary::MessageSummary;
+pub use message_summary::MessageSummary;
 
-use crate::{ClientConfig, Error, Result, rules::EolRule};
+use crate::{
+    ClientConfig, Error, GmailScope, Result, label_manager::SYSTEM_LABEL_TYPE,
+    message_list::GmailService, rules::EolRule,
+};
 
 /// Default maximum number of results to return per page from Gmail API calls.
 ///
@@ -154,12 +160,16 @@ pub const DEFAULT_MAX_RESULTS: &str = "200";
 #[derive(Clone)]
 pub struct GmailClient {
     hub: Gmail<HttpsConnector<HttpConnector>>,
-    label_map: BTreeMap<String, String>,
+    pub(crate) label_map: BTreeMap<String, String>,
+    pub(crate) system_label_ids: BTreeSet<String>,
+    scope: GmailScope,
     pub(crate) max_results: u32,
+    pub(crate) max_messages: Option<u32>,
     pub(crate) label_ids: Vec<String>,
     pub(crate) query: String,
     pub(crate) messages: Vec<MessageSummary>,
     pub(crate) rule: Option<EolRule>,
+    pub(crate) message_budget: Option<u32>,
     pub(crate) execute: bool,
 }
 
@@ -167,10 +177,14 @@ impl std::fmt::Debug for GmailClient {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("GmailClient")
             .field("label_map", &self.label_map)
+            .field("system_label_ids", &self.system_label_ids)
+            .field("scope", &self.scope)
             .field("max_results", &self.max_results)
+            .field("max_messages", &self.max_messages)
             .field("label_ids", &self.label_ids)
             .field("query", &self.query)
             .field("messages_count", &self.messages.len())
+            .field("message_budget", &self.message_budget)
             .field("execute", &self.execute)
             .finish_non_exhaustive()
     }
@@ -203,6 +217,10 @@ impl GmailClient {
     /// "installed application" flow. It sets up the HTTPS connector, authenticates
     /// using the provided credentials, and fetches the label mapping from Gmail.
     ///
+    /// The configured scope is widened to a broader one when only a token for
+    /// that scope is cached, so the cached token is reused rather than a new
+    /// consent flow started (see [`GmailScope::widen_to_cached`]).
+    ///
     /// # Arguments
     ///
     /// * `config` - Client configuration containing OAuth2 credentials and settings
@@ -220,6 +238,8 @@ impl GmailClient {
     /// - [`Error::GoogleGmail1`] - Gmail API errors during authentication or label fetch
     /// - Network connectivity issues during OAuth2 flow
     /// - [`Error::NoLabelsFound`] - If no labels exist in the mailbox (unusual)
+    /// - [`Error::TlsRootsUnavailable`] - If the native TLS root certificates cannot be loaded
+    /// - [`Error::AuthenticatorFailed`] - If the OAuth2 authenticator or token cache cannot be set up
     ///
     /// # Examples
     ///
@@ -240,18 +260,17 @@ impl GmailClient {
     ///
     /// # Panics
     ///
-    /// This method contains `.unwrap()` calls for:
-    /// - HTTPS connector building (should not fail with valid TLS setup)
-    /// - Default max results parsing (hardcoded valid string)
-    /// - OAuth2 authenticator building (should not fail with valid config)
+    /// Parsing the hardcoded [`DEFAULT_MAX_RESULTS`] is unwrapped and cannot fail.
     ///
     /// [`Error::GoogleGmail1`]: crate::Error::GoogleGmail1
     /// [`Error::NoLabelsFound`]: crate::Error::NoLabelsFound
+    /// [`Error::TlsRootsUnavailable`]: crate::Error::TlsRootsUnavailable
+    /// [`Error::AuthenticatorFailed`]: crate::Error::AuthenticatorFailed
     pub async fn new_with_config(config: ClientConfig) -> Result<Self> {
         let executor = TokioExecutor::new();
         let connector = HttpsConnectorBuilder::new()
             .with_native_roots()
-            .unwrap()
+            .map_err(Error::TlsRootsUnavailable)?
             .https_or_http()
             .enable_http1()
             .build();
@@ -259,6 +278,24 @@ impl GmailClient {
         let client = Client::builder(executor.clone()).build(connector.clone());
         log::trace!("file to persist tokens to `{}`", config.persist_path());
 
+        let scope = config.scope().widen_to_cached(config.persist_path());
+        if scope != config.scope() {
+            log::debug!(
+                "using the cached token for `{scope}` access, which covers `{}`",
+                config.scope()
+            );
+        }
+        log::debug!("requesting `{scope}` access ({})", scope.as_url());
+        if !Path::new(config.persist_path()).exists() {
+            log::info!("no cached token found, authorisation will be requested in the browser");
+        } else if !scope.is_cached_in(config.persist_path()) {
+            log::warn!(
+                "cached token at `{}` was not granted `{scope}` access ({}); authorisation will be requested in the browser",
+                config.persist_path(),
+                scope.as_url()
+            );
+        }
+
         let auth_client = Client::builder(executor).build(connector);
         let auth = InstalledFlowAuthenticator::with_client(
             config.secret().clone(),
@@ -268,19 +305,23 @@ impl GmailClient {
         .persist_tokens_to_disk(config.persist_path())
         .build()
         .await
-        .unwrap();
+        .map_err(|e| Error::AuthenticatorFailed(config.persist_path().to_string(), e))?;
 
         let hub = Gmail::new(client, auth);
-        let label_map = GmailClient::get_label_map(&hub).await?;
+        let (label_map, system_label_ids) = GmailClient::get_label_map(&hub, scope).await?;
 
         Ok(GmailClient {
             hub,
             label_map,
+            system_label_ids,
+            scope,
             max_results: DEFAULT_MAX_RESULTS.parse::<u32>().unwrap(),
+            max_messages: None,
             label_ids: Vec::new(),
             query: String::new(),
             messages: Vec::new(),
             rule: None,
+            message_budget: None,
             execute: false,
         })
     }
@@ -288,16 +329,18 @@ impl GmailClient {
     /// Fetches the label mapping from Gmail API.
     ///
     /// This method retrieves all labels from the user's Gmail account and creates
-    /// a mapping from label names to their corresponding label IDs.
+    /// a mapping from label names to their corresponding label IDs, together
+    /// with the IDs of the labels Gmail reports as `system` labels.
     ///
     /// # Arguments
     ///
     /// * `hub` - The Gmail API hub instance for making API calls
+    /// * `scope` - The OAuth2 scope to authorise the call with
     ///
     /// # Returns
     ///
-    /// Returns a `BTreeMap` containing label name to ID mappings, or an error if
-    /// the API call fails or no labels are found.
+    /// Returns a `BTreeMap` containing label name to ID mappings and the set of
+    /// system label IDs, or an error if the API call fails or no labels are found.
     ///
     /// # Errors
     ///
@@ -308,10 +351,11 @@ impl GmailClient {
     /// [`Error::NoLabelsFound`]: crate::Error::NoLabelsFound
     async fn get_label_map(
         hub: &Gmail<HttpsConnector<HttpConnector>>,
-    ) -> Result<BTreeMap<String, String>> {
+        scope: GmailScope,
+    ) -> Result<(BTreeMap<String, String>, BTreeSet<String>)> {
         let call = hub.users().labels_list("me");
         let (_response, list) = call
-            .add_scope("https://mail.google.com/")
+            .add_scope(scope.as_url())
             .doit()
             .await
             .map_err(Box::new)?;
@@ -321,15 +365,19 @@ impl GmailClient {
         };
 
         let mut label_map = BTreeMap::new();
+        let mut system_label_ids = BTreeSet::new();
         for label in &label_list {
             if label.id.is_some() && label.name.is_some() {
                 let name = label.name.clone().unwrap();
                 let id = label.id.clone().unwrap();
+                if label.type_.as_deref() == Some(SYSTEM_LABEL_TYPE) {
+                    system_label_ids.insert(id.clone());
+                }
                 label_map.insert(name, id);
             }
         }
 
-        Ok(label_map)
+        Ok((label_map, system_label_ids))
     }
 
     /// Retrieves the Gmail label ID for a given label name.
@@ -367,6 +415,34 @@ impl GmailClient {
         self.label_map.get(name).cloned()
     }
 
+    /// Returns the mailbox's label names mapped to their label IDs.
+    ///
+    /// Pass this to [`Rules::evaluate`](crate::Rules::evaluate) to check rules
+    /// against messages fetched by this client.
+    pub fn label_map(&self) -> &BTreeMap<String, String> {
+        &self.label_map
+    }
+
+    /// Retrieves the Gmail label name for a given label ID.
+    ///
+    /// This is the reverse of [`GmailClient::get_label_id`] and is useful for
+    /// displaying the labels applied to a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The label ID to look up (e.g. `Label_12` or `INBOX`)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(String)` containing the label name if the ID exists,
+    /// or `None` if it is not found.
+    pub fn get_label_name(&self, id: &str) -> Option<String> {
+        self.label_map
+            .iter()
+            .find(|(_, label_id)| label_id.as_str() == id)
+            .map(|(name, _)| name.clone())
+    }
+
     /// Displays all available labels and their IDs to the log.
     ///
     /// This method iterates through the internal label mapping and outputs each
@@ -398,6 +474,77 @@ impl GmailClient {
         }
     }
 
+    /// Returns the OAuth2 scope the client was authorised with.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use cull_gmail::{GmailClient, GmailScope};
+    /// # fn example(client: &GmailClient) {
+    /// if !client.scope().covers(GmailScope::Full) {
+    ///     println!("permanent deletion is not available");
+    /// }
+    /// # }
+    /// ```
+    pub fn scope(&self) -> GmailScope {
+        self.scope
+    }
+
+    /// Returns the scope URL to authorise a call that needs at least `required`.
+    ///
+    /// The client's own scope is used whenever it is sufficient so that every
+    /// call shares a single cached token; otherwise `required` is requested.
+    pub(crate) fn scope_for(&self, required: GmailScope) -> &'static str {
+        self.scope.max(required).as_url()
+    }
+
+    /// Returns the total number of messages in the mailbox.
+    ///
+    /// # Errors
+    ///
+    /// * Gmail API errors while fetching the mailbox profile
+    pub async fn messages_total(&self) -> Result<u32> {
+        let (_response, profile) = self
+            .hub
+            .users()
+            .get_profile("me")
+            .add_scope(self.scope_for(GmailScope::ReadOnly))
+            .doit()
+            .await
+            .map_err(Box::new)?;
+
+        Ok(profile
+            .messages_total
+            .and_then(|n| u32::try_from(n).ok())
+            .unwrap_or_default())
+    }
+
+    /// Returns Gmail's estimate of the number of messages a rule selects
+    /// under a label.
+    ///
+    /// A single page is requested and Gmail's result size estimate used, so
+    /// the count is cheap but approximate. The rule's `max_messages` limit is
+    /// not applied.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LabelNotFoundInMailbox`] if the label does not exist
+    /// * [`Error::NoQueryStringCalculated`] if the rule's retention is invalid
+    /// * Gmail API errors while listing messages
+    pub async fn estimate_rule_messages(&self, rule: &EolRule, label: &str) -> Result<u32> {
+        let Some(label_id) = self.get_label_id(label) else {
+            return Err(Error::LabelNotFoundInMailbox(label.to_string()));
+        };
+        let Some(query) = rule.eol_query() else {
+            return Err(Error::NoQueryStringCalculated(rule.id()));
+        };
+
+        let list = self
+            .list_messages_page(&[label_id], &query, 1, None)
+            .await?;
+        Ok(list.result_size_estimate.unwrap_or_default())
+    }
+
     /// Returns a clone of the Gmail API hub for direct API access.
     ///
     /// This method provides access to the underlying Gmail API client hub,
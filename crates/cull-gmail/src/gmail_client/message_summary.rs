@@ -3,92 +3,160 @@
 //! This module provides the `MessageSummary` struct for representing Gmail message metadata
 //! in a simplified format suitable for display and processing.
 
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use google_gmail1::api::Message as GmailMessage;
+
 use crate::utils::Elide;
 
 /// A simplified representation of Gmail message metadata.
 ///
-/// `MessageSummary` stores essential message information including ID, subject, and date.
-/// It provides methods for accessing this information with fallback text for missing data.
+/// `MessageSummary` stores the message and thread IDs, the Subject, Date, From,
+/// To and List-Id headers, the size estimate, label IDs, snippet and the time
+/// Gmail received the message. It provides methods for accessing this
+/// information with fallback text for missing data.
+///
+/// Summaries are created with only an ID when messages are listed and are
+/// filled in once the message metadata has been fetched.
 ///
 /// # Examples
 ///
 /// ```rust,no_run
-/// // MessageSummary is pub(crate), so this example is for illustration only
-/// # struct MessageSummary { id: String, subject: Option<String>, date: Option<String> }
-/// # impl MessageSummary {
-/// #     fn new(id: &str) -> Self { Self { id: id.to_string(), subject: None, date: None } }
-/// #     fn set_subject(&mut self, subject: Option<String>) { self.subject = subject; }
-/// #     fn set_date(&mut self, date: Option<String>) { self.date = date; }
-/// #     fn subject(&self) -> &str { self.subject.as_deref().unwrap_or("*** No Subject ***") }
-/// #     fn date(&self) -> &str { self.date.as_deref().unwrap_or("*** No Date ***") }
-/// #     fn list_date_and_subject(&self) -> String { format!("Date: {}, Subject: {}", self.date(), self.subject()) }
-/// # }
-/// let mut summary = MessageSummary::new("message_123");
-/// summary.set_subject(Some("Hello World".to_string()));
-/// summary.set_date(Some("2023-01-15 10:30:00".to_string()));
+/// use cull_gmail::{GmailClient, MessageList};
 ///
-/// println!("Subject: {}", summary.subject());
-/// println!("Date: {}", summary.date());
-/// println!("Summary: {}", summary.list_date_and_subject());
+/// # async fn example(client: &mut GmailClient) -> cull_gmail::Result<()> {
+/// client.get_messages(1).await?;
+/// client.log_messages("", "").await?;
+///
+/// for summary in client.messages() {
+///     println!(
+///         "{} {} {} bytes from {}",
+///         summary.id(),
+///         summary.subject(),
+///         summary.size_estimate().unwrap_or_default(),
+///         summary.from().unwrap_or("unknown sender"),
+///     );
+/// }
+/// # Ok(())
+/// # }
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct MessageSummary {
     id: String,
+    thread_id: Option<String>,
     date: Option<String>,
     subject: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    list_id: Option<String>,
+    size_estimate: Option<u32>,
+    label_ids: Vec<String>,
+    snippet: Option<String>,
+    internal_date: Option<DateTime<Utc>>,
 }
 
 impl MessageSummary {
     /// Creates a new `MessageSummary` with the given message ID.
     ///
-    /// The subject and date fields are initialized as `None` and can be set later
-    /// using the setter methods.
+    /// All other fields are empty until set from the message metadata, or
+    /// with the `with_` methods when building a summary without Gmail, for
+    /// example to [evaluate](crate::EolRule::evaluate) rules offline.
     ///
     /// # Arguments
     ///
     /// * `id` - The Gmail message ID
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// # struct MessageSummary(String);
-    /// # impl MessageSummary {
-    /// #     fn new(id: &str) -> Self { Self(id.to_string()) }
-    /// #     fn id(&self) -> &str { &self.0 }
-    /// # }
-    /// let summary = MessageSummary::new("1234567890abcdef");
-    /// assert_eq!(summary.id(), "1234567890abcdef");
-    /// ```
-    pub(crate) fn new(id: &str) -> Self {
+    pub fn new(id: &str) -> Self {
         MessageSummary {
             id: id.to_string(),
-            date: None,
-            subject: None,
+            ..Default::default()
         }
     }
 
-    /// Returns the Gmail message ID.
+    /// Updates the summary from a Gmail API message fetched in `metadata` format.
     ///
-    /// # Examples
+    /// Headers not present in the message leave the existing values untouched.
+    pub(crate) fn update_from_message(&mut self, message: &GmailMessage) {
+        if let Some(thread_id) = &message.thread_id {
+            self.thread_id = Some(thread_id.clone());
+        }
+        if let Some(label_ids) = &message.label_ids {
+            self.label_ids = label_ids.clone();
+        }
+        if let Some(snippet) = &message.snippet {
+            self.snippet = Some(snippet.clone());
+        }
+        if let Some(size) = message.size_estimate {
+            self.size_estimate = u32::try_from(size).ok();
+        }
+        if let Some(millis) = message.internal_date {
+            self.internal_date = Utc.timestamp_millis_opt(millis).single();
+        }
+
+        let headers = message
+            .payload
+            .as_ref()
+            .and_then(|payload| payload.headers.as_ref());
+
+        for header in headers.into_iter().flatten() {
+            if let Some(name) = &header.name {
+                self.set_header(name, header.value.clone());
+            }
+        }
+    }
+
+    /// Sets the field matching a message header name (case-insensitive).
     ///
-    /// ```rust,no_run
-    /// # fn example() { }
-    /// ```
-    pub(crate) fn id(&self) -> &str {
+    /// Headers other than Subject, Date, From, To and List-Id are ignored.
+    pub(crate) fn set_header(&mut self, name: &str, value: Option<String>) {
+        match name.to_lowercase().as_str() {
+            "subject" => self.subject = value,
+            "date" => self.date = value,
+            "from" => self.from = value,
+            "to" => self.to = value,
+            "list-id" => self.list_id = value,
+            _ => {}
+        }
+    }
+
+    /// Adds label IDs (or label names) to the message.
+    pub fn with_labels(mut self, labels: &[&str]) -> Self {
+        self.label_ids
+            .extend(labels.iter().map(|label| label.to_string()));
+        self
+    }
+
+    /// Sets the time Gmail received the message.
+    pub fn with_internal_date<Tz: TimeZone>(mut self, date: DateTime<Tz>) -> Self {
+        self.internal_date = Some(date.to_utc());
+        self
+    }
+
+    /// Sets the From header of the message.
+    pub fn with_from(mut self, from: &str) -> Self {
+        self.from = Some(from.to_string());
+        self
+    }
+
+    /// Sets Gmail's estimate of the message size in bytes.
+    pub fn with_size_estimate(mut self, size: u32) -> Self {
+        self.size_estimate = Some(size);
+        self
+    }
+
+    /// Returns the Gmail message ID.
+    pub fn id(&self) -> &str {
         &self.id
     }
 
+    /// Returns the ID of the thread the message belongs to, if known.
+    pub fn thread_id(&self) -> Option<&str> {
+        self.thread_id.as_deref()
+    }
+
     /// Sets the subject line of the message.
     ///
     /// # Arguments
     ///
     /// * `subject` - Optional subject line text
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// # fn example() { }
-    /// ```
     pub(crate) fn set_subject(&mut self, subject: Option<String>) {
         self.subject = subject
     }
@@ -98,13 +166,7 @@ impl MessageSummary {
     /// # Returns
     ///
     /// The subject line if available, otherwise "*** No Subject for Message ***".
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// # fn example() { }
-    /// ```
-    pub(crate) fn subject(&self) -> &str {
+    pub fn subject(&self) -> &str {
         if let Some(s) = &self.subject {
             s
         } else {
@@ -116,29 +178,18 @@ impl MessageSummary {
     ///
     /// # Arguments
     ///
-    /// * `date` - Optional date string (typically in RFC format)
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// # fn example() { }
-    /// ```
+    /// * `date` - Optional date string (typically in RFC 2822 format)
     pub(crate) fn set_date(&mut self, date: Option<String>) {
         self.date = date
     }
 
-    /// Returns the message date or a fallback message if none is set.
+    /// Returns the raw Date header or a fallback message if none is set.
     ///
     /// # Returns
     ///
     /// The date string if available, otherwise "*** No Date for Message ***".
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// # fn example() { }
-    /// ```
-    pub(crate) fn date(&self) -> &str {
+    /// Use [`MessageSummary::date_time`] for a parsed date.
+    pub fn date(&self) -> &str {
         if let Some(d) = &self.date {
             d
         } else {
@@ -146,35 +197,107 @@ impl MessageSummary {
         }
     }
 
+    /// Returns the message date parsed from the Date header.
+    ///
+    /// The header is parsed as RFC 2822, ignoring a trailing comment such as
+    /// `(UTC)`. When the header is missing or malformed the time Gmail received
+    /// the message ([`MessageSummary::internal_date`]) is used instead.
+    pub fn date_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.date
+            .as_deref()
+            .and_then(parse_date_header)
+            .or_else(|| self.internal_date.map(|d| d.fixed_offset()))
+    }
+
+    /// Returns the From header, if present.
+    pub fn from(&self) -> Option<&str> {
+        self.from.as_deref()
+    }
+
+    /// Returns the lower-case sender address from the From header, if present.
+    ///
+    /// Accepts both `Name <address>` and bare address forms.
+    pub fn sender_address(&self) -> Option<String> {
+        let from = self.from.as_deref()?;
+        let address = match (from.rfind('<'), from.rfind('>')) {
+            (Some(start), Some(end)) if start < end => &from[start + 1..end],
+            _ => from,
+        };
+        let address = address.trim().trim_matches('"');
+        (!address.is_empty()).then(|| address.to_lowercase())
+    }
+
+    /// Returns when the message was received.
+    ///
+    /// This is the time Gmail received the message, falling back to the Date
+    /// header when that is unknown.
+    pub fn received(&self) -> Option<DateTime<Utc>> {
+        self.internal_date
+            .or_else(|| self.date_time().map(|date| date.to_utc()))
+    }
+
+    /// Returns the To header, if present.
+    pub fn to(&self) -> Option<&str> {
+        self.to.as_deref()
+    }
+
+    /// Returns the List-Id header of mailing list messages, if present.
+    pub fn list_id(&self) -> Option<&str> {
+        self.list_id.as_deref()
+    }
+
+    /// Returns Gmail's estimate of the message size in bytes, if known.
+    pub fn size_estimate(&self) -> Option<u32> {
+        self.size_estimate
+    }
+
+    /// Returns the IDs of the labels applied to the message.
+    pub fn label_ids(&self) -> &[String] {
+        &self.label_ids
+    }
+
+    /// Returns the short snippet of the message body, if known.
+    pub fn snippet(&self) -> Option<&str> {
+        self.snippet.as_deref()
+    }
+
+    /// Returns the time Gmail received the message, if known.
+    pub fn internal_date(&self) -> Option<DateTime<Utc>> {
+        self.internal_date
+    }
+
     /// Creates a formatted string combining date and subject for list display.
     ///
-    /// This method extracts a portion of the date (characters 5-16) and combines it
-    /// with an elided version of the subject line for compact display in message lists.
+    /// The date is shown as day, month and year (e.g. `25 Dec 2023`) and the
+    /// subject line is elided to 24 characters for compact display in message lists.
     ///
     /// # Returns
     ///
     /// A formatted string with date and subject, or an error message if either
-    /// field is missing.
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// # fn example() { }
-    /// ```
-    pub(crate) fn list_date_and_subject(&self) -> String {
-        let Some(date) = self.date.as_ref() else {
+    /// field is missing or the date cannot be parsed.
+    pub fn list_date_and_subject(&self) -> String {
+        let Some(date) = self.date_time() else {
             return "***invalid date or subject***".to_string();
         };
 
         let Some(subject) = self.subject.as_ref() else {
             return "***invalid date or subject***".to_string();
         };
-        let s = date[5..16].to_string();
-        let s = format!("{s}: {}", subject.clone().elide(24));
-        s
+
+        format!("{}: {}", date.format("%d %b %Y"), subject.clone().elide(24))
     }
 }
 
+/// Parses an RFC 2822 Date header, ignoring any trailing comment.
+fn parse_date_header(date: &str) -> Option<DateTime<FixedOffset>> {
+    let date = date.trim();
+    let date = match date.find(" (") {
+        Some(idx) => &date[..idx],
+        None => date,
+    };
+    DateTime::parse_from_rfc2822(date).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,20 +349,126 @@ mod tests {
         let mut summary = MessageSummary::new("test_id");
 
         // Set up a realistic date and subject
-        summary.set_date(Some("2023-12-25 10:30:00 GMT".to_string()));
+        summary.set_date(Some("Mon, 25 Dec 2023 10:30:00 +0000".to_string()));
         summary.set_subject(Some(
             "This is a very long subject that should be elided".to_string(),
         ));
 
         let display = summary.list_date_and_subject();
 
-        // The method extracts characters 5-16 from date and elides subject to 24 chars
-        // "2023-12-25 10:30:00 GMT" -> chars 5-16 would be "2-25 10:30"
-        assert!(display.contains("2-25 10:30"));
-        assert!(display.contains(":"));
+        // The date is parsed and shown as day, month and year; the subject is elided
+        assert!(display.starts_with("25 Dec 2023: "));
         assert!(display.len() <= 40); // Should be reasonably short for display
     }
 
+    #[test]
+    fn test_message_summary_list_date_and_subject_unparseable_date() {
+        let mut summary = MessageSummary::new("test_id");
+        summary.set_date(Some("2023-12-25 10:30:00 GMT".to_string()));
+        summary.set_subject(Some("Subject".to_string()));
+
+        assert_eq!(
+            summary.list_date_and_subject(),
+            "***invalid date or subject***"
+        );
+    }
+
+    #[test]
+    fn test_message_summary_date_time_parsing() {
+        let mut summary = MessageSummary::new("test_id");
+        summary.set_date(Some("Tue, 14 Jan 2025 10:30:00 -0500 (EST)".to_string()));
+
+        let date = summary.date_time().expect("date should parse");
+        assert_eq!(date.to_rfc3339(), "2025-01-14T10:30:00-05:00");
+
+        // Without a weekday
+        summary.set_date(Some("14 Jan 2025 10:30:00 +0000".to_string()));
+        assert!(summary.date_time().is_some());
+
+        summary.set_date(None);
+        assert!(summary.date_time().is_none());
+    }
+
+    #[test]
+    fn test_message_summary_update_from_message() {
+        use google_gmail1::api::{MessagePart, MessagePartHeader};
+
+        let header = |name: &str, value: &str| MessagePartHeader {
+            name: Some(name.to_string()),
+            value: Some(value.to_string()),
+        };
+        let message = GmailMessage {
+            id: Some("m1".to_string()),
+            thread_id: Some("t1".to_string()),
+            label_ids: Some(vec!["INBOX".to_string(), "UNREAD".to_string()]),
+            snippet: Some("Hello there".to_string()),
+            size_estimate: Some(2048),
+            internal_date: Some(1_700_000_000_000),
+            payload: Some(MessagePart {
+                headers: Some(vec![
+                    header("Subject", "Weekly news"),
+                    header("From", "News <news@example.com>"),
+                    header("To", "me@example.com"),
+                    header("List-Id", "<news.example.com>"),
+                    header("X-Other", "ignored"),
+                ]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let mut summary = MessageSummary::new("m1");
+        summary.update_from_message(&message);
+
+        assert_eq!(summary.thread_id(), Some("t1"));
+        assert_eq!(summary.label_ids(), ["INBOX", "UNREAD"]);
+        assert_eq!(summary.snippet(), Some("Hello there"));
+        assert_eq!(summary.size_estimate(), Some(2048));
+        assert_eq!(summary.subject(), "Weekly news");
+        assert_eq!(summary.from(), Some("News <news@example.com>"));
+        assert_eq!(summary.to(), Some("me@example.com"));
+        assert_eq!(summary.list_id(), Some("<news.example.com>"));
+        assert_eq!(
+            summary.internal_date().unwrap().to_rfc3339(),
+            "2023-11-14T22:13:20+00:00"
+        );
+
+        // No Date header, so the internal date is used
+        assert_eq!(
+            summary.date_time().unwrap().to_rfc3339(),
+            "2023-11-14T22:13:20+00:00"
+        );
+    }
+
+    #[test]
+    fn test_message_summary_sender_and_received() {
+        let summary = MessageSummary::new("m1").with_from("\"News, Inc\" <News@Example.COM>");
+        assert_eq!(
+            summary.sender_address(),
+            Some("news@example.com".to_string())
+        );
+        assert_eq!(summary.received(), None);
+
+        let summary = summary.with_from(" bare@example.org ");
+        assert_eq!(
+            summary.sender_address(),
+            Some("bare@example.org".to_string())
+        );
+
+        let mut summary = summary.with_from("<>");
+        assert_eq!(summary.sender_address(), None);
+
+        summary.set_date(Some("Tue, 14 Jan 2025 10:30:00 -0500".to_string()));
+        assert_eq!(
+            summary.received().unwrap().to_rfc3339(),
+            "2025-01-14T15:30:00+00:00"
+        );
+
+        let received = Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap();
+        let summary = summary.with_internal_date(received);
+        assert_eq!(summary.received(), Some(received));
+    }
+
     #[test]
     fn test_message_summary_list_date_and_subject_missing_fields() {
         let mut summary = MessageSummary::new("test_id");
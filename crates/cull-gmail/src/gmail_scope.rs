@@ -0,0 +1,322 @@
+//! # Gmail Scope Module
+//!
+//! This module defines the OAuth2 scopes cull-gmail requests when talking to
+//! the Gmail API and selects the least privileged scope needed for a task.
+//!
+//! ## Overview
+//!
+//! Gmail offers a hierarchy of scopes. cull-gmail uses three of them:
+//!
+//! | Variant | Scope URL | Allows |
+//! |---------|-----------|--------|
+//! | `ReadOnly` | `https://www.googleapis.com/auth/gmail.readonly` | Listing labels and messages |
+//! | `Modify` | `https://www.googleapis.com/auth/gmail.modify` | Labelling, trashing and restoring messages |
+//! | `Full` | `https://mail.google.com/` | Permanent deletion |
+//!
+//! Each variant includes the permissions of those above it, so the variants
+//! are ordered and the larger of two scopes satisfies both.
+//!
+//! ## Usage Examples
+//!
+//! ```rust
+//! use cull_gmail::{GmailScope, Rules};
+//!
+//! // The default rules only trash messages, so modify access is enough
+//! let rules = Rules::new();
+//! assert_eq!(GmailScope::for_rules(&rules), GmailScope::Modify);
+//!
+//! // Full access covers everything else
+//! assert!(GmailScope::Full.covers(GmailScope::ReadOnly));
+//! assert!(!GmailScope::ReadOnly.covers(GmailScope::Modify));
+//! ```
+//!
+//! ## Token Cache
+//!
+//! OAuth2 tokens are cached per scope set. When a command needs a scope that
+//! the cached token was not granted, a fresh consent flow is started. Use
+//! [`GmailScope::is_cached_in`] to detect this before authenticating, and
+//! [`GmailScope::widen_to_cached`] to reuse a cached token granted a broader
+//! scope instead.
+
+use std::{fmt, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::{EolAction, Rules};
+
+/// The level of Gmail access requested through OAuth2.
+///
+/// Variants are ordered from least to most privileged, so comparisons such as
+/// `GmailScope::Modify < GmailScope::Full` hold.
+///
+/// # Examples
+///
+/// ```rust
+/// use cull_gmail::GmailScope;
+///
+/// assert_eq!(GmailScope::default(), GmailScope::ReadOnly);
+/// assert_eq!(
+///     GmailScope::Modify.as_url(),
+///     "https://www.googleapis.com/auth/gmail.modify"
+/// );
+/// assert_eq!(GmailScope::ReadOnly.max(GmailScope::Full), GmailScope::Full);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GmailScope {
+    /// Read-only access to messages, labels and settings.
+    ///
+    /// Sufficient for listing labels and messages.
+    #[default]
+    ReadOnly,
+
+    /// Read and write access, excluding permanent deletion.
+    ///
+    /// Required for moving messages to the trash and changing labels.
+    Modify,
+
+    /// Full access to the mailbox.
+    ///
+    /// Only required to permanently delete messages.
+    Full,
+}
+
+/// Shape of a single entry in the yup-oauth2 token cache file.
+#[derive(Debug, Deserialize)]
+struct CachedToken {
+    scopes: Vec<String>,
+}
+
+impl fmt::Display for GmailScope {
+    /// Formats the scope as a lowercase name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cull_gmail::GmailScope;
+    ///
+    /// assert_eq!(GmailScope::ReadOnly.to_string(), "readonly");
+    /// assert_eq!(GmailScope::Modify.to_string(), "modify");
+    /// assert_eq!(GmailScope::Full.to_string(), "full");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GmailScope::ReadOnly => write!(f, "readonly"),
+            GmailScope::Modify => write!(f, "modify"),
+            GmailScope::Full => write!(f, "full"),
+        }
+    }
+}
+
+impl GmailScope {
+    /// Returns the OAuth2 scope URL passed to the Gmail API.
+    pub fn as_url(&self) -> &'static str {
+        match self {
+            GmailScope::ReadOnly => "https://www.googleapis.com/auth/gmail.readonly",
+            GmailScope::Modify => "https://www.googleapis.com/auth/gmail.modify",
+            GmailScope::Full => "https://mail.google.com/",
+        }
+    }
+
+    /// Returns the scope matching an OAuth2 scope URL, if it is one cull-gmail uses.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cull_gmail::GmailScope;
+    ///
+    /// assert_eq!(
+    ///     GmailScope::from_url("https://mail.google.com/"),
+    ///     Some(GmailScope::Full)
+    /// );
+    /// assert_eq!(GmailScope::from_url("https://example.com/"), None);
+    /// ```
+    pub fn from_url(url: &str) -> Option<Self> {
+        Self::variants().iter().copied().find(|s| s.as_url() == url)
+    }
+
+    /// Returns `true` if this scope grants everything `other` grants.
+    pub fn covers(&self, other: GmailScope) -> bool {
+        *self >= other
+    }
+
+    /// Returns the scope needed to carry out an end-of-life action.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cull_gmail::{EolAction, GmailScope};
+    ///
+    /// assert_eq!(GmailScope::for_action(EolAction::Trash), GmailScope::Modify);
+    /// assert_eq!(GmailScope::for_action(EolAction::Delete), GmailScope::Full);
+    /// ```
+    pub fn for_action(action: EolAction) -> Self {
+        match action {
+            EolAction::Trash => GmailScope::Modify,
+            EolAction::Delete => GmailScope::Full,
+        }
+    }
+
+    /// Returns the scope needed to run every rule in `rules`.
+    ///
+    /// Full access is only requested when at least one rule permanently
+    /// deletes messages; otherwise modify access is sufficient.
+    pub fn for_rules(rules: &Rules) -> Self {
+        if rules
+            .get_rules_by_label_for_action(EolAction::Delete)
+            .is_empty()
+        {
+            GmailScope::Modify
+        } else {
+            GmailScope::Full
+        }
+    }
+
+    /// Returns `true` if the token cache at `persist_path` holds a token
+    /// granted for this scope.
+    ///
+    /// A missing or unreadable cache is reported as `false`. Tokens are cached
+    /// per scope set, so a token granted only for a broader scope still
+    /// requires a new consent flow; see [`GmailScope::widen_to_cached`].
+    pub fn is_cached_in(&self, persist_path: &str) -> bool {
+        Self::cached_scopes(Path::new(persist_path))
+            .iter()
+            .any(|scopes| scopes.iter().any(|s| s == self.as_url()))
+    }
+
+    /// Returns the scope to request so that a cached token can be reused.
+    ///
+    /// If the token cache at `persist_path` holds a token for this scope, the
+    /// scope is returned unchanged. Otherwise the least privileged cached
+    /// scope that covers it is returned, so a token granted at `init` for a
+    /// broader scope is used without a new consent flow. If no cached scope
+    /// covers it, the scope is returned unchanged.
+    pub fn widen_to_cached(self, persist_path: &str) -> GmailScope {
+        if self.is_cached_in(persist_path) {
+            return self;
+        }
+        Self::variants()
+            .iter()
+            .copied()
+            .filter(|scope| scope.covers(self) && scope.is_cached_in(persist_path))
+            .min()
+            .unwrap_or(self)
+    }
+
+    /// Reads the scope sets of all tokens stored in the cache file.
+    fn cached_scopes(path: &Path) -> Vec<Vec<String>> {
+        let Ok(json) = fs::read_to_string(path) else {
+            return Vec::new();
+        };
+
+        match serde_json::from_str::<Vec<CachedToken>>(&json) {
+            Ok(tokens) => tokens.into_iter().map(|t| t.scopes).collect(),
+            Err(e) => {
+                log::debug!("could not parse token cache `{}`: {e}", path.display());
+                Vec::new()
+            }
+        }
+    }
+
+    /// Returns all scopes in order of increasing privilege.
+    pub fn variants() -> &'static [GmailScope] {
+        &[GmailScope::ReadOnly, GmailScope::Modify, GmailScope::Full]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MessageAge, Retention};
+
+    #[test]
+    fn test_default_scope_is_readonly() {
+        assert_eq!(GmailScope::default(), GmailScope::ReadOnly);
+    }
+
+    #[test]
+    fn test_scope_ordering() {
+        assert!(GmailScope::ReadOnly < GmailScope::Modify);
+        assert!(GmailScope::Modify < GmailScope::Full);
+        assert!(GmailScope::Full.covers(GmailScope::Modify));
+        assert!(GmailScope::Modify.covers(GmailScope::Modify));
+        assert!(!GmailScope::Modify.covers(GmailScope::Full));
+    }
+
+    #[test]
+    fn test_url_round_trip() {
+        for scope in GmailScope::variants() {
+            assert_eq!(GmailScope::from_url(scope.as_url()), Some(*scope));
+        }
+        assert_eq!(GmailScope::from_url(""), None);
+    }
+
+    #[test]
+    fn test_for_rules_without_delete_is_modify() {
+        let rules = Rules::new();
+        assert_eq!(GmailScope::for_rules(&rules), GmailScope::Modify);
+    }
+
+    #[test]
+    fn test_for_rules_with_delete_is_full() {
+        let mut rules = Rules::new();
+        let retention = Retention::new(MessageAge::Years(3), false);
+        rules.add_rule(retention, Some("old-stuff"), true);
+        assert_eq!(GmailScope::for_rules(&rules), GmailScope::Full);
+    }
+
+    #[test]
+    fn test_is_cached_in_reads_token_cache() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("gmail1");
+        let cache = format!(
+            r#"[{{"scopes":["{}"],"token":{{"access_token":"a"}}}}]"#,
+            GmailScope::Modify.as_url()
+        );
+        fs::write(&path, cache).unwrap();
+        let path = path.to_string_lossy();
+
+        assert!(GmailScope::Modify.is_cached_in(&path));
+        assert!(!GmailScope::ReadOnly.is_cached_in(&path));
+        assert!(!GmailScope::Full.is_cached_in(&path));
+    }
+
+    #[test]
+    fn test_widen_to_cached() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("gmail1");
+        let path_str = path.to_string_lossy();
+        assert_eq!(
+            GmailScope::ReadOnly.widen_to_cached(&path_str),
+            GmailScope::ReadOnly
+        );
+
+        let cache = format!(
+            r#"[{{"scopes":["{}"],"token":{{"access_token":"a"}}}}]"#,
+            GmailScope::Modify.as_url()
+        );
+        fs::write(&path, cache).unwrap();
+
+        assert_eq!(
+            GmailScope::ReadOnly.widen_to_cached(&path_str),
+            GmailScope::Modify
+        );
+        assert_eq!(
+            GmailScope::Modify.widen_to_cached(&path_str),
+            GmailScope::Modify
+        );
+        assert_eq!(
+            GmailScope::Full.widen_to_cached(&path_str),
+            GmailScope::Full
+        );
+    }
+
+    #[test]
+    fn test_is_cached_in_missing_or_invalid_cache() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("gmail1");
+        assert!(!GmailScope::ReadOnly.is_cached_in(&path.to_string_lossy()));
+
+        fs::write(&path, "not json").unwrap();
+        assert!(!GmailScope::ReadOnly.is_cached_in(&path.to_string_lossy()));
+    }
+}
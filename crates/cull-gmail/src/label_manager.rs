@@ -0,0 +1,768 @@
+//! # Label Manager Module
+//!
+//! This module provides the [`LabelManager`] trait for managing Gmail labels
+//! (listing, creating, renaming, deleting and colouring them) and for changing
+//! the labels applied to the prepared message list in bulk.
+//!
+//! ## Overview
+//!
+//! Label changes use the Gmail `batchModify` API, which accepts up to 1000
+//! message IDs per call, so larger message lists are processed in chunks in
+//! the same way as [`RuleProcessor::process_in_chunks`](crate::RuleProcessor::process_in_chunks).
+//!
+//! Labels are referred to by name. Names are resolved to IDs using the label
+//! map fetched when the client is created; labels created through this trait
+//! are added to the map so they can be used straight away.
+//!
+//! Rules may name a group of nested labels with a wildcard level: `*` matches
+//! exactly one level and `**` one or more, so `Vendors/*` matches
+//! `Vendors/Acme` and `Vendors/**` also matches `Vendors/Acme/Invoices`.
+//! [`LabelManager::expand_label`] expands such a pattern using the label map.
+//!
+//! [`LabelManager::create_missing_labels`] creates the labels rules refer to,
+//! such as the `retention/1-years` labels added by
+//! [`Retention`](crate::Retention), together with any missing parent labels.
+//!
+//! ## Usage Examples
+//!
+//! ```rust,no_run
+//! use cull_gmail::{ClientConfig, GmailClient, LabelManager, MessageList};
+//!
+//! # async fn example(config: ClientConfig) -> cull_gmail::Result<()> {
+//! let mut client = GmailClient::new_with_config(config).await?;
+//! client.set_query("from:newsletter@example.com");
+//! client.get_messages(0).await?;
+//!
+//! // Tag the messages for a retention rule, creating the label if needed
+//! client
+//!     .modify_labels(&["retention/1-years".to_string()], &[], true)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Gmail API Requirements
+//!
+//! Listing labels only needs read access. Creating, renaming, deleting and
+//! colouring labels, and modifying messages, require the
+//! `https://www.googleapis.com/auth/gmail.modify` scope.
+//!
+//! System labels such as `INBOX` or `SENT` cannot be renamed, deleted or
+//! coloured.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future::Future,
+};
+
+use futures::{StreamExt, TryStreamExt, stream};
+use google_gmail1::api::{BatchModifyMessagesRequest, Label, LabelColor};
+use serde::Serialize;
+
+use crate::{Error, GmailClient, GmailScope, MessageList, Result};
+
+/// Maximum number of message IDs accepted by a single `batchModify` call.
+const BATCH_MODIFY_LIMIT: usize = 1000;
+
+/// Type Gmail gives to its own labels, such as `INBOX` or `SENT`.
+pub(crate) const SYSTEM_LABEL_TYPE: &str = "system";
+
+/// Maximum number of labels fetched at the same time when listing labels.
+const LABEL_FETCH_CONCURRENCY: usize = 8;
+
+/// Separator between the levels of a nested label name.
+const NESTED_LABEL_SEPARATOR: char = '/';
+
+/// Label level matching exactly one level of a nested label name.
+const ANY_LEVEL: &str = "*";
+
+/// Label level matching one or more levels of a nested label name.
+const ANY_LEVELS: &str = "**";
+
+/// Details of a Gmail label with its message counts.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LabelInfo {
+    /// Label name, with `/` separating nested labels
+    pub name: String,
+    /// Gmail label ID
+    pub id: String,
+    /// `system` for labels created by Gmail, `user` for all others
+    pub label_type: String,
+    /// Number of messages with the label
+    pub messages_total: u32,
+    /// Number of unread messages with the label
+    pub messages_unread: u32,
+    /// Background colour as a hex string, if set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_color: Option<String>,
+    /// Text colour as a hex string, if set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_color: Option<String>,
+}
+
+impl From<Label> for LabelInfo {
+    fn from(label: Label) -> Self {
+        let count = |n: Option<i32>| n.and_then(|n| u32::try_from(n).ok()).unwrap_or_default();
+        let color = label.color.unwrap_or_default();
+        LabelInfo {
+            name: label.name.unwrap_or_default(),
+            id: label.id.unwrap_or_default(),
+            label_type: label.type_.unwrap_or_default(),
+            messages_total: count(label.messages_total),
+            messages_unread: count(label.messages_unread),
+            background_color: color.background_color,
+            text_color: color.text_color,
+        }
+    }
+}
+
+/// Operations for managing labels and applying them to messages.
+pub trait LabelManager {
+    /// Lists all labels with their type and message counts, sorted by name.
+    ///
+    /// Counts are only returned by Gmail when each label is fetched on its
+    /// own, so this makes one request per label, a few at a time.
+    ///
+    /// # Errors
+    ///
+    /// * Gmail API errors while fetching labels
+    fn list_labels(&self) -> impl std::future::Future<Output = Result<Vec<LabelInfo>>> + Send;
+
+    /// Creates a user label and returns its ID.
+    ///
+    /// The new label is shown in the label list and message list, and is
+    /// added to the client's label map.
+    ///
+    /// # Errors
+    ///
+    /// * Gmail API errors, for example when a label with the name already exists
+    /// * [`Error::LabelIdMissing`] if Gmail does not return the new label's ID
+    fn create_label(
+        &mut self,
+        name: &str,
+    ) -> impl std::future::Future<Output = Result<String>> + Send;
+
+    /// Returns the labels in `names` that do not exist, with their missing parents.
+    ///
+    /// Nested labels such as `retention/1-years` need their parent
+    /// (`retention`) to exist, so parents are listed before their children.
+    /// Each name appears once.
+    fn missing_labels(&self, names: &[String]) -> Vec<String>;
+
+    /// Returns the labels matched by a label name or wildcard pattern.
+    ///
+    /// A name without a wildcard level is returned as it is, whether or not
+    /// the label exists. A pattern is expanded to the matching labels in the
+    /// label map, sorted by name.
+    fn expand_label(&self, label: &str) -> Vec<String>;
+
+    /// Creates the labels in `names` that do not exist, parents first.
+    ///
+    /// # Returns
+    ///
+    /// The names of the labels that were created.
+    ///
+    /// # Errors
+    ///
+    /// * Gmail API errors while creating labels
+    fn create_missing_labels(
+        &mut self,
+        names: &[String],
+    ) -> impl std::future::Future<Output = Result<Vec<String>>> + Send;
+
+    /// Renames a user label.
+    ///
+    /// Nested labels keep their own names, so renaming `Vendors` does not
+    /// rename `Vendors/Acme`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LabelNotFoundInMailbox`] if the label does not exist
+    /// * [`Error::SystemLabel`] if the label is a system label
+    /// * Gmail API errors, for example when the new name is already in use
+    fn rename_label(
+        &mut self,
+        name: &str,
+        new_name: &str,
+    ) -> impl std::future::Future<Output = Result<()>> + Send;
+
+    /// Deletes a user label.
+    ///
+    /// Messages keep their other labels; only the label itself is removed.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LabelNotFoundInMailbox`] if the label does not exist
+    /// * [`Error::SystemLabel`] if the label is a system label
+    /// * Gmail API errors while deleting the label
+    fn delete_label(&mut self, name: &str) -> impl std::future::Future<Output = Result<()>> + Send;
+
+    /// Sets the background and text colour of a user label.
+    ///
+    /// Colours are hex strings such as `#fb4c2f`. Gmail only accepts colours
+    /// from its label palette.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LabelNotFoundInMailbox`] if the label does not exist
+    /// * [`Error::SystemLabel`] if the label is a system label
+    /// * Gmail API errors, for example when a colour is not in the palette
+    fn set_label_color(
+        &self,
+        name: &str,
+        background_color: &str,
+        text_color: &str,
+    ) -> impl std::future::Future<Output = Result<()>> + Send;
+
+    /// Adds and removes labels on all prepared messages.
+    ///
+    /// Labels are given by name. Labels in `add` that do not exist are created
+    /// first when `create_missing` is `true`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LabelNotFoundInMailbox`] if a label does not exist and is not
+    ///   created
+    /// * Gmail API errors while creating labels or modifying messages
+    fn modify_labels(
+        &mut self,
+        add: &[String],
+        remove: &[String],
+        create_missing: bool,
+    ) -> impl std::future::Future<Output = Result<()>> + Send;
+
+    /// Calls the Gmail API to add and remove label IDs on a slice of messages.
+    ///
+    /// At most 1000 message IDs may be passed in one call.
+    fn call_batch_modify(
+        &self,
+        ids: &[String],
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> impl std::future::Future<Output = Result<()>> + Send;
+}
+
+/// Returns `true` if `label` has a `*` or `**` wildcard level.
+pub(crate) fn is_label_pattern(label: &str) -> bool {
+    label
+        .split(NESTED_LABEL_SEPARATOR)
+        .any(|level| level == ANY_LEVEL || level == ANY_LEVELS)
+}
+
+/// Returns `true` if the label `name` matches the wildcard `pattern`.
+fn label_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.split(NESTED_LABEL_SEPARATOR).collect::<Vec<_>>();
+    let name = name.split(NESTED_LABEL_SEPARATOR).collect::<Vec<_>>();
+    levels_match(&pattern, &name)
+}
+
+fn levels_match(pattern: &[&str], name: &[&str]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((&ANY_LEVELS, rest)), Some((_, name_rest))) => {
+            levels_match(rest, name_rest) || levels_match(pattern, name_rest)
+        }
+        (Some((&ANY_LEVEL, rest)), Some((_, name_rest))) => levels_match(rest, name_rest),
+        (Some((level, rest)), Some((name_level, name_rest))) => {
+            level == name_level && levels_match(rest, name_rest)
+        }
+        _ => false,
+    }
+}
+
+/// Expands a label name or wildcard pattern to the keys of `label_map` it matches.
+///
+/// This is [`LabelManager::expand_label`] for a label map that is not a
+/// mailbox's, such as the labels of test messages. A name without a wildcard
+/// level is returned as it is; a pattern is expanded to the matching names,
+/// sorted.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use cull_gmail::expand_label_pattern;
+///
+/// let labels = BTreeMap::from(
+///     ["Vendors/Acme", "Vendors/Globex", "Clients/Acme"].map(|l| (l.to_string(), l.to_string())),
+/// );
+/// assert_eq!(
+///     expand_label_pattern(&labels, "Vendors/*"),
+///     vec!["Vendors/Acme", "Vendors/Globex"]
+/// );
+/// ```
+pub fn expand_label_pattern(label_map: &BTreeMap<String, String>, label: &str) -> Vec<String> {
+    if !is_label_pattern(label) {
+        return vec![label.to_string()];
+    }
+    label_map
+        .keys()
+        .filter(|name| label_matches(label, name))
+        .cloned()
+        .collect()
+}
+
+/// Lists the names, and their parents, that are not keys of `label_map`.
+///
+/// Wildcard patterns are skipped as they cannot be created.
+fn missing_from(label_map: &BTreeMap<String, String>, names: &[String]) -> Vec<String> {
+    let mut missing: Vec<String> = Vec::new();
+    for name in names.iter().filter(|name| !is_label_pattern(name)) {
+        let parents = name
+            .match_indices(NESTED_LABEL_SEPARATOR)
+            .map(|(i, _)| &name[..i]);
+        for label in parents.chain([name.as_str()]) {
+            if !label_map.contains_key(label) && !missing.iter().any(|m| m == label) {
+                missing.push(label.to_string());
+            }
+        }
+    }
+    missing
+}
+
+/// Internal trait defining the Gmail operations used to relabel messages.
+///
+/// This trait enables unit testing of the label changes and chunking
+/// without network calls, in the same way as the rule processor's
+/// `MailOperations`.
+#[doc(hidden)]
+pub(crate) trait LabelOperations {
+    /// Look up the ID of a label by name
+    fn label_id_for(&self, name: &str) -> Option<String>;
+
+    /// Create a label and return its ID
+    fn create_label_id(&mut self, name: &str) -> impl Future<Output = Result<String>> + Send;
+
+    /// IDs of the prepared messages
+    fn prepared_message_ids(&self) -> Vec<String>;
+
+    /// Log the prepared messages before they are changed
+    fn log_prepared(&mut self, pre: &str, post: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Add and remove label IDs on up to [`BATCH_MODIFY_LIMIT`] messages
+    fn batch_modify(
+        &self,
+        ids: &[String],
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Resolves label names to IDs, optionally creating missing labels.
+async fn resolve_label_ids<T: LabelOperations>(
+    client: &mut T,
+    names: &[String],
+    create: bool,
+) -> Result<Vec<String>> {
+    let mut ids = Vec::with_capacity(names.len());
+    for name in names {
+        let id = match client.label_id_for(name) {
+            Some(id) => id,
+            None if create => client.create_label_id(name).await?,
+            None => return Err(Error::LabelNotFoundInMailbox(name.to_string())),
+        };
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+/// Adds and removes label IDs on messages, [`BATCH_MODIFY_LIMIT`] at a time.
+pub(crate) async fn modify_in_chunks<T: LabelOperations>(
+    client: &T,
+    message_ids: &[String],
+    add_label_ids: &[String],
+    remove_label_ids: &[String],
+) -> Result<()> {
+    for (i, chunk) in message_ids.chunks(BATCH_MODIFY_LIMIT).enumerate() {
+        log::info!("Processing chunk {i}");
+        client
+            .batch_modify(chunk, add_label_ids, remove_label_ids)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Orchestrates [`LabelManager::modify_labels`] so it can be unit tested.
+async fn modify_message_labels<T: LabelOperations>(
+    client: &mut T,
+    add: &[String],
+    remove: &[String],
+    create_missing: bool,
+) -> Result<()> {
+    let remove_ids = resolve_label_ids(client, remove, false).await?;
+    let add_ids = resolve_label_ids(client, add, create_missing).await?;
+
+    let message_ids = client.prepared_message_ids();
+
+    // Early return if no messages to change, avoiding unnecessary API calls
+    if message_ids.is_empty() {
+        log::info!("No messages to label - skipping batch modify operation");
+        return Ok(());
+    }
+
+    client
+        .log_prepared("Labels updated on message with subject `", "`")
+        .await?;
+
+    modify_in_chunks(client, &message_ids, &add_ids, &remove_ids).await
+}
+
+impl LabelOperations for GmailClient {
+    fn label_id_for(&self, name: &str) -> Option<String> {
+        self.get_label_id(name)
+    }
+
+    async fn create_label_id(&mut self, name: &str) -> Result<String> {
+        LabelManager::create_label(self, name).await
+    }
+
+    fn prepared_message_ids(&self) -> Vec<String> {
+        MessageList::message_ids(self)
+    }
+
+    async fn log_prepared(&mut self, pre: &str, post: &str) -> Result<()> {
+        MessageList::log_messages(self, pre, post).await
+    }
+
+    async fn batch_modify(
+        &self,
+        ids: &[String],
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> Result<()> {
+        let batch_request = BatchModifyMessagesRequest {
+            add_label_ids: Some(add_label_ids.to_vec()),
+            ids: Some(ids.to_vec()),
+            remove_label_ids: Some(remove_label_ids.to_vec()),
+        };
+
+        log::trace!("{batch_request:#?}");
+
+        let _res = self
+            .hub()
+            .users()
+            .messages_batch_modify(batch_request, "me")
+            .add_scope(self.scope_for(GmailScope::Modify))
+            .doit()
+            .await
+            .map_err(Box::new)?;
+
+        Ok(())
+    }
+}
+
+/// Resolves the ID of the user label `name`, refusing system labels.
+fn user_label_id_in(
+    label_map: &BTreeMap<String, String>,
+    system_label_ids: &BTreeSet<String>,
+    name: &str,
+) -> Result<String> {
+    let Some(id) = label_map.get(name) else {
+        return Err(Error::LabelNotFoundInMailbox(name.to_string()));
+    };
+    if system_label_ids.contains(id) {
+        return Err(Error::SystemLabel(name.to_string()));
+    }
+    Ok(id.clone())
+}
+
+impl GmailClient {
+    /// Resolves the ID of a user label, refusing system labels.
+    fn user_label_id(&self, name: &str) -> Result<String> {
+        user_label_id_in(&self.label_map, &self.system_label_ids, name)
+    }
+
+    /// Applies a partial update to a user label.
+    async fn patch_label(&self, id: &str, request: Label) -> Result<()> {
+        let _res = self
+            .hub()
+            .users()
+            .labels_patch(request, "me", id)
+            .add_scope(self.scope_for(GmailScope::Modify))
+            .doit()
+            .await
+            .map_err(Box::new)?;
+        Ok(())
+    }
+}
+
+impl LabelManager for GmailClient {
+    async fn list_labels(&self) -> Result<Vec<LabelInfo>> {
+        let ids = self.label_map.values().cloned().collect::<Vec<_>>();
+        let mut labels: Vec<LabelInfo> = stream::iter(ids)
+            .map(|id| async move {
+                let (_res, label) = self
+                    .hub()
+                    .users()
+                    .labels_get("me", &id)
+                    .add_scope(self.scope_for(GmailScope::ReadOnly))
+                    .doit()
+                    .await
+                    .map_err(Box::new)?;
+                Ok::<_, Error>(LabelInfo::from(label))
+            })
+            .buffer_unordered(LABEL_FETCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+        labels.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(labels)
+    }
+
+    async fn create_label(&mut self, name: &str) -> Result<String> {
+        let request = Label {
+            name: Some(name.to_string()),
+            label_list_visibility: Some("labelShow".to_string()),
+            message_list_visibility: Some("show".to_string()),
+            ..Default::default()
+        };
+
+        let (_res, label) = self
+            .hub()
+            .users()
+            .labels_create(request, "me")
+            .add_scope(self.scope_for(GmailScope::Modify))
+            .doit()
+            .await
+            .map_err(Box::new)?;
+
+        let Some(id) = label.id else {
+            return Err(Error::LabelIdMissing(name.to_string()));
+        };
+        log::info!("Created label `{name}` ({id})");
+        self.label_map.insert(name.to_string(), id.clone());
+
+        Ok(id)
+    }
+
+    fn missing_labels(&self, names: &[String]) -> Vec<String> {
+        missing_from(&self.label_map, names)
+    }
+
+    fn expand_label(&self, label: &str) -> Vec<String> {
+        expand_label_pattern(&self.label_map, label)
+    }
+
+    async fn create_missing_labels(&mut self, names: &[String]) -> Result<Vec<String>> {
+        let missing = self.missing_labels(names);
+        for name in &missing {
+            self.create_label(name).await?;
+        }
+        Ok(missing)
+    }
+
+    async fn rename_label(&mut self, name: &str, new_name: &str) -> Result<()> {
+        let id = self.user_label_id(name)?;
+        let request = Label {
+            name: Some(new_name.to_string()),
+            ..Default::default()
+        };
+        self.patch_label(&id, request).await?;
+
+        log::info!("Renamed label `{name}` to `{new_name}`");
+        self.label_map.remove(name);
+        self.label_map.insert(new_name.to_string(), id);
+
+        Ok(())
+    }
+
+    async fn delete_label(&mut self, name: &str) -> Result<()> {
+        let id = self.user_label_id(name)?;
+        self.hub()
+            .users()
+            .labels_delete("me", &id)
+            .add_scope(self.scope_for(GmailScope::Modify))
+            .doit()
+            .await
+            .map_err(Box::new)?;
+
+        log::info!("Deleted label `{name}` ({id})");
+        self.label_map.remove(name);
+
+        Ok(())
+    }
+
+    async fn set_label_color(
+        &self,
+        name: &str,
+        background_color: &str,
+        text_color: &str,
+    ) -> Result<()> {
+        let id = self.user_label_id(name)?;
+        let request = Label {
+            color: Some(LabelColor {
+                background_color: Some(background_color.to_string()),
+                text_color: Some(text_color.to_string()),
+            }),
+            ..Default::default()
+        };
+        self.patch_label(&id, request).await?;
+        log::info!("Set colour of label `{name}` to {background_color} on {text_color}");
+
+        Ok(())
+    }
+
+    async fn modify_labels(
+        &mut self,
+        add: &[String],
+        remove: &[String],
+        create_missing: bool,
+    ) -> Result<()> {
+        modify_message_labels(self, add, remove, create_missing).await
+    }
+
+    async fn call_batch_modify(
+        &self,
+        ids: &[String],
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> Result<()> {
+        self.batch_modify(ids, add_label_ids, remove_label_ids)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::FakeLabels;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_modify_labels_in_chunks() {
+        let mut client = FakeLabels::new(&[("Keep", "Label_1"), ("Review", "Label_2")], 2500);
+
+        modify_message_labels(&mut client, &names(&["Keep"]), &names(&["Review"]), false)
+            .await
+            .unwrap();
+
+        let add = names(&["Label_1"]);
+        let remove = names(&["Label_2"]);
+        assert_eq!(
+            client.calls(),
+            vec![
+                (1000, add.clone(), remove.clone()),
+                (1000, add.clone(), remove.clone()),
+                (500, add, remove),
+            ]
+        );
+        assert!(client.created.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_modify_labels_creates_missing_add_labels() {
+        let mut client = FakeLabels::new(&[("Keep", "Label_1")], 3);
+
+        let result = modify_message_labels(&mut client, &names(&["New"]), &[], false).await;
+        assert!(matches!(result, Err(Error::LabelNotFoundInMailbox(l)) if l == "New"));
+        assert!(client.calls().is_empty());
+
+        modify_message_labels(&mut client, &names(&["Keep", "New"]), &[], true)
+            .await
+            .unwrap();
+        assert_eq!(client.created, vec!["New"]);
+        assert_eq!(
+            client.calls(),
+            vec![(3, names(&["Label_1", "Label_new_0"]), vec![])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_modify_labels_missing_remove_label() {
+        let mut client = FakeLabels::new(&[("Keep", "Label_1")], 3);
+
+        // Labels to remove are never created
+        let result =
+            modify_message_labels(&mut client, &names(&["Keep"]), &names(&["Gone"]), true).await;
+        assert!(matches!(result, Err(Error::LabelNotFoundInMailbox(l)) if l == "Gone"));
+        assert!(client.created.is_empty());
+        assert!(client.calls().is_empty());
+    }
+
+    #[test]
+    fn test_missing_from_includes_parents_once() {
+        let label_map = BTreeMap::from([
+            ("INBOX".to_string(), "INBOX".to_string()),
+            ("Vendors".to_string(), "Label_1".to_string()),
+        ]);
+        let names = [
+            "retention/1-years",
+            "retention/5-years",
+            "Vendors/Acme/Invoices",
+            "INBOX",
+            "Projects/**",
+        ]
+        .map(str::to_string);
+
+        assert_eq!(
+            missing_from(&label_map, &names),
+            vec![
+                "retention",
+                "retention/1-years",
+                "retention/5-years",
+                "Vendors/Acme",
+                "Vendors/Acme/Invoices",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_user_label_id_refuses_system_labels() {
+        let label_map = BTreeMap::from([
+            ("INBOX".to_string(), "INBOX".to_string()),
+            (
+                "CATEGORY_UPDATES".to_string(),
+                "CATEGORY_UPDATES".to_string(),
+            ),
+            ("Vendors".to_string(), "Label_1".to_string()),
+            ("Imported".to_string(), "Imported_7".to_string()),
+        ]);
+        let system_label_ids = BTreeSet::from(["INBOX", "CATEGORY_UPDATES"].map(str::to_string));
+
+        let id = |name| user_label_id_in(&label_map, &system_label_ids, name);
+        assert_eq!(id("Vendors").unwrap(), "Label_1");
+        // The type decides, not the shape of the ID
+        assert_eq!(id("Imported").unwrap(), "Imported_7");
+        assert!(matches!(id("INBOX"), Err(Error::SystemLabel(l)) if l == "INBOX"));
+        assert!(matches!(id("CATEGORY_UPDATES"), Err(Error::SystemLabel(_))));
+        assert!(matches!(id("Missing"), Err(Error::LabelNotFoundInMailbox(l)) if l == "Missing"));
+    }
+
+    #[test]
+    fn test_expand_label_wildcards() {
+        let label_map = [
+            "INBOX",
+            "Vendors",
+            "Vendors/Acme",
+            "Vendors/Acme/Invoices",
+            "Vendors/Globex",
+            "Vendorship",
+            "Projects/Acme",
+        ]
+        .map(|name| (name.to_string(), name.to_string()))
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+
+        assert_eq!(
+            expand_label_pattern(&label_map, "Vendors/*"),
+            vec!["Vendors/Acme", "Vendors/Globex"]
+        );
+        assert_eq!(
+            expand_label_pattern(&label_map, "Vendors/**"),
+            vec!["Vendors/Acme", "Vendors/Acme/Invoices", "Vendors/Globex"]
+        );
+        assert_eq!(
+            expand_label_pattern(&label_map, "*/Acme"),
+            vec!["Projects/Acme", "Vendors/Acme"]
+        );
+        assert!(expand_label_pattern(&label_map, "Missing/*").is_empty());
+        assert_eq!(expand_label_pattern(&label_map, "Missing"), vec!["Missing"]);
+
+        assert!(is_label_pattern("Vendors/**"));
+        assert!(!is_label_pattern("Vendors*"));
+    }
+}
@@ -7,9 +7,14 @@
 mod client_config;
 mod eol_action;
 mod error;
+mod filter_manager;
 mod gmail_client;
+mod gmail_scope;
+mod label_manager;
+mod message_export;
 mod message_list;
 mod retention;
+mod rule_plan;
 mod rule_processor;
 mod rules;
 #[cfg(test)]
@@ -21,15 +26,20 @@ pub use gmail_client::DEFAULT_MAX_RESULTS;
 
 pub use client_config::ClientConfig;
 pub use gmail_client::GmailClient;
-pub(crate) use gmail_client::MessageSummary;
+pub use gmail_client::MessageSummary;
+pub use gmail_scope::GmailScope;
 pub use retention::Retention;
-pub use rules::{Rules, ValidationIssue};
+pub use rules::{EolRule, RuleDecision, RuleEvaluation, Rules, ValidationIssue};
 
 pub use eol_action::EolAction;
 pub use error::Error;
 pub use retention::MessageAge;
 
+pub use filter_manager::{FilterManager, RuleFilter};
+pub use label_manager::{LabelInfo, LabelManager, expand_label_pattern};
+pub use message_export::{ExportFormat, MessageExport};
 pub use message_list::MessageList;
+pub use rule_plan::{Plan, PlanEntry};
 pub use rule_processor::RuleProcessor;
 
 /// Type alias for result with crate Error
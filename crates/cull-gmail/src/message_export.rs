@@ -0,0 +1,506 @@
+//! # Message Export Module
+//!
+//! This module archives Gmail messages to local files before they are culled.
+//! Messages are fetched with `format=raw`, which returns the complete RFC 822
+//! source, and written unchanged in one of two formats:
+//!
+//! | Format | Output |
+//! |--------|--------|
+//! | `eml` | One `<message-id>.eml` file per message |
+//! | `mbox` | A single mbox file (mboxrd quoting) per export |
+//!
+//! Each message is written as soon as it is fetched, so memory use does not
+//! grow with the number of messages. Files are written under a temporary name
+//! and renamed once complete, so a failed write never leaves a file that looks
+//! finished. If fetching a message fails, the messages exported before it are
+//! kept; an mbox export is then renamed with an `-incomplete` suffix.
+//!
+//! ## Usage Examples
+//!
+//! ```rust,no_run
+//! use std::path::Path;
+//! use cull_gmail::{ClientConfig, ExportFormat, GmailClient, MessageExport, MessageList};
+//!
+//! # async fn example(config: ClientConfig) -> cull_gmail::Result<()> {
+//! let mut client = GmailClient::new_with_config(config).await?;
+//! client.set_query("older_than:5y");
+//! client.get_messages(1).await?;
+//!
+//! let files = client
+//!     .export_messages(&client.message_ids(), ExportFormat::Mbox, Path::new("archive"))
+//!     .await?;
+//! println!("Wrote {} file(s)", files.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Rules
+//!
+//! A rule with `export_before_action` set archives each chunk of messages as
+//! EML files in that directory before permanently deleting it. If the export
+//! fails the chunk is not deleted.
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{Error, GmailClient, GmailScope, Result};
+
+/// File formats for exported messages.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One RFC 822 `.eml` file per message, named after the message ID.
+    #[default]
+    Eml,
+    /// All exported messages in a single mbox file.
+    Mbox,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Eml => write!(f, "eml"),
+            ExportFormat::Mbox => write!(f, "mbox"),
+        }
+    }
+}
+
+impl ExportFormat {
+    /// Parses a format name, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cull_gmail::ExportFormat;
+    ///
+    /// assert_eq!(ExportFormat::parse("MBOX"), Some(ExportFormat::Mbox));
+    /// assert_eq!(ExportFormat::parse("pst"), None);
+    /// ```
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "eml" => Some(ExportFormat::Eml),
+            "mbox" => Some(ExportFormat::Mbox),
+            _ => None,
+        }
+    }
+}
+
+/// The RFC 822 source of a message as returned by Gmail.
+#[derive(Debug)]
+struct RawMessage {
+    id: String,
+    data: Vec<u8>,
+    internal_date: Option<DateTime<Utc>>,
+}
+
+/// Exporting messages to local files.
+pub trait MessageExport {
+    /// Fetches the raw source of each message and writes it to `dir`.
+    ///
+    /// The directory is created if it does not exist. Returns the paths of
+    /// the files written: one per message for [`ExportFormat::Eml`] and a
+    /// single file for [`ExportFormat::Mbox`]. Each message is written as soon
+    /// as it is fetched; if fetching one fails, the messages before it are
+    /// kept and [`Error::ExportIncomplete`] is returned with their location.
+    ///
+    /// # Errors
+    ///
+    /// * Gmail API errors while fetching the first message
+    /// * [`Error::RawMessageMissing`] if Gmail returns no source for the first message
+    /// * [`Error::ExportIncomplete`] if fetching a later message fails
+    /// * [`Error::ExportFailed`] if a file cannot be written
+    ///
+    /// # Gmail API Requirements
+    ///
+    /// Requires the `https://www.googleapis.com/auth/gmail.readonly` scope.
+    fn export_messages(
+        &self,
+        ids: &[String],
+        format: ExportFormat,
+        dir: &Path,
+    ) -> impl std::future::Future<Output = Result<Vec<PathBuf>>> + Send;
+}
+
+impl GmailClient {
+    /// Fetches a message in `raw` format.
+    async fn get_raw_message(&self, id: &str) -> Result<RawMessage> {
+        let (_res, m) = self
+            .hub()
+            .users()
+            .messages_get("me", id)
+            .add_scope(self.scope_for(GmailScope::ReadOnly))
+            .format("raw")
+            .doit()
+            .await
+            .map_err(Box::new)?;
+
+        let Some(data) = m.raw else {
+            return Err(Error::RawMessageMissing(id.to_string()));
+        };
+
+        Ok(RawMessage {
+            id: id.to_string(),
+            data,
+            internal_date: m.internal_date.and_then(DateTime::from_timestamp_millis),
+        })
+    }
+}
+
+impl MessageExport for GmailClient {
+    async fn export_messages(
+        &self,
+        ids: &[String],
+        format: ExportFormat,
+        dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut writer = ExportWriter::create(dir, format, &ids[0], Utc::now())?;
+        for (written, id) in ids.iter().enumerate() {
+            log::debug!("fetching raw message `{id}`");
+            let message = match self.get_raw_message(id).await {
+                Ok(message) => message,
+                Err(e) if written > 0 => {
+                    let kept = writer.keep_incomplete()?;
+                    return Err(Error::ExportIncomplete(
+                        kept.display().to_string(),
+                        written,
+                        e.to_string(),
+                    ));
+                }
+                Err(e) => {
+                    writer.abandon();
+                    return Err(e);
+                }
+            };
+            writer.write(&message)?;
+        }
+
+        let files = writer.finish()?;
+        log::info!(
+            "Exported {} message(s) as {format} to `{}`",
+            ids.len(),
+            dir.display()
+        );
+
+        Ok(files)
+    }
+}
+
+/// Writes messages to an export directory as they are fetched.
+enum ExportWriter {
+    /// Each message is written to a file of its own straight away.
+    Eml { dir: PathBuf, files: Vec<PathBuf> },
+    /// Messages are appended to one file, renamed into place when finished.
+    Mbox {
+        path: PathBuf,
+        partial: PathBuf,
+        out: io::BufWriter<fs::File>,
+        now: DateTime<Utc>,
+    },
+}
+
+impl ExportWriter {
+    /// Creates `dir` and, for mbox, the file named after the first message.
+    fn create(
+        dir: &Path,
+        format: ExportFormat,
+        first_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        fs::create_dir_all(dir).map_err(|e| Error::ExportFailed(dir.display().to_string(), e))?;
+
+        match format {
+            ExportFormat::Eml => Ok(ExportWriter::Eml {
+                dir: dir.to_path_buf(),
+                files: Vec::new(),
+            }),
+            ExportFormat::Mbox => {
+                let name = format!(
+                    "cull-gmail-{}-{}.mbox",
+                    now.format("%Y%m%dT%H%M%S"),
+                    sanitise(first_id)
+                );
+                let path = dir.join(name);
+                let partial = path.with_extension("part");
+                let file = fs::File::create(&partial)
+                    .map_err(|e| Error::ExportFailed(path.display().to_string(), e))?;
+                Ok(ExportWriter::Mbox {
+                    path,
+                    partial,
+                    out: io::BufWriter::new(file),
+                    now,
+                })
+            }
+        }
+    }
+
+    /// Writes one message.
+    ///
+    /// A failed mbox write removes the partial file.
+    fn write(&mut self, message: &RawMessage) -> Result<()> {
+        match self {
+            ExportWriter::Eml { dir, files } => {
+                let path = dir.join(format!("{}.eml", sanitise(&message.id)));
+                write_file(&path, |out| out.write_all(&message.data))?;
+                files.push(path);
+                Ok(())
+            }
+            ExportWriter::Mbox {
+                path,
+                partial,
+                out,
+                now,
+            } => write_mbox_entry(out, &message.data, message.internal_date.unwrap_or(*now))
+                .map_err(|e| {
+                    let _ = fs::remove_file(&partial);
+                    Error::ExportFailed(path.display().to_string(), e)
+                }),
+        }
+    }
+
+    /// Completes the export and returns the paths of the files written.
+    fn finish(self) -> Result<Vec<PathBuf>> {
+        match self {
+            ExportWriter::Eml { files, .. } => Ok(files),
+            ExportWriter::Mbox {
+                path, partial, out, ..
+            } => {
+                let result = out
+                    .into_inner()
+                    .map_err(|e| e.into_error())
+                    .and_then(|file| file.sync_all())
+                    .and_then(|_| fs::rename(&partial, &path));
+                match result {
+                    Ok(()) => Ok(vec![path]),
+                    Err(e) => {
+                        let _ = fs::remove_file(&partial);
+                        Err(Error::ExportFailed(path.display().to_string(), e))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Keeps the messages written so far after the export stopped early.
+    ///
+    /// An mbox file is renamed with an `-incomplete` suffix so it is never
+    /// mistaken for a finished export. Returns the mbox file, or the directory
+    /// holding the eml files.
+    fn keep_incomplete(self) -> Result<PathBuf> {
+        match self {
+            ExportWriter::Eml { dir, .. } => Ok(dir),
+            ExportWriter::Mbox {
+                path, partial, out, ..
+            } => {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let incomplete = path.with_file_name(format!("{stem}-incomplete.mbox"));
+                out.into_inner()
+                    .map_err(|e| e.into_error())
+                    .and_then(|file| file.sync_all())
+                    .and_then(|_| fs::rename(&partial, &incomplete))
+                    .map_err(|e| Error::ExportFailed(incomplete.display().to_string(), e))?;
+                Ok(incomplete)
+            }
+        }
+    }
+
+    /// Discards an export that has written nothing.
+    fn abandon(self) {
+        if let ExportWriter::Mbox { partial, out, .. } = self {
+            drop(out);
+            let _ = fs::remove_file(&partial);
+        }
+    }
+}
+
+/// Writes a file under a temporary name and renames it into place.
+fn write_file(
+    path: &Path,
+    write: impl FnOnce(&mut io::BufWriter<fs::File>) -> io::Result<()>,
+) -> Result<()> {
+    let partial = path.with_extension("part");
+    let result = fs::File::create(&partial).and_then(|file| {
+        let mut out = io::BufWriter::new(file);
+        write(&mut out)?;
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()
+    });
+
+    match result.and_then(|_| fs::rename(&partial, path)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            Err(Error::ExportFailed(path.display().to_string(), e))
+        }
+    }
+}
+
+/// Appends one message to an mbox stream.
+///
+/// Line endings are normalised to `\n` and body lines matching `>*From ` are
+/// quoted with an extra `>` (mboxrd), so the export can be read back exactly.
+fn write_mbox_entry<W: Write>(out: &mut W, data: &[u8], date: DateTime<Utc>) -> io::Result<()> {
+    writeln!(
+        out,
+        "From MAILER-DAEMON {}",
+        date.format("%a %b %e %H:%M:%S %Y")
+    )?;
+
+    let data = data.strip_suffix(b"\n").unwrap_or(data);
+    for line in data.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let unquoted = line.iter().position(|b| *b != b'>').unwrap_or(line.len());
+        if line[unquoted..].starts_with(b"From ") {
+            out.write_all(b">")?;
+        }
+        out.write_all(line)?;
+        out.write_all(b"\n")?;
+    }
+
+    out.write_all(b"\n")
+}
+
+/// Restricts a message ID to characters that are safe in a file name.
+fn sanitise(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Writes messages through an [`ExportWriter`], as they would be fetched.
+    fn write_messages(
+        dir: &Path,
+        format: ExportFormat,
+        messages: &[RawMessage],
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut writer = ExportWriter::create(dir, format, &messages[0].id, now)?;
+        for message in messages {
+            writer.write(message)?;
+        }
+        writer.finish()
+    }
+
+    fn raw(id: &str, data: &str) -> RawMessage {
+        RawMessage {
+            id: id.to_string(),
+            data: data.as_bytes().to_vec(),
+            internal_date: Some(Utc.with_ymd_and_hms(2020, 1, 2, 3, 4, 5).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_format_parse_and_display() {
+        for format in [ExportFormat::Eml, ExportFormat::Mbox] {
+            assert_eq!(ExportFormat::parse(&format.to_string()), Some(format));
+        }
+        assert_eq!(ExportFormat::parse("zip"), None);
+    }
+
+    #[test]
+    fn test_mbox_entry_quotes_from_lines() {
+        let mut out = Vec::new();
+        let date = Utc.with_ymd_and_hms(2020, 1, 2, 3, 4, 5).unwrap();
+        let data = b"Subject: hi\r\n\r\nFrom here\r\n>From there\r\nbye\r\n";
+        write_mbox_entry(&mut out, data, date).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "From MAILER-DAEMON Thu Jan  2 03:04:05 2020\n\
+             Subject: hi\n\n>From here\n>>From there\nbye\n\n"
+        );
+    }
+
+    #[test]
+    fn test_write_eml_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("archive");
+        let messages = [
+            raw("abc1", "Subject: one\r\n"),
+            raw("abc2", "Subject: two\r\n"),
+        ];
+
+        let files = write_messages(&out, ExportFormat::Eml, &messages, Utc::now()).unwrap();
+
+        assert_eq!(files, vec![out.join("abc1.eml"), out.join("abc2.eml")]);
+        assert_eq!(fs::read(&files[1]).unwrap(), b"Subject: two\r\n");
+        assert!(!out.join("abc1.part").exists());
+    }
+
+    #[test]
+    fn test_write_single_mbox_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 6, 7, 8, 9, 10).unwrap();
+        let messages = [raw("m/1", "Subject: one\n"), raw("m2", "Subject: two\n")];
+
+        let files = write_messages(dir.path(), ExportFormat::Mbox, &messages, now).unwrap();
+
+        assert_eq!(
+            files,
+            vec![dir.path().join("cull-gmail-20250607T080910-m_1.mbox")]
+        );
+        let mbox = fs::read_to_string(&files[0]).unwrap();
+        assert_eq!(mbox.matches("From MAILER-DAEMON").count(), 2);
+        assert!(mbox.contains("Subject: two\n\n"));
+    }
+
+    #[test]
+    fn test_mbox_written_only_when_finished() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 6, 7, 8, 9, 10).unwrap();
+        let path = dir.path().join("cull-gmail-20250607T080910-m1.mbox");
+
+        let mut writer = ExportWriter::create(dir.path(), ExportFormat::Mbox, "m1", now).unwrap();
+        writer.write(&raw("m1", "Subject: one\n")).unwrap();
+        assert!(!path.exists());
+        assert_eq!(writer.finish().unwrap(), vec![path.clone()]);
+        assert!(fs::read_to_string(&path).unwrap().contains("Subject: one"));
+
+        let writer = ExportWriter::create(dir.path(), ExportFormat::Mbox, "m2", now).unwrap();
+        writer.abandon();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_incomplete_mbox_is_not_named_as_finished() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 6, 7, 8, 9, 10).unwrap();
+
+        let mut writer = ExportWriter::create(dir.path(), ExportFormat::Mbox, "m1", now).unwrap();
+        writer.write(&raw("m1", "Subject: one\n")).unwrap();
+        let kept = writer.keep_incomplete().unwrap();
+
+        assert_eq!(
+            kept,
+            dir.path()
+                .join("cull-gmail-20250607T080910-m1-incomplete.mbox")
+        );
+        assert!(fs::read_to_string(&kept).unwrap().contains("Subject: one"));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let writer = ExportWriter::create(dir.path(), ExportFormat::Eml, "m2", now).unwrap();
+        assert_eq!(writer.keep_incomplete().unwrap(), dir.path());
+    }
+
+    #[test]
+    fn test_write_fails_for_unwritable_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("not-a-dir");
+        fs::write(&file, "").unwrap();
+
+        let result = write_messages(&file, ExportFormat::Eml, &[raw("a", "x")], Utc::now());
+
+        assert!(matches!(result, Err(Error::ExportFailed(_, _))));
+    }
+}
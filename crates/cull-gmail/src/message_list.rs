@@ -11,6 +11,8 @@
 //! - Label and query-based filtering
 //! - Message metadata fetching and logging
 //! - Configuration of result limits and query parameters
+//! - An optional cap on the total number of messages collected across pages
+//! - Loading a known list of message IDs instead of searching
 //!
 //! ## Error Handling
 //!
@@ -56,7 +58,7 @@
 #![warn(missing_docs)]
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
-use crate::{GmailClient, MessageSummary, Result};
+use crate::{Error, GmailClient, GmailScope, MessageSummary, Result};
 
 use google_gmail1::{
     Gmail,
@@ -117,6 +119,20 @@ pub trait MessageList {
         post: &str,
     ) -> impl std::future::Future<Output = Result<()>> + Send;
 
+    /// Fetches detailed metadata for every stored message.
+    ///
+    /// Each [`MessageSummary`] is filled in with its headers (Subject, Date,
+    /// From, To, List-Id), size estimate, labels, thread ID, snippet and
+    /// internal date. Nothing is logged, so callers can format the result
+    /// themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if retrieving the metadata of any message fails.
+    ///
+    /// [`MessageSummary`]: crate::MessageSummary
+    fn fetch_metadata(&mut self) -> impl std::future::Future<Output = Result<()>> + Send;
+
     /// Retrieves a list of messages from Gmail based on current filter settings.
     ///
     /// This method calls the Gmail API to get a page of messages matching the
@@ -173,6 +189,10 @@ pub trait MessageList {
     ///   - `1`: Fetch only the first page
     ///   - `n > 1`: Fetch exactly `n` pages or until no more pages are available
     ///
+    /// Paging also stops once the limit set with
+    /// [`set_max_messages`](MessageList::set_max_messages) is reached, whichever
+    /// comes first.
+    ///
     /// # Returns
     ///
     /// Returns `Result<()>` on success. All retrieved messages are stored internally
@@ -198,6 +218,37 @@ pub trait MessageList {
     /// ```
     fn get_messages(&mut self, pages: u32) -> impl std::future::Future<Output = Result<()>> + Send;
 
+    /// Adds messages by ID instead of searching for them.
+    ///
+    /// Each ID is looked up in Gmail and its metadata stored, so the messages
+    /// can be acted on as if they had been returned by `get_messages`.
+    /// Duplicate IDs are added once.
+    ///
+    /// # Returns
+    ///
+    /// The IDs Gmail does not know. These are not added to the message list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a lookup fails for any reason other than the
+    /// message not existing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use cull_gmail::MessageList;
+    /// # async fn example(mut client: impl MessageList) -> cull_gmail::Result<()> {
+    /// let ids = vec!["18c1f2a3b4c5d6e7".to_string()];
+    /// let unknown = client.add_message_ids(&ids).await?;
+    /// assert!(unknown.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn add_message_ids(
+        &mut self,
+        ids: &[String],
+    ) -> impl std::future::Future<Output = Result<Vec<String>>> + Send;
+
     /// Returns a reference to the Gmail API hub for direct API access.
     ///
     /// This method provides access to the underlying Gmail API client for
@@ -255,6 +306,21 @@ pub trait MessageList {
     /// message IDs and any retrieved metadata.
     fn messages(&self) -> &Vec<MessageSummary>;
 
+    /// Removes messages from the list so no action is taken on them.
+    ///
+    /// IDs that are not in the list are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use cull_gmail::MessageList;
+    /// # fn example(mut client: impl MessageList) {
+    /// // Keep a message the user deselected during review
+    /// client.remove_messages(&["18c1f2a3b4c5d6e7".to_string()]);
+    /// # }
+    /// ```
+    fn remove_messages(&mut self, ids: &[String]);
+
     /// Sets the search query string for filtering messages.
     ///
     /// This method configures the Gmail search query that will be used in
@@ -354,6 +420,62 @@ pub trait MessageList {
     /// # }
     /// ```
     fn set_max_results(&mut self, value: u32);
+
+    /// Returns the limit on the total number of messages collected, if any.
+    fn max_messages(&self) -> Option<u32>;
+
+    /// Limits the total number of messages collected by `get_messages`.
+    ///
+    /// Paging stops as soon as the limit is reached and the last page is
+    /// trimmed, so exactly `value` messages are kept when enough match. `None`
+    /// removes the limit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use cull_gmail::MessageList;
+    /// # async fn example(mut client: impl MessageList) -> cull_gmail::Result<()> {
+    /// client.set_max_results(500);
+    /// client.set_max_messages(Some(1200));
+    /// client.get_messages(0).await?; // three requests: 500, 500 and 200
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn set_max_messages(&mut self, value: Option<u32>);
+}
+
+/// Looks up message IDs, returning summaries for known messages and the unknown IDs.
+///
+/// IDs already in `existing` and repeated IDs are skipped.
+async fn lookup_message_ids<S: GmailService>(
+    service: &S,
+    existing: &[MessageSummary],
+    ids: &[String],
+) -> Result<(Vec<MessageSummary>, Vec<String>)> {
+    let mut found: Vec<MessageSummary> = Vec::new();
+    let mut unknown: Vec<String> = Vec::new();
+
+    for id in ids {
+        let seen =
+            existing.iter().chain(found.iter()).any(|m| m.id() == id) || unknown.contains(id);
+        if seen {
+            continue;
+        }
+        match service.get_message_metadata(id).await {
+            Ok(message) => {
+                let mut summary = MessageSummary::new(id);
+                summary.update_from_message(&message);
+                found.push(summary);
+            }
+            Err(Error::NotFound(_)) => {
+                log::debug!("Message `{id}` not found");
+                unknown.push(id.to_string());
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok((found, unknown))
 }
 
 /// Abstraction for Gmail API calls used by MessageList.
@@ -382,6 +504,29 @@ impl GmailClient {
             out.append(&mut list_ids);
         }
     }
+
+    /// Page size to request so that no more than `limit` messages are collected.
+    fn page_size(max_results: u32, collected: usize, limit: Option<u32>) -> u32 {
+        match limit {
+            Some(limit) => {
+                let remaining = (limit as usize).saturating_sub(collected);
+                max_results.min(u32::try_from(remaining).unwrap_or(u32::MAX))
+            }
+            None => max_results,
+        }
+    }
+
+    /// Whether `collected` messages satisfy the limit.
+    fn limit_reached(collected: usize, limit: Option<u32>) -> bool {
+        limit.is_some_and(|limit| collected >= limit as usize)
+    }
+
+    /// Drops any messages beyond the limit.
+    fn trim_to_limit(messages: &mut Vec<MessageSummary>, limit: Option<u32>) {
+        if let Some(limit) = limit {
+            messages.truncate(limit as usize);
+        }
+    }
 }
 
 impl GmailService for GmailClient {
@@ -405,7 +550,11 @@ impl GmailService for GmailClient {
         if let Some(token) = page_token {
             call = call.page_token(&token);
         }
-        let (_response, list) = call.doit().await.map_err(Box::new)?;
+        let (_response, list) = call
+            .add_scope(self.scope_for(GmailScope::ReadOnly))
+            .doit()
+            .await
+            .map_err(Box::new)?;
         Ok(list)
     }
 
@@ -414,10 +563,13 @@ impl GmailService for GmailClient {
         let (_res, m) = hub
             .users()
             .messages_get("me", message_id)
-            .add_scope("https://mail.google.com/")
+            .add_scope(self.scope_for(GmailScope::ReadOnly))
             .format("metadata")
             .add_metadata_headers("subject")
             .add_metadata_headers("date")
+            .add_metadata_headers("from")
+            .add_metadata_headers("to")
+            .add_metadata_headers("list-id")
             .doit()
             .await
             .map_err(Box::new)?;
@@ -436,6 +588,16 @@ impl MessageList for GmailClient {
         self.max_results
     }
 
+    /// Report the limit on messages collected
+    fn max_messages(&self) -> Option<u32> {
+        self.max_messages
+    }
+
+    /// Set the limit on messages collected
+    fn set_max_messages(&mut self, value: Option<u32>) {
+        self.max_messages = value;
+    }
+
     /// Add label to the labels collection
     fn add_labels(&mut self, labels: &[String]) -> Result<()> {
         log::debug!("labels from command line: {labels:?}");
@@ -471,6 +633,11 @@ impl MessageList for GmailClient {
         self.messages.iter().map(|m| m.id().to_string()).collect()
     }
 
+    /// Remove messages from the list
+    fn remove_messages(&mut self, ids: &[String]) {
+        self.messages.retain(|m| !ids.iter().any(|id| id == m.id()));
+    }
+
     /// Get a reference to the message_ids
     fn label_ids(&self) -> Vec<String> {
         self.label_ids.clone()
@@ -483,38 +650,31 @@ impl MessageList for GmailClient {
 
     /// Run the Gmail api as configured
     async fn get_messages(&mut self, pages: u32) -> Result<()> {
-        let list = self.list_messages(None).await?;
-        match pages {
-            1 => {}
-            0 => {
-                let mut list = list;
-                let mut page = 1;
-                loop {
-                    page += 1;
-                    log::debug!("Processing page #{page}");
-                    if list.next_page_token.is_none() {
-                        break;
-                    }
-                    list = self.list_messages(list.next_page_token).await?;
-                    // self.log_message_subjects(&list).await?;
-                }
+        let mut list = self.list_messages(None).await?;
+        let mut page = 1;
+        while pages == 0 || page < pages {
+            if list.next_page_token.is_none() {
+                break;
             }
-            _ => {
-                let mut list = list;
-                for page in 2..=pages {
-                    log::debug!("Processing page #{page}");
-                    if list.next_page_token.is_none() {
-                        break;
-                    }
-                    list = self.list_messages(list.next_page_token).await?;
-                    // self.log_message_subjects(&list).await?;
-                }
+            if Self::limit_reached(self.messages.len(), self.max_messages) {
+                log::debug!("Reached the limit of {:?} messages", self.max_messages);
+                break;
             }
+            page += 1;
+            log::debug!("Processing page #{page}");
+            list = self.list_messages(list.next_page_token).await?;
         }
 
         Ok(())
     }
 
+    async fn add_message_ids(&mut self, ids: &[String]) -> Result<Vec<String>> {
+        let (mut found, unknown) = lookup_message_ids(&*self, &self.messages, ids).await?;
+        log::debug!("Added {} message(s) by ID", found.len());
+        self.messages.append(&mut found);
+        Ok(unknown)
+    }
+
     async fn list_messages(
         &mut self,
         next_page_token: Option<String>,
@@ -529,13 +689,13 @@ impl MessageList for GmailClient {
             log::debug!("Setting token for next page.");
         }
 
+        let page_size = Self::page_size(self.max_results, self.messages.len(), self.max_messages);
+        if page_size == 0 {
+            return Ok(ListMessagesResponse::default());
+        }
+
         let list = self
-            .list_messages_page(
-                &self.label_ids,
-                &self.query,
-                self.max_results,
-                next_page_token,
-            )
+            .list_messages_page(&self.label_ids, &self.query, page_size, next_page_token)
             .await?;
         log::trace!(
             "Estimated {} messages.",
@@ -548,33 +708,27 @@ impl MessageList for GmailClient {
         }
 
         Self::append_list_to_messages(&mut self.messages, &list);
+        Self::trim_to_limit(&mut self.messages, self.max_messages);
 
         Ok(list)
     }
 
-    async fn log_messages(&mut self, pre: &str, post: &str) -> Result<()> {
+    async fn fetch_metadata(&mut self) -> Result<()> {
         for i in 0..self.messages.len() {
             let id = self.messages[i].id().to_string();
             log::trace!("{id}");
             let m = self.get_message_metadata(&id).await?;
-            let message = &mut self.messages[i];
             log::trace!("Got the message: {m:?}");
-            let Some(payload) = m.payload else { continue };
-            let Some(headers) = payload.headers else {
-                continue;
-            };
-
-            for header in headers {
-                if let Some(name) = header.name {
-                    match name.to_lowercase().as_str() {
-                        "subject" => message.set_subject(header.value),
-                        "date" => message.set_date(header.value),
-                        _ => {}
-                    }
-                } else {
-                    continue;
-                }
-            }
+            self.messages[i].update_from_message(&m);
+        }
+
+        Ok(())
+    }
+
+    async fn log_messages(&mut self, pre: &str, post: &str) -> Result<()> {
+        self.fetch_metadata().await?;
+
+        for message in &self.messages {
             log::info!("{pre}{}{post}", message.list_date_and_subject());
         }
 
@@ -590,6 +744,7 @@ mod tests {
         label_ids: Vec<String>,
         query: String,
         max_results: u32,
+        max_messages: Option<u32>,
         messages: Vec<MessageSummary>,
     }
 
@@ -599,6 +754,7 @@ mod tests {
                 label_ids: vec![],
                 query: String::new(),
                 max_results: 200,
+                max_messages: None,
                 messages: vec![],
             }
         }
@@ -612,6 +768,9 @@ mod tests {
         async fn log_messages(&mut self, _pre: &str, _post: &str) -> Result<()> {
             Ok(())
         }
+        async fn fetch_metadata(&mut self) -> Result<()> {
+            Ok(())
+        }
         async fn list_messages(
             &mut self,
             _next_page_token: Option<String>,
@@ -621,6 +780,9 @@ mod tests {
         async fn get_messages(&mut self, _pages: u32) -> Result<()> {
             Ok(())
         }
+        async fn add_message_ids(&mut self, _ids: &[String]) -> Result<Vec<String>> {
+            Ok(vec![])
+        }
         fn hub(&self) -> Gmail<HttpsConnector<HttpConnector>> {
             panic!("not used in tests")
         }
@@ -633,6 +795,9 @@ mod tests {
         fn messages(&self) -> &Vec<MessageSummary> {
             &self.messages
         }
+        fn remove_messages(&mut self, ids: &[String]) {
+            self.messages.retain(|m| !ids.iter().any(|id| id == m.id()));
+        }
         fn set_query(&mut self, query: &str) {
             self.query = query.to_string();
         }
@@ -648,6 +813,12 @@ mod tests {
         fn set_max_results(&mut self, value: u32) {
             self.max_results = value;
         }
+        fn max_messages(&self) -> Option<u32> {
+            self.max_messages
+        }
+        fn set_max_messages(&mut self, value: Option<u32>) {
+            self.max_messages = value;
+        }
     }
 
     use std::collections::HashMap;
@@ -657,8 +828,10 @@ mod tests {
         label_ids: Vec<String>,
         query: String,
         max_results: u32,
+        max_messages: Option<u32>,
         messages: Vec<MessageSummary>,
         pages: Mutex<HashMap<Option<String>, ListMessagesResponse>>,
+        requested_sizes: Mutex<Vec<u32>>,
     }
 
     impl TestClient {
@@ -667,8 +840,10 @@ mod tests {
                 label_ids: vec![],
                 query: String::new(),
                 max_results: 200,
+                max_messages: None,
                 messages: vec![],
                 pages: Mutex::new(map),
+                requested_sizes: Mutex::new(vec![]),
             }
         }
     }
@@ -678,9 +853,10 @@ mod tests {
             &self,
             _label_ids: &[String],
             _query: &str,
-            _max_results: u32,
+            max_results: u32,
             page_token: Option<String>,
         ) -> Result<ListMessagesResponse> {
+            self.requested_sizes.lock().unwrap().push(max_results);
             let map = self.pages.lock().unwrap();
             Ok(map
                 .get(&page_token)
@@ -688,7 +864,10 @@ mod tests {
                 .unwrap_or_else(ListMessagesResponse::default))
         }
 
-        async fn get_message_metadata(&self, _message_id: &str) -> Result<GmailMessage> {
+        async fn get_message_metadata(&self, message_id: &str) -> Result<GmailMessage> {
+            if message_id.starts_with("missing") {
+                return Err(Error::NotFound(message_id.to_string()));
+            }
             Ok(GmailMessage::default())
         }
     }
@@ -700,6 +879,12 @@ mod tests {
         fn max_results(&self) -> u32 {
             self.max_results
         }
+        fn max_messages(&self) -> Option<u32> {
+            self.max_messages
+        }
+        fn set_max_messages(&mut self, value: Option<u32>) {
+            self.max_messages = value;
+        }
         fn add_labels(&mut self, _labels: &[String]) -> Result<()> {
             Ok(())
         }
@@ -712,6 +897,9 @@ mod tests {
         fn messages(&self) -> &Vec<MessageSummary> {
             &self.messages
         }
+        fn remove_messages(&mut self, ids: &[String]) {
+            self.messages.retain(|m| !ids.iter().any(|id| id == m.id()));
+        }
         fn message_ids(&self) -> Vec<String> {
             self.messages.iter().map(|m| m.id().to_string()).collect()
         }
@@ -723,36 +911,34 @@ mod tests {
         }
         async fn get_messages(&mut self, pages: u32) -> Result<()> {
             let mut list = self.list_messages(None).await?;
-            match pages {
-                1 => {}
-                0 => loop {
-                    if list.next_page_token.is_none() {
-                        break;
-                    }
-                    list = self.list_messages(list.next_page_token).await?;
-                },
-                _ => {
-                    for _page in 2..=pages {
-                        if list.next_page_token.is_none() {
-                            break;
-                        }
-                        list = self.list_messages(list.next_page_token).await?;
-                    }
+            let mut page = 1;
+            while pages == 0 || page < pages {
+                if list.next_page_token.is_none()
+                    || GmailClient::limit_reached(self.messages.len(), self.max_messages)
+                {
+                    break;
                 }
+                page += 1;
+                list = self.list_messages(list.next_page_token).await?;
             }
             Ok(())
         }
+        async fn add_message_ids(&mut self, ids: &[String]) -> Result<Vec<String>> {
+            let (mut found, unknown) = lookup_message_ids(&*self, &self.messages, ids).await?;
+            self.messages.append(&mut found);
+            Ok(unknown)
+        }
         async fn list_messages(
             &mut self,
             next_page_token: Option<String>,
         ) -> Result<ListMessagesResponse> {
+            let page_size =
+                GmailClient::page_size(self.max_results, self.messages.len(), self.max_messages);
+            if page_size == 0 {
+                return Ok(ListMessagesResponse::default());
+            }
             let list = self
-                .list_messages_page(
-                    &self.label_ids,
-                    &self.query,
-                    self.max_results,
-                    next_page_token,
-                )
+                .list_messages_page(&self.label_ids, &self.query, page_size, next_page_token)
                 .await?;
 
             if list.result_size_estimate.unwrap_or(0) == 0 {
@@ -766,12 +952,16 @@ mod tests {
                     .collect();
                 self.messages.append(&mut list_ids);
             }
+            GmailClient::trim_to_limit(&mut self.messages, self.max_messages);
 
             Ok(list)
         }
         async fn log_messages(&mut self, _pre: &str, _post: &str) -> Result<()> {
             Ok(())
         }
+        async fn fetch_metadata(&mut self) -> Result<()> {
+            Ok(())
+        }
     }
 
     #[test]
@@ -799,6 +989,15 @@ mod tests {
         assert_eq!(ml.max_results(), 123);
     }
 
+    #[test]
+    fn remove_messages_drops_ids() {
+        let mut ml = MockList::new();
+        ml.push_msg("abc");
+        ml.push_msg("def");
+        ml.remove_messages(&["abc".to_string(), "xyz".to_string()]);
+        assert_eq!(ml.message_ids(), vec!["def"]);
+    }
+
     #[test]
     fn message_ids_maps_from_messages() {
         let mut ml = MockList::new();
@@ -903,4 +1102,73 @@ mod tests {
         client.get_messages(5).await.unwrap();
         assert_eq!(client.message_ids(), vec!["x"]);
     }
+
+    fn ids_page(ids: &[&str], next: Option<&str>) -> ListMessagesResponse {
+        use google_gmail1::api::Message;
+        ListMessagesResponse {
+            messages: Some(
+                ids.iter()
+                    .map(|id| Message {
+                        id: Some(id.to_string()),
+                        ..Default::default()
+                    })
+                    .collect(),
+            ),
+            next_page_token: next.map(str::to_string),
+            result_size_estimate: Some(ids.len() as u32),
+        }
+    }
+
+    #[test]
+    fn page_size_respects_limit() {
+        assert_eq!(GmailClient::page_size(500, 0, None), 500);
+        assert_eq!(GmailClient::page_size(500, 0, Some(1200)), 500);
+        assert_eq!(GmailClient::page_size(500, 1000, Some(1200)), 200);
+        assert_eq!(GmailClient::page_size(500, 1200, Some(1200)), 0);
+        assert!(GmailClient::limit_reached(3, Some(3)));
+        assert!(!GmailClient::limit_reached(3, None));
+    }
+
+    #[tokio::test]
+    async fn max_messages_stops_paging_exactly() {
+        let mut map = HashMap::new();
+        map.insert(None, ids_page(&["a", "b"], Some("t2")));
+        map.insert(Some("t2".into()), ids_page(&["c", "d"], Some("t3")));
+        map.insert(Some("t3".into()), ids_page(&["e", "f"], None));
+
+        let mut client = TestClient::with_pages(map);
+        client.set_max_results(2);
+        client.set_max_messages(Some(3));
+
+        client.get_messages(0).await.unwrap();
+        assert_eq!(client.message_ids(), vec!["a", "b", "c"]);
+        assert_eq!(*client.requested_sizes.lock().unwrap(), vec![2, 1]);
+    }
+
+    #[tokio::test]
+    async fn pages_limit_applies_before_max_messages() {
+        let mut map = HashMap::new();
+        map.insert(None, ids_page(&["a", "b"], Some("t2")));
+        map.insert(Some("t2".into()), ids_page(&["c", "d"], None));
+
+        let mut client = TestClient::with_pages(map);
+        client.set_max_results(2);
+        client.set_max_messages(Some(10));
+
+        client.get_messages(1).await.unwrap();
+        assert_eq!(client.message_ids(), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn add_message_ids_reports_unknown_and_skips_duplicates() {
+        let mut client = TestClient::with_pages(HashMap::new());
+        let ids = ["a", "missing-1", "b", "a", "missing-1"]
+            .map(str::to_string)
+            .to_vec();
+
+        let unknown = client.add_message_ids(&ids).await.unwrap();
+
+        assert_eq!(client.message_ids(), vec!["a", "b"]);
+        assert_eq!(unknown, vec!["missing-1"]);
+    }
 }
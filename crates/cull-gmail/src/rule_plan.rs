@@ -0,0 +1,221 @@
+//! # Rule Plan Module
+//!
+//! This module provides [`Plan`], the result of resolving the retention rules
+//! into the exact messages each rule would act on.
+//!
+//! ## Overview
+//!
+//! Running rules is split into two phases so the effect can be reviewed before
+//! anything changes:
+//!
+//! 1. **Plan**: every rule is evaluated and the IDs of the messages it selects
+//!    are recorded per label, with a count and sample subjects.
+//! 2. **Apply**: the recorded actions are carried out on exactly those IDs,
+//!    without evaluating the rules again.
+//!
+//! A plan records when it was made and a [fingerprint](Rules::fingerprint) of
+//! the rules it was made from. [`Plan::check`] refuses a plan that is older
+//! than the allowed age or whose rules have changed since, as its message
+//! lists no longer reflect what the rules would select.
+//!
+//! ## Usage Examples
+//!
+//! ```
+//! use chrono::TimeDelta;
+//! use cull_gmail::{EolAction, Plan, PlanEntry, Rules};
+//!
+//! let rules = Rules::new();
+//! let mut plan = Plan::new(&rules);
+//! plan.add_entry(PlanEntry {
+//!     rule_id: 1,
+//!     label: "retention/1-years".to_string(),
+//!     action: EolAction::Trash,
+//!     count: 1,
+//!     message_ids: vec!["18c2f3a4b5d6e7f8".to_string()],
+//!     samples: vec!["Your weekly digest".to_string()],
+//! });
+//!
+//! let json = plan.to_json().unwrap();
+//! let plan = Plan::from_json(&json).unwrap();
+//! assert!(plan.check(&rules, TimeDelta::hours(24)).is_ok());
+//! ```
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{EolAction, Error, GmailScope, Result, Rules};
+
+/// The messages a rule acts on for one label.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanEntry {
+    /// ID of the rule that selected the messages
+    pub rule_id: usize,
+    /// Label the messages were selected by
+    pub label: String,
+    /// Action to apply to the messages
+    pub action: EolAction,
+    /// Number of messages selected
+    pub count: usize,
+    /// IDs of the messages to act on
+    pub message_ids: Vec<String>,
+    /// Subjects of the first few messages, for review
+    pub samples: Vec<String>,
+}
+
+/// The actions running the rules would take, resolved to message IDs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    created: DateTime<Utc>,
+    rules_hash: String,
+    entries: Vec<PlanEntry>,
+}
+
+impl Plan {
+    /// Creates an empty plan for `rules`, made now.
+    pub fn new(rules: &Rules) -> Self {
+        Plan {
+            created: Utc::now(),
+            rules_hash: rules.fingerprint(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds the messages a rule acts on for one label.
+    pub fn add_entry(&mut self, entry: PlanEntry) -> &mut Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Returns when the plan was made.
+    pub fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    /// Returns the planned actions, in the order they are applied.
+    pub fn entries(&self) -> &[PlanEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of messages the plan acts on.
+    pub fn message_count(&self) -> usize {
+        self.entries.iter().map(|e| e.message_ids.len()).sum()
+    }
+
+    /// Returns the OAuth2 scope needed to apply the plan.
+    ///
+    /// Full access is only needed when the plan permanently deletes messages.
+    pub fn scope(&self) -> GmailScope {
+        self.entries
+            .iter()
+            .map(|e| GmailScope::for_action(e.action))
+            .max()
+            .unwrap_or(GmailScope::Modify)
+    }
+
+    /// Serialises the plan as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::SerializationError`] if the plan cannot be serialised
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::SerializationError(e.to_string()))
+    }
+
+    /// Reads a plan from JSON written by [`Plan::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::PlanInvalid`] if the JSON is not a plan
+    pub fn from_json(json: &str) -> Result<Plan> {
+        serde_json::from_str(json).map_err(|e| Error::PlanInvalid(e.to_string()))
+    }
+
+    /// Checks that the plan can still be applied.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::PlanStale`] if the plan was made more than `max_age` ago
+    /// * [`Error::PlanRulesChanged`] if `rules` differ from the rules the plan
+    ///   was made from
+    pub fn check(&self, rules: &Rules, max_age: TimeDelta) -> Result<()> {
+        self.check_at(rules, max_age, Utc::now())
+    }
+
+    fn check_at(&self, rules: &Rules, max_age: TimeDelta, now: DateTime<Utc>) -> Result<()> {
+        let age = now - self.created;
+        if age > max_age {
+            return Err(Error::PlanStale(age.num_hours(), max_age.num_hours()));
+        }
+        if rules.fingerprint() != self.rules_hash {
+            return Err(Error::PlanRulesChanged);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MessageAge, Retention};
+
+    fn entry(action: EolAction) -> PlanEntry {
+        PlanEntry {
+            rule_id: 1,
+            label: "retention/1-years".to_string(),
+            action,
+            count: 2,
+            message_ids: vec!["a1".to_string(), "b2".to_string()],
+            samples: vec!["Hello".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_plan_json_round_trip() {
+        let mut plan = Plan::new(&Rules::new());
+        plan.add_entry(entry(EolAction::Trash))
+            .add_entry(entry(EolAction::Delete));
+
+        let json = plan.to_json().unwrap();
+        assert!(json.contains("\"action\": \"delete\""));
+        assert_eq!(Plan::from_json(&json).unwrap(), plan);
+        assert_eq!(plan.message_count(), 4);
+
+        assert!(matches!(
+            Plan::from_json("{\"entries\": []}"),
+            Err(Error::PlanInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_plan_scope() {
+        let mut plan = Plan::new(&Rules::new());
+        assert_eq!(plan.scope(), GmailScope::Modify);
+        plan.add_entry(entry(EolAction::Trash));
+        assert_eq!(plan.scope(), GmailScope::Modify);
+        plan.add_entry(entry(EolAction::Delete));
+        assert_eq!(plan.scope(), GmailScope::Full);
+    }
+
+    #[test]
+    fn test_plan_check() {
+        let mut rules = Rules::new();
+        let plan = Plan::new(&rules);
+        let max_age = TimeDelta::hours(24);
+
+        assert!(plan.check_at(&rules, max_age, plan.created()).is_ok());
+        assert!(matches!(
+            plan.check_at(&rules, max_age, plan.created() + TimeDelta::hours(25)),
+            Err(Error::PlanStale(25, 24))
+        ));
+
+        rules.add_rule(
+            Retention::new(MessageAge::Days(30), false),
+            Some("spam"),
+            false,
+        );
+        assert!(matches!(
+            plan.check_at(&rules, max_age, plan.created()),
+            Err(Error::PlanRulesChanged)
+        ));
+    }
+}
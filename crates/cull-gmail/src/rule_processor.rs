@@ -10,7 +10,8 @@
 //! - **Destructive Operations**: The [`RuleProcessor::batch_delete`] method permanently
 //!   removes messages from Gmail and cannot be undone.
 //! - **Recoverable Operations**: The [`RuleProcessor::batch_trash`] method moves messages
-//!   to the Gmail trash folder, from which they can be recovered within 30 days.
+//!   to the Gmail trash folder, from which they can be recovered within 30 days using
+//!   [`RuleProcessor::batch_untrash`].
 //! - **Execute Flag**: All destructive operations are gated by an execute flag that must
 //!   be explicitly set to `true`. When `false`, operations run in "dry-run" mode.
 //!
@@ -24,6 +25,9 @@
 //!    - Prepare the message list via [`RuleProcessor::prepare`]
 //!    - Execute the rule's action (trash) if execute flag is true
 //!
+//! Rules with `export_before_action` set archive each chunk of messages locally
+//! before it is permanently deleted; see [`crate::MessageExport`].
+//!
 //! ## Example
 //!
 //! ```text
@@ -49,9 +53,16 @@
 //! }
 //! ```
 
-use google_gmail1::api::{BatchDeleteMessagesRequest, BatchModifyMessagesRequest};
+use std::path::Path;
+
+use google_gmail1::api::BatchDeleteMessagesRequest;
 
-use crate::{EolAction, Error, GmailClient, Result, message_list::MessageList, rules::EolRule};
+use crate::{
+    EolAction, Error, ExportFormat, GmailClient, GmailScope, MessageExport, Result,
+    label_manager::{LabelOperations, modify_in_chunks},
+    message_list::MessageList,
+    rules::EolRule,
+};
 
 /// Gmail label name for the trash folder.
 ///
@@ -63,19 +74,6 @@ const TRASH_LABEL: &str = "TRASH";
 /// This constant ensures consistent usage of the INBOX label throughout the module.
 const INBOX_LABEL: &str = "INBOX";
 
-/// Gmail API scope for modifying messages (recommended scope for most operations).
-///
-/// This scope allows adding/removing labels, moving messages to trash, and other
-/// modification operations. Preferred over broader scopes for security.
-const GMAIL_MODIFY_SCOPE: &str = "https://www.googleapis.com/auth/gmail.modify";
-
-/// Gmail API scope for deleting messages.
-///
-/// This scope allows all operations and is required to authorise the batch
-/// delete operation. It is only used for batch delete. For all other
-/// operations `GMAIL_MODIFY_SCOPE` is preferred.
-const GMAIL_DELETE_SCOPE: &str = "https://mail.google.com/";
-
 /// Internal trait defining the minimal operations needed for rule processing.
 ///
 /// This trait is used internally to enable unit testing of orchestration logic
@@ -92,6 +90,12 @@ pub(crate) trait MailOperations {
     /// Set the query string for message filtering
     fn set_query(&mut self, query: &str);
 
+    /// Limit the number of messages prepared
+    fn set_max_messages(&mut self, value: Option<u32>);
+
+    /// Number of messages prepared
+    fn message_count(&self) -> usize;
+
     /// Prepare messages by fetching from Gmail API
     fn prepare(&mut self, pages: u32) -> impl std::future::Future<Output = Result<()>> + Send;
 
@@ -109,7 +113,18 @@ async fn process_label_with_rule<T: MailOperations>(
     label: &str,
     pages: u32,
     execute: bool,
+    budget: &mut Option<u32>,
 ) -> Result<()> {
+    // The rule's message limit is shared by all of its labels
+    if *budget == Some(0) {
+        log::info!(
+            "Rule #{} reached its limit of {:?} messages, skipping label: {label}",
+            rule.id(),
+            rule.max_messages()
+        );
+        return Ok(());
+    }
+
     // Add the label for filtering
     client.add_labels(&[label.to_owned()])?;
 
@@ -125,9 +140,15 @@ async fn process_label_with_rule<T: MailOperations>(
 
     // Set the query and prepare messages
     client.set_query(&query);
+    client.set_max_messages(*budget);
     log::info!("Ready to process messages for label: {label}");
     client.prepare(pages).await?;
 
+    if let Some(left) = budget {
+        let count = u32::try_from(client.message_count()).unwrap_or(u32::MAX);
+        *left = left.saturating_sub(count);
+    }
+
     // Execute or dry-run based on execute flag
     if execute {
         log::info!("Execute mode: applying rule action to messages");
@@ -138,6 +159,42 @@ async fn process_label_with_rule<T: MailOperations>(
     }
 }
 
+/// Orchestrates [`RuleProcessor::batch_untrash`] so it can be unit tested.
+///
+/// `TRASH` is removed from the prepared messages and `INBOX`, or the
+/// `restore_label`, is added, in chunks of up to 1000 messages.
+async fn untrash_messages<T: LabelOperations>(
+    client: &mut T,
+    restore_label: Option<&str>,
+) -> Result<()> {
+    let message_ids = client.prepared_message_ids();
+
+    // Early return if no messages to restore, avoiding unnecessary API calls
+    if message_ids.is_empty() {
+        log::info!("No messages to restore - skipping batch untrash operation");
+        return Ok(());
+    }
+
+    let label_id = match restore_label {
+        None => INBOX_LABEL.to_string(),
+        Some(name) => client
+            .label_id_for(name)
+            .ok_or_else(|| Error::LabelNotFoundInMailbox(name.to_string()))?,
+    };
+
+    client
+        .log_prepared("Message with subject `", "` restored from trash")
+        .await?;
+
+    modify_in_chunks(
+        client,
+        &message_ids,
+        &[label_id],
+        &[TRASH_LABEL.to_string()],
+    )
+    .await
+}
+
 /// Implement the internal mail operations trait for GmailClient.
 impl MailOperations for GmailClient {
     fn add_labels(&mut self, labels: &[String]) -> Result<()> {
@@ -152,6 +209,14 @@ impl MailOperations for GmailClient {
         MessageList::set_query(self, query);
     }
 
+    fn set_max_messages(&mut self, value: Option<u32>) {
+        MessageList::set_max_messages(self, value);
+    }
+
+    fn message_count(&self) -> usize {
+        self.messages.len()
+    }
+
     async fn prepare(&mut self, pages: u32) -> Result<()> {
         self.get_messages(pages).await
     }
@@ -175,6 +240,9 @@ pub trait RuleProcessor {
     /// 3. Prepares the message list for processing
     /// 4. Executes the rule's action (if execute flag is true) or runs in dry-run mode
     ///
+    /// The rule's `max_messages` limit covers all labels processed since
+    /// [`set_rule`](Self::set_rule); once it is used up, further labels are skipped.
+    ///
     /// # Arguments
     ///
     /// * `label` - The Gmail label name to process (e.g., "INBOX", "old-emails")
@@ -328,8 +396,32 @@ pub trait RuleProcessor {
     /// Requires the `https://www.googleapis.com/auth/gmail.modify` scope.
     fn batch_trash(&mut self) -> impl std::future::Future<Output = Result<()>> + Send;
 
+    /// Restores all prepared messages from the Gmail trash folder.
+    ///
+    /// The TRASH label is removed and `INBOX` is added back, or the label named
+    /// by `restore_label` when given. To find trashed messages, include the
+    /// `TRASH` label in the filter before preparing the message list.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - All messages successfully restored
+    /// * `Err(Error::LabelNotFoundInMailbox)` - `restore_label` does not exist
+    /// * `Err(_)` - Gmail API error, network failure, or insufficient permissions
+    ///
+    /// # Gmail API Requirements
+    ///
+    /// Requires the `https://www.googleapis.com/auth/gmail.modify` scope.
+    fn batch_untrash(
+        &mut self,
+        restore_label: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<()>> + Send;
+
     /// Chunk the message lists to respect API limits and call required action.
     ///
+    /// When the current rule sets `export_before_action`, each chunk is
+    /// exported as EML files before it is deleted. If the export fails the
+    /// chunk is not deleted and the error is returned.
+    ///
     /// # Returns
     ///
     /// * `Ok(())` - All messages successfully deleted
@@ -384,6 +476,18 @@ pub trait RuleProcessor {
         &self,
         ids: &[String],
     ) -> impl std::future::Future<Output = Result<()>> + Send;
+
+    /// Calls the Gmail API to remove a slice of messages from the trash and
+    /// add `label_id` to them.
+    ///
+    /// # Gmail API Requirements
+    ///
+    /// Requires the `https://www.googleapis.com/auth/gmail.modify` scope.
+    fn call_batch_untrash(
+        &self,
+        ids: &[String],
+        label_id: &str,
+    ) -> impl std::future::Future<Output = Result<()>> + Send;
 }
 
 impl RuleProcessor for GmailClient {
@@ -402,7 +506,12 @@ impl RuleProcessor for GmailClient {
     ///
     /// The rule defines which messages to target and what action to perform on them.
     /// This must be called before processing any labels.
+    ///
+    /// The rule's `max_messages` limit is shared by the labels processed until
+    /// the next call, so a rule with several labels acts on at most that many
+    /// messages in total.
     fn set_rule(&mut self, value: EolRule) {
+        self.message_budget = value.max_messages();
         self.rule = Some(value);
     }
 
@@ -442,9 +551,12 @@ impl RuleProcessor for GmailClient {
         };
 
         let execute = self.execute;
+        let mut budget = self.message_budget;
 
         // Delegate to internal orchestration function
-        process_label_with_rule(self, &rule, label, 0, execute).await
+        let result = process_label_with_rule(self, &rule, label, 0, execute, &mut budget).await;
+        self.message_budget = budget;
+        result
     }
 
     /// Fetches messages from Gmail API based on current query and label filters.
@@ -521,6 +633,18 @@ impl RuleProcessor for GmailClient {
         Ok(())
     }
 
+    /// Restores all prepared messages from Gmail's trash using batch modify API.
+    ///
+    /// Messages are processed in chunks of up to 1000 IDs, the batch modify
+    /// limit. `INBOX` is restored unless another label is requested.
+    ///
+    /// # API Scope Requirements
+    ///
+    /// Uses `https://www.googleapis.com/auth/gmail.modify` scope.
+    async fn batch_untrash(&mut self, restore_label: Option<&str>) -> Result<()> {
+        untrash_messages(self, restore_label).await
+    }
+
     async fn process_in_chunks(&self, message_ids: Vec<String>, action: EolAction) -> Result<()> {
         let (chunks, remainder) = message_ids.as_chunks::<1000>();
         log::info!(
@@ -529,9 +653,27 @@ impl RuleProcessor for GmailClient {
             remainder.len()
         );
 
-        let act = async |action, list| match action {
+        let export_dir = self
+            .rule
+            .as_ref()
+            .and_then(|rule| rule.export_before_action())
+            .map(Path::new);
+
+        let act = async |action, list: &[String]| match action {
             EolAction::Trash => self.call_batch_trash(list).await,
-            EolAction::Delete => self.call_batch_delete(list).await,
+            EolAction::Delete => {
+                if let Some(dir) = export_dir
+                    && let Err(e) = self.export_messages(list, ExportFormat::Eml, dir).await
+                {
+                    log::error!(
+                        "Export to `{}` failed, {} message(s) not deleted",
+                        dir.display(),
+                        list.len()
+                    );
+                    return Err(e);
+                }
+                self.call_batch_delete(list).await
+            }
         };
 
         if !chunks.is_empty() {
@@ -558,7 +700,7 @@ impl RuleProcessor for GmailClient {
             .hub()
             .users()
             .messages_batch_delete(batch_request, "me")
-            .add_scope(GMAIL_DELETE_SCOPE)
+            .add_scope(self.scope_for(GmailScope::Full))
             .doit()
             .await
             .map_err(Box::new);
@@ -571,35 +713,20 @@ impl RuleProcessor for GmailClient {
     }
 
     async fn call_batch_trash(&self, ids: &[String]) -> Result<()> {
-        let ids = Some(Vec::from(ids));
-        let add_label_ids = Some(vec![TRASH_LABEL.to_string()]);
-        let remove_label_ids = Some(vec![INBOX_LABEL.to_string()]);
-
-        let batch_request = BatchModifyMessagesRequest {
-            add_label_ids,
-            ids,
-            remove_label_ids,
-        };
-
-        log::trace!("{batch_request:#?}");
-
-        let _res = self
-            .hub()
-            .users()
-            .messages_batch_modify(batch_request, "me")
-            .add_scope(GMAIL_MODIFY_SCOPE)
-            .doit()
+        self.batch_modify(ids, &[TRASH_LABEL.to_string()], &[INBOX_LABEL.to_string()])
             .await
-            .map_err(Box::new)?;
+    }
 
-        Ok(())
+    async fn call_batch_untrash(&self, ids: &[String], label_id: &str) -> Result<()> {
+        self.batch_modify(ids, &[label_id.to_string()], &[TRASH_LABEL.to_string()])
+            .await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{EolAction, Error, MessageSummary, rules::EolRule};
+    use crate::{EolAction, Error, MessageSummary, rules::EolRule, test_utils::FakeLabels};
     use std::sync::{Arc, Mutex};
 
     /// Test helper to create a simple EolRule with or without a query
@@ -624,6 +751,9 @@ mod tests {
         labels: Vec<String>,
         label_ids: Vec<String>,
         query: String,
+        max_messages: Option<u32>,
+        available_messages: usize,
+        prepared_messages: usize,
         messages_prepared: bool,
         prepare_call_count: u32,
         batch_trash_call_count: Arc<Mutex<u32>>, // Use Arc<Mutex> for thread safety
@@ -639,6 +769,9 @@ mod tests {
                 labels: Vec::new(),
                 label_ids: Vec::new(),
                 query: String::new(),
+                max_messages: None,
+                available_messages: 0,
+                prepared_messages: 0,
                 messages_prepared: false,
                 prepare_call_count: 0,
                 batch_trash_call_count: Arc::new(Mutex::new(0)),
@@ -715,6 +848,14 @@ mod tests {
             self.query = query.to_owned();
         }
 
+        fn set_max_messages(&mut self, value: Option<u32>) {
+            self.max_messages = value;
+        }
+
+        fn message_count(&self) -> usize {
+            self.prepared_messages
+        }
+
         async fn prepare(&mut self, _pages: u32) -> Result<()> {
             // Always increment the counter to track that prepare was called
             self.prepare_call_count += 1;
@@ -723,6 +864,10 @@ mod tests {
                 return Err(Error::NoLabelsFound); // Use a valid error variant
             }
             self.messages_prepared = true;
+            self.prepared_messages = match self.max_messages {
+                Some(limit) => self.available_messages.min(limit as usize),
+                None => self.available_messages,
+            };
             Ok(())
         }
 
@@ -743,7 +888,7 @@ mod tests {
         let rule = create_test_rule(1, true);
         let label = "missing-label";
 
-        let result = process_label_with_rule(&mut client, &rule, label, 0, false).await;
+        let result = process_label_with_rule(&mut client, &rule, label, 0, false, &mut None).await;
 
         assert!(matches!(result, Err(Error::LabelNotFoundInMailbox(_))));
         assert_eq!(client.prepare_call_count, 0);
@@ -756,7 +901,7 @@ mod tests {
         let rule = create_test_rule(2, false); // Rule without query
         let label = "test-label";
 
-        let result = process_label_with_rule(&mut client, &rule, label, 0, false).await;
+        let result = process_label_with_rule(&mut client, &rule, label, 0, false, &mut None).await;
 
         assert!(matches!(result, Err(Error::NoQueryStringCalculated(2))));
         assert_eq!(client.prepare_call_count, 0);
@@ -769,7 +914,7 @@ mod tests {
         let rule = create_test_rule(3, true);
         let label = "test-label";
 
-        let result = process_label_with_rule(&mut client, &rule, label, 0, false).await;
+        let result = process_label_with_rule(&mut client, &rule, label, 0, false, &mut None).await;
 
         assert!(result.is_ok());
         assert_eq!(client.prepare_call_count, 1);
@@ -784,7 +929,7 @@ mod tests {
         let rule = create_test_rule(4, true);
         let label = "test-label";
 
-        let result = process_label_with_rule(&mut client, &rule, label, 0, true).await;
+        let result = process_label_with_rule(&mut client, &rule, label, 0, true, &mut None).await;
 
         assert!(result.is_ok());
         assert_eq!(client.prepare_call_count, 1);
@@ -802,7 +947,7 @@ mod tests {
         let rule = create_test_rule(5, true);
         let label = "test-label";
 
-        let result = process_label_with_rule(&mut client, &rule, label, 0, true).await;
+        let result = process_label_with_rule(&mut client, &rule, label, 0, true, &mut None).await;
 
         assert!(result.is_err());
         assert_eq!(client.prepare_call_count, 1); // prepare should be called once
@@ -818,7 +963,7 @@ mod tests {
         let rule = create_test_rule(6, true);
         let label = "test-label";
 
-        let result = process_label_with_rule(&mut client, &rule, label, 0, true).await;
+        let result = process_label_with_rule(&mut client, &rule, label, 0, true, &mut None).await;
 
         assert!(result.is_err());
         assert_eq!(client.prepare_call_count, 1);
@@ -832,7 +977,8 @@ mod tests {
         let label = "test-label";
         let pages = 5;
 
-        let result = process_label_with_rule(&mut client, &rule, label, pages, false).await;
+        let result =
+            process_label_with_rule(&mut client, &rule, label, pages, false, &mut None).await;
 
         assert!(result.is_ok());
         assert_eq!(client.prepare_call_count, 1);
@@ -840,6 +986,99 @@ mod tests {
         // but our simple FakeClient doesn't track this. In practice, you might want to enhance it.
     }
 
+    #[tokio::test]
+    async fn test_rule_max_messages_passed_to_client() {
+        let mut client = FakeClient::with_labels(vec!["test-label".to_string()]);
+        let mut rule = create_test_rule(8, true);
+        rule.set_max_messages(Some(250));
+        let mut budget = rule.max_messages();
+
+        let result =
+            process_label_with_rule(&mut client, &rule, "test-label", 0, false, &mut budget).await;
+
+        assert!(result.is_ok());
+        assert_eq!(client.max_messages, Some(250));
+    }
+
+    #[tokio::test]
+    async fn test_rule_max_messages_shared_across_labels() {
+        let mut client = FakeClient::new();
+        client.available_messages = 200;
+        let mut rule = create_test_rule(9, true);
+        rule.add_label("other-label");
+        rule.set_max_messages(Some(250));
+        let mut budget = rule.max_messages();
+
+        process_label_with_rule(&mut client, &rule, "test-label", 0, false, &mut budget)
+            .await
+            .unwrap();
+        assert_eq!(client.message_count(), 200);
+        assert_eq!(budget, Some(50));
+
+        process_label_with_rule(&mut client, &rule, "other-label", 0, false, &mut budget)
+            .await
+            .unwrap();
+        assert_eq!(client.max_messages, Some(50));
+        assert_eq!(
+            client.message_count(),
+            50,
+            "only the rest of the limit is used"
+        );
+        assert_eq!(budget, Some(0));
+
+        let result =
+            process_label_with_rule(&mut client, &rule, "third-label", 0, false, &mut budget).await;
+        assert!(result.is_ok());
+        assert_eq!(
+            client.prepare_call_count, 2,
+            "labels past the limit are skipped"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_untrash_restores_inbox_in_chunks() {
+        let mut client = FakeLabels::new(&[], 2001);
+
+        untrash_messages(&mut client, None).await.unwrap();
+
+        let add = vec![INBOX_LABEL.to_string()];
+        let remove = vec![TRASH_LABEL.to_string()];
+        assert_eq!(
+            client.calls(),
+            vec![
+                (1000, add.clone(), remove.clone()),
+                (1000, add.clone(), remove.clone()),
+                (1, add, remove),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_untrash_restores_to_label() {
+        let mut client = FakeLabels::new(&[("Receipts", "Label_7")], 3);
+
+        untrash_messages(&mut client, Some("Receipts"))
+            .await
+            .unwrap();
+        assert_eq!(
+            client.calls(),
+            vec![(
+                3,
+                vec!["Label_7".to_string()],
+                vec![TRASH_LABEL.to_string()]
+            )]
+        );
+
+        let mut client = FakeLabels::new(&[], 3);
+        let result = untrash_messages(&mut client, Some("Missing")).await;
+        assert!(matches!(result, Err(Error::LabelNotFoundInMailbox(l)) if l == "Missing"));
+        assert!(client.calls().is_empty());
+        assert!(
+            client.created.is_empty(),
+            "restore labels are never created"
+        );
+    }
+
     /// Test the rule processor trait setters and getters
     #[test]
     fn test_rule_processor_setters_and_getters() {
@@ -895,6 +1134,14 @@ mod tests {
                 Ok(())
             }
 
+            async fn batch_untrash(&mut self, _restore_label: Option<&str>) -> Result<()> {
+                Ok(())
+            }
+
+            async fn call_batch_untrash(&self, _ids: &[String], _label_id: &str) -> Result<()> {
+                Ok(())
+            }
+
             async fn process_in_chunks(
                 &self,
                 _message_ids: Vec<String>,
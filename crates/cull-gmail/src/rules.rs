@@ -38,23 +38,36 @@
 //!
 //! # Persistence
 //!
-//! Rules are automatically saved to `~/.cull-gmail/rules.toml` and can be loaded
+//! Rules are saved to `~/.cull-gmail/rules.toml` by default and can be loaded
 //! using [`Rules::load()`]. The configuration uses TOML format for human readability.
+//!
+//! Rules loaded with [`Rules::load_from()`] remember the file they came from,
+//! and are saved back to it. Saving replaces the file atomically, keeps the
+//! previous version as `rules.toml.bak`, and holds a `rules.toml.lock` file
+//! while writing so that concurrent edits do not interleave. If the file was
+//! changed by another process after it was loaded, the save fails with
+//! [`Error::RulesChanged`] rather than overwriting that change.
 
 use std::{
     collections::BTreeMap,
     env, fmt,
     fs::{self, read_to_string},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::Mutex,
 };
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 mod eol_rule;
+mod evaluate;
+mod mail_filters;
+mod persist;
 
 pub use eol_rule::EolRule;
+pub use evaluate::{RuleDecision, RuleEvaluation};
 
-use crate::{EolAction, Error, MessageAge, Result, Retention};
+use crate::{EolAction, Error, MessageAge, Result, Retention, label_manager};
 
 /// A collection of end-of-life rules for Gmail message processing.
 ///
@@ -95,13 +108,23 @@ use crate::{EolAction, Error, MessageAge, Result, Retention};
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Rules {
     rules: BTreeMap<String, EolRule>,
+    /// The file the rules were loaded from, where they are saved
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Hash of the file's contents when last loaded or saved
+    #[serde(skip)]
+    source_hash: Mutex<Option<String>>,
 }
 
 impl Default for Rules {
     fn default() -> Self {
         let rules = BTreeMap::new();
 
-        let mut cfg = Self { rules };
+        let mut cfg = Self {
+            rules,
+            path: None,
+            source_hash: Mutex::new(None),
+        };
 
         cfg.add_rule(Retention::new(MessageAge::Years(1), true), None, false)
             .add_rule(Retention::new(MessageAge::Weeks(1), true), None, false)
@@ -456,6 +479,38 @@ impl Rules {
         Ok(())
     }
 
+    /// Replaces a label in every rule that references it.
+    ///
+    /// Used to keep the rules in step when a label is renamed in Gmail. The
+    /// configuration is not saved, so the caller decides where to write it.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the rules that were changed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use cull_gmail::Rules;
+    ///
+    /// let mut rules = Rules::load().expect("Failed to load rules");
+    /// let changed = rules.rename_label("newsletters", "Newsletters/Weekly");
+    /// if !changed.is_empty() {
+    ///     rules.save().expect("Failed to save rules");
+    /// }
+    /// ```
+    pub fn rename_label(&mut self, label: &str, new_label: &str) -> Vec<usize> {
+        let mut changed = Vec::new();
+        for rule in self.rules.values_mut() {
+            if rule.labels().iter().any(|l| l == label) {
+                rule.remove_label(label);
+                rule.add_label(new_label);
+                changed.push(rule.id());
+            }
+        }
+        changed
+    }
+
     /// Sets the action for an existing rule and saves the configuration.
     ///
     /// Finds the rule with the specified ID and updates its action (trash or delete).
@@ -491,9 +546,174 @@ impl Rules {
         Ok(())
     }
 
+    /// Sets or clears the export directory on a specific rule.
+    ///
+    /// When set, messages are archived as EML files in `dir` before the rule
+    /// permanently deletes them. The configuration is saved after the change.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use cull_gmail::Rules;
+    ///
+    /// let mut rules = Rules::load().expect("Failed to load rules");
+    /// rules.set_export_on_rule(1, Some("/srv/mail-archive"))
+    ///      .expect("Failed to set export directory");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RuleNotFound`] if no rule exists with the specified ID
+    /// * IO errors from saving the configuration file
+    pub fn set_export_on_rule(&mut self, id: usize, dir: Option<&str>) -> Result<()> {
+        let Some(rule) = self.rules.get_mut(id.to_string().as_str()) else {
+            return Err(Error::RuleNotFound(id));
+        };
+        rule.set_export_before_action(dir);
+        self.save()?;
+        match dir {
+            Some(dir) => println!("Export before action set to `{dir}` on rule `#{id}`"),
+            None => println!("Export before action turned off on rule `#{id}`"),
+        }
+
+        Ok(())
+    }
+
+    /// Sets or clears the limit on messages processed per run on a specific rule.
+    ///
+    /// The configuration is saved after the change.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use cull_gmail::Rules;
+    ///
+    /// let mut rules = Rules::load().expect("Failed to load rules");
+    /// rules.set_max_messages_on_rule(1, Some(5000))
+    ///      .expect("Failed to set message limit");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RuleNotFound`] if no rule exists with the specified ID
+    /// * IO errors from saving the configuration file
+    pub fn set_max_messages_on_rule(&mut self, id: usize, value: Option<u32>) -> Result<()> {
+        let Some(rule) = self.rules.get_mut(id.to_string().as_str()) else {
+            return Err(Error::RuleNotFound(id));
+        };
+        rule.set_max_messages(value);
+        self.save()?;
+        match value {
+            Some(value) => println!("Limit of {value} messages set on rule `#{id}`"),
+            None => println!("Message limit removed from rule `#{id}`"),
+        }
+
+        Ok(())
+    }
+
+    /// Protects senders against a specific rule.
+    ///
+    /// Messages from these senders are excluded from the rule's query from
+    /// then on. The configuration is saved after the change.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use cull_gmail::Rules;
+    ///
+    /// let mut rules = Rules::load().expect("Failed to load rules");
+    /// rules.add_protected_senders(1, &["boss@example.com".to_string()])
+    ///      .expect("Failed to protect sender");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RuleNotFound`] if no rule exists with the specified ID
+    /// * IO errors from saving the configuration file
+    pub fn add_protected_senders(&mut self, id: usize, senders: &[String]) -> Result<()> {
+        let Some(rule) = self.rules.get_mut(id.to_string().as_str()) else {
+            return Err(Error::RuleNotFound(id));
+        };
+        for sender in senders {
+            rule.add_protected_sender(sender);
+        }
+        self.save()?;
+        println!("Protected {} sender(s) against rule `#{id}`", senders.len());
+
+        Ok(())
+    }
+
+    /// Sets the criteria of the Gmail filter that labels incoming mail for a rule.
+    ///
+    /// Incoming messages from any of `senders` that match `query` get the
+    /// rule's labels once `rules filters sync` has run. Passing no senders and
+    /// no query removes the criteria. The configuration is saved after the change.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use cull_gmail::Rules;
+    ///
+    /// let mut rules = Rules::load().expect("Failed to load rules");
+    /// rules.set_filter_on_rule(1, &["news@example.com".to_string()], None)
+    ///      .expect("Failed to set filter criteria");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RuleNotFound`] if no rule exists with the specified ID
+    /// * IO errors from saving the configuration file
+    pub fn set_filter_on_rule(
+        &mut self,
+        id: usize,
+        senders: &[String],
+        query: Option<&str>,
+    ) -> Result<()> {
+        let Some(rule) = self.rules.get_mut(id.to_string().as_str()) else {
+            return Err(Error::RuleNotFound(id));
+        };
+        rule.set_filter_criteria(senders, query);
+        self.save()?;
+        if senders.is_empty() && query.is_none() {
+            println!("Filter criteria removed from rule `#{id}`");
+        } else {
+            println!("Filter criteria set on rule `#{id}`");
+        }
+
+        Ok(())
+    }
+
+    /// Records the ID of the Gmail filter managed for a rule.
+    ///
+    /// The configuration is not saved; call [`Rules::save_to`] afterwards.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RuleNotFound`] if no rule exists with the specified ID
+    pub fn set_filter_id(&mut self, id: usize, filter_id: Option<&str>) -> Result<()> {
+        let Some(rule) = self.rules.get_mut(id.to_string().as_str()) else {
+            return Err(Error::RuleNotFound(id));
+        };
+        rule.set_filter_id(filter_id);
+        Ok(())
+    }
+
+    /// Returns a copy of every rule, ordered by ID.
+    pub fn all_rules(&self) -> Vec<EolRule> {
+        let mut rules = self.rules.values().cloned().collect::<Vec<_>>();
+        rules.sort_by_key(EolRule::id);
+        rules
+    }
+
+    /// Returns the file the rules were loaded from, where they are saved.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
     /// Saves the current rule configuration to disk.
     ///
-    /// The configuration is saved as TOML format to `~/.cull-gmail/rules.toml`.
+    /// The configuration is saved as TOML format to the file the rules were
+    /// loaded from (see [`Rules::path`]), or to `~/.cull-gmail/rules.toml`.
     /// The directory is created if it doesn't exist.
     ///
     /// # Examples
@@ -513,14 +733,20 @@ impl Rules {
     /// * TOML serialization errors
     /// * IO errors when writing to the file system
     /// * File system permission errors
+    /// * [`Error::RulesChanged`] if another process changed the file since the
+    ///   rules were loaded
     pub fn save(&self) -> Result<()> {
-        self.save_to(None)
+        self.save_to(self.path())
     }
 
     /// Saves the current rule configuration to a specified path.
     ///
     /// If no path is provided, defaults to `~/.cull-gmail/rules.toml`.
-    /// The directory is created if it doesn't exist.
+    /// The directory is created if it doesn't exist. The file is replaced
+    /// atomically and its previous version kept with a `.bak` extension.
+    ///
+    /// When saving to the file the rules were loaded from, the file must not
+    /// have been changed by another process since it was loaded or last saved.
     ///
     /// # Arguments
     ///
@@ -542,32 +768,57 @@ impl Rules {
     /// * TOML serialization errors
     /// * IO errors when writing to the file system
     /// * File system permission errors
+    /// * [`Error::RulesLocked`] if another process is still saving the rules
+    /// * [`Error::RulesChanged`] if another process changed the file since the
+    ///   rules were loaded
     pub fn save_to(&self, path: Option<&Path>) -> Result<()> {
-        let save_path = if let Some(p) = path {
-            p.to_path_buf()
-        } else {
-            let home_dir = env::home_dir().ok_or_else(|| {
-                Error::HomeExpansionFailed("~/.cull-gmail/rules.toml".to_string())
-            })?;
-            home_dir.join(".cull-gmail/rules.toml")
-        };
+        let save_path = rules_path(path)?;
 
         // Ensure directory exists
         if let Some(parent) = save_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        let res = toml::to_string(self);
-        log::trace!("toml conversion result: {res:#?}");
-
-        if let Ok(output) = res {
-            fs::write(&save_path, output)?;
-            log::trace!("Config saved to {}", save_path.display());
+        let output = toml::to_string(self).map_err(|e| Error::SerializationError(e.to_string()))?;
+
+        let mut source_hash = self
+            .source_hash
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let is_source = self.path.as_deref() == Some(save_path.as_path());
+        let expected = source_hash.as_deref().filter(|_| is_source);
+        persist::write(&save_path, &output, expected)?;
+        if is_source {
+            *source_hash = Some(sha256_hex(output.as_bytes()));
         }
+        log::trace!("Config saved to {}", save_path.display());
 
         Ok(())
     }
 
+    /// Returns a SHA-256 hash of the rules, as hex.
+    ///
+    /// The hash is taken over the TOML the rules are saved as, so it changes
+    /// whenever a rule is added, removed or edited, but not when the rules
+    /// file is only reformatted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cull_gmail::{MessageAge, Retention, Rules};
+    ///
+    /// let mut rules = Rules::new();
+    /// let before = rules.fingerprint();
+    /// assert_eq!(before, Rules::new().fingerprint());
+    ///
+    /// rules.add_rule(Retention::new(MessageAge::Days(30), false), Some("spam"), false);
+    /// assert_ne!(rules.fingerprint(), before);
+    /// ```
+    pub fn fingerprint(&self) -> String {
+        let toml = toml::to_string(self).unwrap_or_default();
+        sha256_hex(toml.as_bytes())
+    }
+
     /// Loads rule configuration from disk.
     ///
     /// Reads the configuration from `~/.cull-gmail/rules.toml` and deserializes
@@ -598,7 +849,8 @@ impl Rules {
 
     /// Loads rule configuration from a specified path.
     ///
-    /// If no path is provided, defaults to `~/.cull-gmail/rules.toml`.
+    /// If no path is provided, defaults to `~/.cull-gmail/rules.toml`. The
+    /// rules remember the path, and [`Rules::save`] writes back to it.
     ///
     /// # Arguments
     ///
@@ -620,19 +872,14 @@ impl Rules {
     /// * TOML parsing errors if the file is malformed
     /// * File not found errors if the configuration doesn't exist
     pub fn load_from(path: Option<&Path>) -> Result<Rules> {
-        let load_path = if let Some(p) = path {
-            p.to_path_buf()
-        } else {
-            let home_dir = env::home_dir().ok_or_else(|| {
-                Error::HomeExpansionFailed("~/.cull-gmail/rules.toml".to_string())
-            })?;
-            home_dir.join(".cull-gmail/rules.toml")
-        };
+        let load_path = rules_path(path)?;
 
         log::trace!("Loading config from {}", load_path.display());
 
-        let input = read_to_string(load_path)?;
-        let config = toml::from_str::<Rules>(&input)?;
+        let input = read_to_string(&load_path)?;
+        let mut config = toml::from_str::<Rules>(&input)?;
+        config.path = Some(load_path);
+        config.source_hash = Mutex::new(Some(sha256_hex(input.as_bytes())));
         Ok(config)
     }
 
@@ -729,8 +976,60 @@ impl Rules {
 
         issues
     }
+
+    /// Checks the rules' labels against the labels in a mailbox.
+    ///
+    /// `label_map` maps the mailbox's label names to their IDs, as returned by
+    /// [`GmailClient::label_map`](crate::GmailClient::label_map). Reports:
+    ///
+    /// - labels that do not exist, and wildcard labels that match none
+    /// - labels that are Gmail system labels such as `SENT`, `DRAFT` or
+    ///   `STARRED`, as a warning
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use cull_gmail::{Rules, ValidationIssue};
+    ///
+    /// let rules = Rules::new();
+    /// let issues = rules.validate_labels(&BTreeMap::new());
+    /// assert!(matches!(issues[0], ValidationIssue::LabelNotFound { .. }));
+    /// ```
+    pub fn validate_labels(&self, label_map: &BTreeMap<String, String>) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        for rule in self.all_rules() {
+            for label in rule.labels() {
+                let expanded = label_manager::expand_label_pattern(label_map, &label);
+                if !expanded.iter().any(|name| label_map.contains_key(name)) {
+                    issues.push(ValidationIssue::LabelNotFound {
+                        rule_id: rule.id(),
+                        label,
+                    });
+                    continue;
+                }
+                if let Some(system) = expanded.iter().find(|name| {
+                    SENSITIVE_SYSTEM_LABELS
+                        .iter()
+                        .any(|system| name.eq_ignore_ascii_case(system))
+                }) {
+                    issues.push(ValidationIssue::SystemLabel {
+                        rule_id: rule.id(),
+                        label: system.clone(),
+                    });
+                }
+            }
+        }
+
+        issues
+    }
 }
 
+/// Gmail system labels that a retention rule is unlikely to be meant for.
+const SENSITIVE_SYSTEM_LABELS: [&str; 5] = ["SENT", "DRAFT", "STARRED", "IMPORTANT", "CHAT"];
+
 /// An issue found during rules validation.
 #[derive(Debug, PartialEq)]
 pub enum ValidationIssue {
@@ -758,6 +1057,49 @@ pub enum ValidationIssue {
         /// The duplicated label.
         label: String,
     },
+    /// A rule's label does not exist in the mailbox.
+    LabelNotFound {
+        /// The ID of the offending rule.
+        rule_id: usize,
+        /// The missing label or unmatched wildcard label.
+        label: String,
+    },
+    /// A rule acts on a Gmail system label such as `SENT` or `STARRED`.
+    SystemLabel {
+        /// The ID of the offending rule.
+        rule_id: usize,
+        /// The system label.
+        label: String,
+    },
+    /// A rule currently matches no messages.
+    NoMatches {
+        /// The ID of the offending rule.
+        rule_id: usize,
+    },
+    /// A rule matches a large share of the mailbox.
+    LargeShare {
+        /// The ID of the offending rule.
+        rule_id: usize,
+        /// The number of messages the rule matches.
+        count: usize,
+        /// The number of messages in the mailbox.
+        total: usize,
+    },
+}
+
+impl ValidationIssue {
+    /// Returns `true` for issues that may be intended and do not fail validation.
+    ///
+    /// Rules on system labels, rules matching no messages and rules matching
+    /// a large share of the mailbox are warnings.
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            ValidationIssue::SystemLabel { .. }
+                | ValidationIssue::NoMatches { .. }
+                | ValidationIssue::LargeShare { .. }
+        )
+    }
 }
 
 impl fmt::Display for ValidationIssue {
@@ -775,10 +1117,51 @@ impl fmt::Display for ValidationIssue {
             ValidationIssue::DuplicateLabel { label } => {
                 write!(f, "Label '{label}' is used in multiple rules")
             }
+            ValidationIssue::LabelNotFound { rule_id, label } => {
+                write!(
+                    f,
+                    "Rule #{rule_id}: label '{label}' not found in the mailbox"
+                )
+            }
+            ValidationIssue::SystemLabel { rule_id, label } => {
+                write!(f, "Rule #{rule_id}: acts on the system label '{label}'")
+            }
+            ValidationIssue::NoMatches { rule_id } => {
+                write!(f, "Rule #{rule_id}: matches no messages")
+            }
+            ValidationIssue::LargeShare {
+                rule_id,
+                count,
+                total,
+            } => {
+                let percent = *count as f64 * 100.0 / (*total).max(1) as f64;
+                write!(
+                    f,
+                    "Rule #{rule_id}: matches {count} of {total} messages ({percent:.0}%)"
+                )
+            }
         }
     }
 }
 
+/// Returns a SHA-256 hash of `bytes`, as hex.
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Returns the given rules path, or `~/.cull-gmail/rules.toml`.
+fn rules_path(path: Option<&Path>) -> Result<PathBuf> {
+    if let Some(p) = path {
+        return Ok(p.to_path_buf());
+    }
+    let home_dir = env::home_dir()
+        .ok_or_else(|| Error::HomeExpansionFailed("~/.cull-gmail/rules.toml".to_string()))?;
+    Ok(home_dir.join(".cull-gmail/rules.toml"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1078,6 +1461,119 @@ mod tests {
         assert_eq!(rule.action(), Some(EolAction::Delete));
     }
 
+    #[test]
+    fn test_set_export_on_rule() {
+        setup_test_environment();
+
+        let mut rules = Rules::new();
+
+        rules.set_export_on_rule(1, Some("/srv/archive")).unwrap();
+        assert_eq!(
+            rules.get_rule(1).unwrap().export_before_action(),
+            Some("/srv/archive")
+        );
+
+        rules.set_export_on_rule(1, None).unwrap();
+        assert_eq!(rules.get_rule(1).unwrap().export_before_action(), None);
+
+        assert!(matches!(
+            rules.set_export_on_rule(999, None),
+            Err(Error::RuleNotFound(999))
+        ));
+    }
+
+    #[test]
+    fn test_set_max_messages_on_rule() {
+        setup_test_environment();
+
+        let mut rules = Rules::new();
+
+        rules.set_max_messages_on_rule(1, Some(5000)).unwrap();
+        assert_eq!(rules.get_rule(1).unwrap().max_messages(), Some(5000));
+
+        rules.set_max_messages_on_rule(1, None).unwrap();
+        assert_eq!(rules.get_rule(1).unwrap().max_messages(), None);
+
+        assert!(matches!(
+            rules.set_max_messages_on_rule(999, None),
+            Err(Error::RuleNotFound(999))
+        ));
+    }
+
+    #[test]
+    fn test_rename_label() {
+        setup_test_environment();
+
+        let mut rules = Rules::new();
+        rules.add_label_to_rule(1, "old-name").unwrap();
+        rules.add_label_to_rule(2, "old-name").unwrap();
+
+        let mut changed = rules.rename_label("old-name", "Vendors/new-name");
+        changed.sort();
+
+        assert_eq!(changed, vec![1, 2]);
+        for id in [1, 2] {
+            let labels = rules.get_rule(id).unwrap().labels();
+            assert!(labels.contains(&"Vendors/new-name".to_string()));
+            assert!(!labels.contains(&"old-name".to_string()));
+        }
+        assert!(rules.rename_label("missing", "other").is_empty());
+    }
+
+    #[test]
+    fn test_add_protected_senders() {
+        setup_test_environment();
+
+        let mut rules = Rules::new();
+
+        rules
+            .add_protected_senders(1, &["boss@example.com".to_string()])
+            .unwrap();
+        assert_eq!(
+            rules.get_rule(1).unwrap().protected_senders(),
+            vec!["boss@example.com"]
+        );
+
+        assert!(matches!(
+            rules.add_protected_senders(999, &[]),
+            Err(Error::RuleNotFound(999))
+        ));
+    }
+
+    #[test]
+    fn test_set_filter_on_rule() {
+        setup_test_environment();
+
+        let mut rules = Rules::new();
+
+        rules
+            .set_filter_on_rule(1, &["news@example.com".to_string()], Some("-is:starred"))
+            .unwrap();
+        rules.set_filter_id(1, Some("ANe1Bmj")).unwrap();
+        let rule = rules.get_rule(1).unwrap();
+        assert_eq!(rule.senders(), vec!["news@example.com"]);
+        assert_eq!(rule.query(), Some("-is:starred"));
+        assert_eq!(rule.filter_id(), Some("ANe1Bmj"));
+
+        let ids = rules
+            .all_rules()
+            .iter()
+            .map(EolRule::id)
+            .collect::<Vec<_>>();
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+
+        assert!(matches!(
+            rules.set_filter_on_rule(999, &[], None),
+            Err(Error::RuleNotFound(999))
+        ));
+        assert!(matches!(
+            rules.set_filter_id(999, None),
+            Err(Error::RuleNotFound(999))
+        ));
+    }
+
     #[test]
     fn test_set_action_on_rule_nonexistent() {
         setup_test_environment();
@@ -1287,6 +1783,100 @@ action = "bad"
         );
     }
 
+    #[test]
+    fn test_validate_labels_against_mailbox() {
+        setup_test_environment();
+        let toml_str = r#"
+[rules."1"]
+id = 1
+retention = "y:1"
+labels = ["news", "missing", "Vendors/*", "Clients/*"]
+action = "Trash"
+
+[rules."2"]
+id = 2
+retention = "y:5"
+labels = ["SENT"]
+action = "Delete"
+"#;
+        let rules: Rules = toml::from_str(toml_str).unwrap();
+        let label_map = BTreeMap::from(
+            [
+                ("news", "Label_1"),
+                ("Vendors/Acme", "Label_2"),
+                ("SENT", "SENT"),
+            ]
+            .map(|(name, id)| (name.to_string(), id.to_string())),
+        );
+
+        let issues = rules.validate_labels(&label_map);
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::LabelNotFound {
+                    rule_id: 1,
+                    label: "Clients/*".to_string()
+                },
+                ValidationIssue::LabelNotFound {
+                    rule_id: 1,
+                    label: "missing".to_string()
+                },
+                ValidationIssue::SystemLabel {
+                    rule_id: 2,
+                    label: "SENT".to_string()
+                },
+            ]
+        );
+        assert!(!issues[0].is_warning());
+        assert!(issues[2].is_warning());
+        assert_eq!(
+            ValidationIssue::LargeShare {
+                rule_id: 1,
+                count: 300,
+                total: 1000
+            }
+            .to_string(),
+            "Rule #1: matches 300 of 1000 messages (30%)"
+        );
+    }
+
+    #[test]
+    fn test_edits_saved_to_loaded_path() {
+        setup_test_environment();
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("custom-rules.toml");
+        Rules::new().save_to(Some(&path)).unwrap();
+
+        let mut rules = Rules::load_from(Some(&path)).unwrap();
+        assert_eq!(rules.path(), Some(path.as_path()));
+        rules.add_label_to_rule(1, "saved-here").unwrap();
+
+        let reloaded = Rules::load_from(Some(&path)).unwrap();
+        assert!(reloaded.labels().contains(&"saved-here".to_string()));
+        let backup = fs::read_to_string(dir.path().join("custom-rules.toml.bak")).unwrap();
+        assert!(!backup.contains("saved-here"));
+    }
+
+    #[test]
+    fn test_save_fails_if_changed_since_load() {
+        setup_test_environment();
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rules.toml");
+        Rules::new().save_to(Some(&path)).unwrap();
+
+        let mut first = Rules::load_from(Some(&path)).unwrap();
+        let mut second = Rules::load_from(Some(&path)).unwrap();
+        first.add_label_to_rule(1, "first").unwrap();
+        // Saving again after its own save is not a conflict
+        first.add_label_to_rule(2, "first-again").unwrap();
+
+        let result = second.add_label_to_rule(1, "second");
+        assert!(matches!(result, Err(Error::RulesChanged(_))));
+        let saved = Rules::load_from(Some(&path)).unwrap().labels();
+        assert!(saved.contains(&"first-again".to_string()));
+        assert!(!saved.contains(&"second".to_string()));
+    }
+
     // Integration tests for save/load would require file system setup
     // These are marked as ignore to avoid interference with actual config files
     #[test]
@@ -27,7 +27,9 @@ use std::{collections::BTreeSet, fmt};
 use chrono::{DateTime, Datelike, Local, TimeDelta, TimeZone};
 use serde::{Deserialize, Serialize};
 
-use crate::{MessageAge, Retention, eol_action::EolAction};
+use crate::{
+    MessageAge, Retention, RuleFilter, eol_action::EolAction, label_manager::is_label_pattern,
+};
 
 /// A rule that defines end-of-life processing for Gmail messages.
 ///
@@ -57,6 +59,16 @@ pub struct EolRule {
     labels: BTreeSet<String>,
     query: Option<String>,
     action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    export_before_action: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_messages: Option<u32>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    protected_senders: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    senders: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filter_id: Option<String>,
 }
 
 impl fmt::Display for EolRule {
@@ -290,6 +302,128 @@ impl EolRule {
         EolAction::parse(&self.action)
     }
 
+    /// Sets the directory messages are exported to before they are deleted.
+    ///
+    /// Passing `None` turns the export off.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// # use cull_gmail::rules::eol_rule::EolRule;
+    /// let mut rule = EolRule::new(1);
+    /// rule.set_export_before_action(Some("/srv/mail-archive"));
+    ///
+    /// assert_eq!(rule.export_before_action(), Some("/srv/mail-archive"));
+    /// ```
+    pub(crate) fn set_export_before_action(&mut self, dir: Option<&str>) -> &mut Self {
+        self.export_before_action = dir.map(str::to_string);
+        self
+    }
+
+    /// Returns the directory messages are exported to before deletion, if any.
+    ///
+    /// When set, each chunk of messages is archived as EML files in this
+    /// directory before it is permanently deleted, and the chunk is skipped if
+    /// the export fails.
+    pub fn export_before_action(&self) -> Option<&str> {
+        self.export_before_action.as_deref()
+    }
+
+    /// Sets the maximum number of messages the rule processes in one run.
+    ///
+    /// Passing `None` removes the limit.
+    pub(crate) fn set_max_messages(&mut self, value: Option<u32>) -> &mut Self {
+        self.max_messages = value;
+        self
+    }
+
+    /// Returns the maximum number of messages processed in one run, if any.
+    ///
+    /// Paging stops as soon as this many messages have been collected, so a
+    /// large backlog is worked through over several runs.
+    pub fn max_messages(&self) -> Option<u32> {
+        self.max_messages
+    }
+
+    /// Protects messages from a sender against this rule.
+    ///
+    /// Messages from protected senders are left out of the rule's query, so
+    /// they are never trashed or deleted by it.
+    pub(crate) fn add_protected_sender(&mut self, sender: &str) -> &mut Self {
+        self.protected_senders.insert(sender.to_lowercase());
+        self
+    }
+
+    /// Returns the sender addresses this rule never acts on.
+    pub fn protected_senders(&self) -> Vec<String> {
+        self.protected_senders.iter().cloned().collect()
+    }
+
+    /// Sets the criteria of the Gmail filter that labels incoming mail for this rule.
+    ///
+    /// Incoming messages from any of `senders` that also match `query` get the
+    /// rule's labels. Passing no senders and no query removes the criteria.
+    pub(crate) fn set_filter_criteria(
+        &mut self,
+        senders: &[String],
+        query: Option<&str>,
+    ) -> &mut Self {
+        self.senders = senders.iter().map(|s| s.to_lowercase()).collect();
+        self.query = query.map(str::to_string);
+        self
+    }
+
+    /// Returns the senders whose incoming mail is labelled for this rule.
+    pub fn senders(&self) -> Vec<String> {
+        self.senders.iter().cloned().collect()
+    }
+
+    /// Returns the search query incoming mail must match to be labelled for this rule.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// Records the ID of the Gmail filter created for this rule.
+    pub(crate) fn set_filter_id(&mut self, id: Option<&str>) -> &mut Self {
+        self.filter_id = id.map(str::to_string);
+        self
+    }
+
+    /// Returns the ID of the Gmail filter created for this rule, if any.
+    pub fn filter_id(&self) -> Option<&str> {
+        self.filter_id.as_deref()
+    }
+
+    /// Returns the Gmail filter that applies this rule's labels to incoming mail.
+    ///
+    /// Wildcard label patterns cannot be applied by a filter and are left out.
+    /// Returns `None` when the rule has no sender or query criteria, or no
+    /// other labels.
+    pub fn filter(&self) -> Option<RuleFilter> {
+        let labels = self
+            .labels
+            .iter()
+            .filter(|label| !is_label_pattern(label))
+            .cloned()
+            .collect::<Vec<_>>();
+        if (self.senders.is_empty() && self.query.is_none()) || labels.is_empty() {
+            return None;
+        }
+
+        let from = (!self.senders.is_empty()).then(|| {
+            self.senders
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" OR ")
+        });
+        Some(RuleFilter {
+            from,
+            query: self.query.clone(),
+            labels,
+        })
+    }
+
     /// Returns the raw action string as stored in the rule.
     pub(crate) fn action_str(&self) -> &str {
         &self.action
@@ -369,12 +503,45 @@ impl EolRule {
     ///     // Output will be something like "before: 2024-08-15"
     /// }
     /// ```
+    ///
+    /// Protected senders are excluded with a `-from:` term each.
     pub(crate) fn eol_query(&self) -> Option<String> {
         let today = chrono::Local::now();
-        self.calculate_for_date(today)
+        let query = self.calculate_for_date(today)?;
+        Some(self.exclude_protected_senders(query))
+    }
+
+    /// Appends a `-from:` term for each protected sender to `query`.
+    fn exclude_protected_senders(&self, query: String) -> String {
+        self.protected_senders
+            .iter()
+            .fold(query, |query, sender| format!("{query} -from:{sender}"))
     }
 
     fn calculate_for_date(&self, today: DateTime<Local>) -> Option<String> {
+        let deadline = self.cutoff_for_date(today)?;
+        Some(format!("before: {}", deadline.format("%Y-%m-%d")))
+    }
+
+    /// Returns the date before which messages are old enough for the rule to act.
+    ///
+    /// The cutoff is `today` less the rule's retention period. Returns `None`
+    /// if the retention period is not set or cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{Local, TimeZone};
+    /// use cull_gmail::Rules;
+    ///
+    /// let rules = Rules::new();
+    /// let rule = rules.get_rule(1).unwrap();
+    /// let today = Local.with_ymd_and_hms(2025, 9, 15, 0, 0, 0).unwrap();
+    ///
+    /// let cutoff = rule.cutoff_for_date(today).unwrap();
+    /// assert_eq!(cutoff.format("%Y-%m-%d").to_string(), "2024-09-15");
+    /// ```
+    pub fn cutoff_for_date(&self, today: DateTime<Local>) -> Option<DateTime<Local>> {
         let message_age = MessageAge::parse(&self.retention)?;
         log::debug!("testing for {message_age}");
 
@@ -421,7 +588,7 @@ impl EolRule {
             }
         };
 
-        Some(format!("before: {}", deadline.format("%Y-%m-%d")))
+        Some(deadline)
     }
 }
 
@@ -557,4 +724,79 @@ mod test {
 
         assert_eq!("before: 2007-09-10", query);
     }
+
+    #[test]
+    fn test_export_before_action_round_trips_through_toml() {
+        let mut rule = build_test_rule(crate::MessageAge::Years(1));
+        let toml = toml::to_string(&rule).unwrap();
+        assert!(!toml.contains("export_before_action"));
+
+        rule.set_export_before_action(Some("/srv/archive"));
+        let toml = toml::to_string(&rule).unwrap();
+        let loaded: EolRule = toml::from_str(&toml).unwrap();
+        assert_eq!(loaded.export_before_action(), Some("/srv/archive"));
+    }
+
+    #[test]
+    fn test_max_messages_round_trips_through_toml() {
+        let mut rule = build_test_rule(crate::MessageAge::Years(1));
+        let toml = toml::to_string(&rule).unwrap();
+        assert!(!toml.contains("max_messages"));
+
+        rule.set_max_messages(Some(5000));
+        let toml = toml::to_string(&rule).unwrap();
+        let loaded: EolRule = toml::from_str(&toml).unwrap();
+        assert_eq!(loaded.max_messages(), Some(5000));
+    }
+
+    #[test]
+    fn test_protected_senders_excluded_from_query() {
+        let mut rule = build_test_rule(crate::MessageAge::Years(1));
+        let toml = toml::to_string(&rule).unwrap();
+        assert!(!toml.contains("protected_senders"));
+
+        rule.add_protected_sender("Boss@example.com");
+        rule.add_protected_sender("hr@example.com");
+        let query = rule.eol_query().unwrap();
+        assert!(query.starts_with("before: "));
+        assert!(query.ends_with(" -from:boss@example.com -from:hr@example.com"));
+
+        let toml = toml::to_string(&rule).unwrap();
+        let loaded: EolRule = toml::from_str(&toml).unwrap();
+        assert_eq!(
+            loaded.protected_senders(),
+            vec!["boss@example.com", "hr@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_filter_from_senders_and_query() {
+        let mut rule = build_test_rule(crate::MessageAge::Years(1));
+        assert!(rule.filter().is_none());
+
+        rule.set_filter_criteria(
+            &["News@example.com".to_string(), "deals@shop.com".to_string()],
+            Some("subject:weekly"),
+        );
+        let filter = rule.filter().unwrap();
+        assert_eq!(
+            filter.from.as_deref(),
+            Some("deals@shop.com OR news@example.com")
+        );
+        assert_eq!(filter.query.as_deref(), Some("subject:weekly"));
+        assert_eq!(filter.labels, rule.labels());
+
+        // Wildcard labels cannot be applied by a filter
+        rule.add_label("Vendors/*");
+        assert_eq!(rule.filter().unwrap().labels, filter.labels);
+
+        rule.set_filter_id(Some("ANe1Bmj"));
+        let toml = toml::to_string(&rule).unwrap();
+        let loaded: EolRule = toml::from_str(&toml).unwrap();
+        assert_eq!(loaded.filter(), Some(filter));
+        assert_eq!(loaded.filter_id(), Some("ANe1Bmj"));
+
+        rule.set_filter_criteria(&[], None);
+        assert!(rule.filter().is_none());
+    }
 }
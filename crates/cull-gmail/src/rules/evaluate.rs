@@ -0,0 +1,292 @@
+//! Local evaluation of rules against message metadata.
+//!
+//! Rules normally select messages through Gmail search: the rule's labels are
+//! resolved to label IDs and its retention period becomes a `before:` query.
+//! The functions here reach the same decision locally from a message's
+//! labels, sender and received date, without contacting Gmail. This allows
+//! rules to be tested offline, decisions to be explained, and differences
+//! between Gmail's date handling and the intended retention to be caught.
+//!
+//! A message is due under a rule when:
+//!
+//! 1. it carries one of the rule's labels, with wildcard labels expanded
+//!    against the label map;
+//! 2. its sender is not one of the rule's protected senders; and
+//! 3. it was received before the start of the cutoff day, in local time,
+//!    which is the date in the rule's `before:` query.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+
+use super::{EolRule, Rules};
+use crate::{MessageSummary, label_manager};
+
+/// Why a rule does or does not apply to a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleDecision {
+    /// The message has none of the rule's labels.
+    NoLabel,
+    /// The rule's retention period cannot be parsed.
+    InvalidRetention,
+    /// The message is from one of the rule's protected senders.
+    ProtectedSender(String),
+    /// The message's received date is unknown.
+    NoDate,
+    /// The message was received on or after the cutoff.
+    NotDue,
+    /// The message was received before the cutoff.
+    Due,
+}
+
+/// The result of evaluating a rule against a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleEvaluation {
+    /// ID of the evaluated rule
+    pub rule_id: usize,
+    /// The message label the rule matched, if any
+    pub label: Option<String>,
+    /// Messages received before this time are due, if the retention is valid
+    pub cutoff: Option<DateTime<Local>>,
+    /// Whether the rule applies, and why
+    pub decision: RuleDecision,
+}
+
+impl RuleEvaluation {
+    /// Returns `true` if the rule's action applies to the message.
+    pub fn applies(&self) -> bool {
+        self.decision == RuleDecision::Due
+    }
+}
+
+impl EolRule {
+    /// Decides whether the rule applies to a message as of `today`.
+    ///
+    /// `label_map` maps label names to the IDs found in
+    /// [`MessageSummary::label_ids`]; a label missing from the map is compared
+    /// by name, so messages with label names instead of IDs can be evaluated
+    /// without a mailbox. Wildcard labels are expanded against the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use chrono::{Local, TimeZone};
+    /// use cull_gmail::{MessageSummary, RuleDecision, Rules};
+    ///
+    /// let rules = Rules::new();
+    /// let rule = rules.get_rule(1).unwrap(); // one year on `retention/1-years`
+    /// let message = MessageSummary::new("m1")
+    ///     .with_labels(&["retention/1-years"])
+    ///     .with_internal_date(Local.with_ymd_and_hms(2023, 1, 2, 9, 0, 0).unwrap());
+    ///
+    /// let today = Local.with_ymd_and_hms(2025, 9, 15, 12, 0, 0).unwrap();
+    /// let evaluation = rule.evaluate(&message, &BTreeMap::new(), today);
+    /// assert_eq!(evaluation.decision, RuleDecision::Due);
+    /// assert_eq!(evaluation.label.as_deref(), Some("retention/1-years"));
+    /// ```
+    pub fn evaluate(
+        &self,
+        message: &MessageSummary,
+        label_map: &BTreeMap<String, String>,
+        today: DateTime<Local>,
+    ) -> RuleEvaluation {
+        self.evaluate_with(message, label_map, today, &[])
+    }
+
+    /// Evaluates the rule, skipping wildcard matches on the `owned` labels.
+    fn evaluate_with(
+        &self,
+        message: &MessageSummary,
+        label_map: &BTreeMap<String, String>,
+        today: DateTime<Local>,
+        owned: &[String],
+    ) -> RuleEvaluation {
+        let label = self.matching_label(message, label_map, owned);
+        let cutoff = self.cutoff_for_date(today).and_then(start_of_day);
+        let evaluation = |decision| RuleEvaluation {
+            rule_id: self.id(),
+            label: label.clone(),
+            cutoff,
+            decision,
+        };
+
+        if label.is_none() {
+            return evaluation(RuleDecision::NoLabel);
+        }
+        let Some(cutoff) = cutoff else {
+            return evaluation(RuleDecision::InvalidRetention);
+        };
+        if let Some(sender) = message.sender_address()
+            && self.protected_senders().contains(&sender)
+        {
+            return evaluation(RuleDecision::ProtectedSender(sender));
+        }
+
+        match message.received() {
+            Some(received) if received < cutoff => evaluation(RuleDecision::Due),
+            Some(_) => evaluation(RuleDecision::NotDue),
+            None => evaluation(RuleDecision::NoDate),
+        }
+    }
+
+    /// Finds the first label of the rule, after expansion, that the message has.
+    fn matching_label(
+        &self,
+        message: &MessageSummary,
+        label_map: &BTreeMap<String, String>,
+        owned: &[String],
+    ) -> Option<String> {
+        self.labels().into_iter().find_map(|label| {
+            let wildcard = label_manager::is_label_pattern(&label);
+            label_manager::expand_label_pattern(label_map, &label)
+                .into_iter()
+                .filter(|name| !(wildcard && owned.contains(name)))
+                .find(|name| {
+                    let id = label_map.get(name).unwrap_or(name);
+                    message.label_ids().contains(id)
+                })
+        })
+    }
+}
+
+impl Rules {
+    /// Evaluates every rule against a message as of `today`, in rule ID order.
+    ///
+    /// As when the rules are run, a label matched by a wildcard that has a
+    /// rule of its own is left to that rule. See [`EolRule::evaluate`].
+    pub fn evaluate(
+        &self,
+        message: &MessageSummary,
+        label_map: &BTreeMap<String, String>,
+        today: DateTime<Local>,
+    ) -> Vec<RuleEvaluation> {
+        let owned = self.labels();
+        self.all_rules()
+            .iter()
+            .map(|rule| rule.evaluate_with(message, label_map, today, &owned))
+            .collect()
+    }
+}
+
+/// Returns midnight at the start of the day of `time`, in local time.
+fn start_of_day(time: DateTime<Local>) -> Option<DateTime<Local>> {
+    Local
+        .from_local_datetime(&time.date_naive().and_time(NaiveTime::MIN))
+        .earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MessageAge, Retention};
+
+    fn message(labels: &[&str], received: (i32, u32, u32)) -> MessageSummary {
+        let (year, month, day) = received;
+        MessageSummary::new("m1")
+            .with_labels(labels)
+            .with_internal_date(Local.with_ymd_and_hms(year, month, day, 9, 0, 0).unwrap())
+    }
+
+    fn today() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 9, 15, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_cutoff_is_start_of_day() {
+        let mut rules = Rules::new();
+        rules.add_rule(
+            Retention::new(MessageAge::Days(30), false),
+            Some("spam"),
+            false,
+        );
+        let rule = rules.all_rules().last().cloned().unwrap();
+        let labels = BTreeMap::new();
+
+        // 30 days before 15 Sep 12:00 is 16 Aug 12:00; `before:` starts the day
+        let evaluation = rule.evaluate(&message(&["spam"], (2025, 8, 16)), &labels, today());
+        assert_eq!(evaluation.decision, RuleDecision::NotDue);
+        assert_eq!(
+            evaluation.cutoff,
+            Some(Local.with_ymd_and_hms(2025, 8, 16, 0, 0, 0).unwrap())
+        );
+
+        let evaluation = rule.evaluate(&message(&["spam"], (2025, 8, 15)), &labels, today());
+        assert!(evaluation.applies());
+
+        let evaluation = rule.evaluate(&message(&["INBOX"], (2020, 1, 1)), &labels, today());
+        assert_eq!(evaluation.decision, RuleDecision::NoLabel);
+
+        let undated = MessageSummary::new("m2").with_labels(&["spam"]);
+        let evaluation = rule.evaluate(&undated, &labels, today());
+        assert_eq!(evaluation.decision, RuleDecision::NoDate);
+    }
+
+    #[test]
+    fn test_evaluate_label_ids_and_protected_senders() {
+        let mut rules = Rules::new();
+        rules.add_rule(
+            Retention::new(MessageAge::Years(1), false),
+            Some("news"),
+            false,
+        );
+        let id = rules.all_rules().last().unwrap().id();
+        rules
+            .add_protected_senders(id, &["boss@example.com".to_string()])
+            .unwrap();
+        let rule = rules.get_rule(id).unwrap();
+        let labels = BTreeMap::from([("news".to_string(), "Label_7".to_string())]);
+
+        let by_id = message(&["Label_7"], (2020, 1, 1));
+        assert!(rule.evaluate(&by_id, &labels, today()).applies());
+
+        let by_name = message(&["news"], (2020, 1, 1));
+        assert_eq!(
+            rule.evaluate(&by_name, &labels, today()).decision,
+            RuleDecision::NoLabel
+        );
+
+        let protected = by_id.with_from("The Boss <Boss@Example.com>");
+        assert_eq!(
+            rule.evaluate(&protected, &labels, today()).decision,
+            RuleDecision::ProtectedSender("boss@example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rules_evaluate_wildcard_precedence() {
+        let mut rules = Rules::new();
+        rules.add_rule(
+            Retention::new(MessageAge::Years(1), false),
+            Some("Vendors/*"),
+            false,
+        );
+        rules.add_rule(
+            Retention::new(MessageAge::Years(9), false),
+            Some("Vendors/Acme"),
+            false,
+        );
+        let labels = BTreeMap::from([
+            ("Vendors/Acme".to_string(), "Label_1".to_string()),
+            ("Vendors/Globex".to_string(), "Label_2".to_string()),
+        ]);
+
+        let evaluations = rules.evaluate(&message(&["Label_1"], (2020, 1, 1)), &labels, today());
+        let matched = evaluations
+            .iter()
+            .filter(|e| e.label.is_some())
+            .map(|e| (e.rule_id, e.applies()))
+            .collect::<Vec<_>>();
+        // Only the Acme rule's nine years apply, not the wildcard's one year
+        assert_eq!(matched, vec![(6, false)]);
+
+        let evaluations = rules.evaluate(&message(&["Label_2"], (2020, 1, 1)), &labels, today());
+        let matched = evaluations
+            .iter()
+            .filter(|e| e.applies())
+            .map(|e| e.label.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(matched, vec![Some("Vendors/Globex")]);
+    }
+}
@@ -0,0 +1,303 @@
+//! Conversion between rules and Gmail's `mailFilters.xml` format.
+//!
+//! Gmail's settings exports and imports filters as an Atom feed in which each
+//! `entry` is a filter and each `apps:property` a criterion or action:
+//!
+//! ```xml
+//! <entry>
+//!     <category term='filter'></category>
+//!     <title>Mail Filter</title>
+//!     <apps:property name='from' value='news@example.com'/>
+//!     <apps:property name='label' value='newsletters'/>
+//! </entry>
+//! ```
+//!
+//! Rules with sender or query criteria are written as one filter per label.
+//! Reading works the other way: each filter that applies a label becomes a
+//! rule for that label, keeping the filter's criteria.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::{Reader, XmlVersion, escape::escape, events::Event};
+
+use super::Rules;
+use crate::{Error, MessageAge, Result, Retention};
+
+/// Separator Gmail uses between alternative senders in the `from` criterion.
+const SENDER_SEPARATOR: &str = " OR ";
+
+impl Rules {
+    /// Writes the rules with sender or query criteria as Gmail filters XML.
+    ///
+    /// The result can be imported in Gmail under *Settings > Filters and
+    /// Blocked Addresses > Import filters*. Rules without criteria have no
+    /// filter and are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cull_gmail::Rules;
+    ///
+    /// let xml = Rules::new().to_mail_filters_xml();
+    /// assert!(xml.contains("<title>Mail Filters</title>"));
+    /// ```
+    pub fn to_mail_filters_xml(&self) -> String {
+        self.mail_filters_xml(Utc::now())
+    }
+
+    fn mail_filters_xml(&self, now: DateTime<Utc>) -> String {
+        let updated = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut xml = format!(
+            "<?xml version='1.0' encoding='UTF-8'?>\n\
+             <feed xmlns='http://www.w3.org/2005/Atom' xmlns:apps='http://schemas.google.com/apps/2006'>\n\
+             \t<title>Mail Filters</title>\n\
+             \t<id>tag:mail.google.com,2008:filters:cull-gmail</id>\n\
+             \t<updated>{updated}</updated>\n"
+        );
+
+        for rule in self.all_rules() {
+            let Some(filter) = rule.filter() else {
+                continue;
+            };
+            for (n, label) in filter.labels.iter().enumerate() {
+                xml.push_str(&format!(
+                    "\t<entry>\n\
+                     \t\t<category term='filter'></category>\n\
+                     \t\t<title>Mail Filter</title>\n\
+                     \t\t<id>tag:mail.google.com,2008:filter:cull-gmail-{}-{n}</id>\n\
+                     \t\t<updated>{updated}</updated>\n\
+                     \t\t<content></content>\n",
+                    rule.id()
+                ));
+                let properties = [
+                    ("from", filter.from.as_deref()),
+                    ("hasTheWord", filter.query.as_deref()),
+                    ("label", Some(label.as_str())),
+                ];
+                for (name, value) in properties {
+                    if let Some(value) = value {
+                        xml.push_str(&format!(
+                            "\t\t<apps:property name='{name}' value='{}'/>\n",
+                            escape(value)
+                        ));
+                    }
+                }
+                xml.push_str("\t</entry>\n");
+            }
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    /// Adds a rule for each filter in Gmail filters XML that applies a label.
+    ///
+    /// Each new rule targets the filter's label, keeps its criteria as the
+    /// rule's senders and query, and uses the given retention period and
+    /// action. Filters for labels that already have a rule are skipped with
+    /// a warning. The configuration is not saved.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the rules that were added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cull_gmail::{MessageAge, Rules};
+    ///
+    /// let xml = "<feed><entry>\
+    ///     <apps:property name='from' value='news@example.com'/>\
+    ///     <apps:property name='label' value='newsletters'/>\
+    ///     </entry></feed>";
+    ///
+    /// let mut rules = Rules::new();
+    /// let ids = rules.import_mail_filters(xml, MessageAge::Years(1), false).unwrap();
+    /// let rule = rules.get_rule(ids[0]).unwrap();
+    /// assert_eq!(rule.labels(), vec!["newsletters"]);
+    /// assert_eq!(rule.senders(), vec!["news@example.com"]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::MailFiltersInvalid`] if the XML cannot be parsed
+    pub fn import_mail_filters(
+        &mut self,
+        xml: &str,
+        age: MessageAge,
+        delete: bool,
+    ) -> Result<Vec<usize>> {
+        let mut imported = Vec::new();
+
+        for properties in read_filters(xml)? {
+            let Some(label) = properties.get("label") else {
+                log::debug!("skipping filter without a label: {properties:?}");
+                continue;
+            };
+            if self.labels().contains(label) {
+                log::warn!("a rule already applies to label `{label}`, filter skipped");
+                continue;
+            }
+
+            self.add_rule(Retention::new(age.clone(), false), Some(label), delete);
+            let Some(id) = self.all_rules().last().map(|rule| rule.id()) else {
+                continue;
+            };
+
+            let senders = properties
+                .get("from")
+                .map(|from| {
+                    from.split(SENDER_SEPARATOR)
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let query = query_from(&properties);
+            if let Some(rule) = self.rules.get_mut(&id.to_string()) {
+                rule.set_filter_criteria(&senders, query.as_deref());
+            }
+            imported.push(id);
+        }
+
+        Ok(imported)
+    }
+}
+
+/// Reads the properties of each filter entry in Gmail filters XML.
+fn read_filters(xml: &str) -> Result<Vec<BTreeMap<String, String>>> {
+    let invalid = |e: &dyn std::fmt::Display| Error::MailFiltersInvalid(e.to_string());
+
+    let mut reader = Reader::from_str(xml);
+    let mut filters = Vec::new();
+    let mut entry: Option<BTreeMap<String, String>> = None;
+
+    loop {
+        match reader.read_event().map_err(|e| invalid(&e))? {
+            Event::Start(e) if e.local_name().as_ref() == "entry" => {
+                entry = Some(BTreeMap::new());
+            }
+            Event::End(e) if e.local_name().as_ref() == "entry" => {
+                filters.extend(entry.take());
+            }
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == "property" => {
+                let Some(properties) = entry.as_mut() else {
+                    continue;
+                };
+                let attribute = |key: &str| -> Result<Option<String>> {
+                    let Some(attr) = e.try_get_attribute(key).map_err(|e| invalid(&e))? else {
+                        return Ok(None);
+                    };
+                    let value = attr
+                        .normalized_value(XmlVersion::default())
+                        .map_err(|e| invalid(&e))?;
+                    Ok(Some(value.into_owned()))
+                };
+                if let (Some(name), Some(value)) = (attribute("name")?, attribute("value")?) {
+                    properties.insert(name, value);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(filters)
+}
+
+/// Combines the word, recipient and subject criteria of a filter into a query.
+fn query_from(properties: &BTreeMap<String, String>) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(to) = properties.get("to") {
+        parts.push(format!("to:({to})"));
+    }
+    if let Some(subject) = properties.get("subject") {
+        parts.push(format!("subject:({subject})"));
+    }
+    if let Some(words) = properties.get("hasTheWord") {
+        parts.push(words.clone());
+    }
+    if let Some(words) = properties.get("doesNotHaveTheWord") {
+        parts.push(format!("-({words})"));
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const GMAIL_EXPORT: &str = "<?xml version='1.0' encoding='UTF-8'?>\
+        <feed xmlns='http://www.w3.org/2005/Atom' xmlns:apps='http://schemas.google.com/apps/2006'>\
+        <title>Mail Filters</title>\
+        <entry>\
+            <category term='filter'></category>\
+            <title>Mail Filter</title>\
+            <apps:property name='from' value='deals@shop.com OR news@example.com'/>\
+            <apps:property name='subject' value='Weekly &amp; more'/>\
+            <apps:property name='label' value='Newsletters'/>\
+            <apps:property name='sizeOperator' value='s_sl'/>\
+        </entry>\
+        <entry>\
+            <category term='filter'></category>\
+            <apps:property name='from' value='spam@example.com'/>\
+            <apps:property name='shouldTrash' value='true'/>\
+        </entry>\
+        </feed>";
+
+    #[test]
+    fn test_import_mail_filters() {
+        let mut rules = Rules::new();
+        let ids = rules
+            .import_mail_filters(GMAIL_EXPORT, MessageAge::Months(6), false)
+            .unwrap();
+        assert_eq!(ids.len(), 1);
+
+        let rule = rules.get_rule(ids[0]).unwrap();
+        assert_eq!(rule.labels(), vec!["Newsletters"]);
+        assert_eq!(rule.retention(), "m:6");
+        assert_eq!(rule.senders(), vec!["deals@shop.com", "news@example.com"]);
+        assert_eq!(rule.query(), Some("subject:(Weekly & more)"));
+
+        // A second import does not duplicate the rule
+        let ids = rules
+            .import_mail_filters(GMAIL_EXPORT, MessageAge::Months(6), false)
+            .unwrap();
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    fn test_mail_filters_round_trip() {
+        let mut rules = Rules::new();
+        rules
+            .import_mail_filters(GMAIL_EXPORT, MessageAge::Years(1), true)
+            .unwrap();
+
+        let now = Utc.with_ymd_and_hms(2025, 9, 15, 8, 0, 0).unwrap();
+        let xml = rules.mail_filters_xml(now);
+        assert!(xml.contains("<updated>2025-09-15T08:00:00Z</updated>"));
+        assert!(
+            xml.contains("<apps:property name='hasTheWord' value='subject:(Weekly &amp; more)'/>")
+        );
+        assert_eq!(xml.matches("<entry>").count(), 1);
+
+        let mut copy = Rules::new();
+        let ids = copy
+            .import_mail_filters(&xml, MessageAge::Years(1), true)
+            .unwrap();
+        let rule = copy.get_rule(ids[0]).unwrap();
+        assert_eq!(rule.filter(), rules.get_rule(ids[0]).unwrap().filter());
+    }
+
+    #[test]
+    fn test_import_invalid_xml() {
+        let mut rules = Rules::new();
+        assert!(matches!(
+            rules.import_mail_filters("<feed><entry></feed>", MessageAge::Years(1), false),
+            Err(Error::MailFiltersInvalid(_))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+//! Safe writing of the rules file.
+//!
+//! The rules file is never written in place. The new rules are written to a
+//! temporary file beside it, which is then renamed over the original, so an
+//! interrupted save cannot leave a truncated file behind. The previous version
+//! is kept as `<file>.bak`.
+//!
+//! Writers take an advisory lock by creating `<file>.lock`, so that two
+//! processes editing the same rules do not interleave their writes. A writer
+//! finding the lock waits for it to be released, up to [`LOCK_TIMEOUT`].
+//!
+//! The lock only covers the write. To stop a process overwriting rules that
+//! another changed after it loaded them, the writer passes the hash of the
+//! file as it was loaded, and the write fails if the file no longer matches.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process, thread,
+    time::{Duration, Instant},
+};
+
+use super::sha256_hex;
+use crate::{Error, Result};
+
+/// How long a writer waits for another to release the lock.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a waiting writer checks the lock.
+const LOCK_POLL: Duration = Duration::from_millis(50);
+
+/// Returns `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// An advisory lock on a rules file, released when dropped.
+struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    /// Creates the lock file for `target`, waiting while another writer holds it.
+    fn acquire(target: &Path) -> Result<Lock> {
+        let path = sibling(target, ".lock");
+        let start = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    // The process ID helps to identify a stale lock
+                    let _ = writeln!(file, "{}", process::id());
+                    return Ok(Lock { path });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if start.elapsed() >= LOCK_TIMEOUT {
+                        return Err(Error::RulesLocked(path.display().to_string()));
+                    }
+                    thread::sleep(LOCK_POLL);
+                }
+                Err(e) => return Err(Error::RulesSaveFailed(target.display().to_string(), e)),
+            }
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!("Could not remove lock file {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Replaces the contents of `path` with `contents`, keeping a backup.
+///
+/// If `expected` is given, it is the hash of the file's contents when the
+/// rules were loaded; the write fails with [`Error::RulesChanged`] if the file
+/// has changed since.
+pub(super) fn write(path: &Path, contents: &str, expected: Option<&str>) -> Result<()> {
+    let _lock = Lock::acquire(path)?;
+    let failed = |e| Error::RulesSaveFailed(path.display().to_string(), e);
+
+    if let Some(expected) = expected {
+        match fs::read(path) {
+            Ok(current) if sha256_hex(&current) != expected => {
+                return Err(Error::RulesChanged(path.display().to_string()));
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(failed(e)),
+        }
+    }
+
+    let temp = sibling(path, ".tmp");
+    if let Err(e) = write_synced(&temp, contents) {
+        let _ = fs::remove_file(&temp);
+        return Err(failed(e));
+    }
+
+    if path.exists() {
+        // Keep the file's permissions, which the rename would otherwise replace
+        if let Ok(metadata) = fs::metadata(path) {
+            let _ = fs::set_permissions(&temp, metadata.permissions());
+        }
+        fs::copy(path, sibling(path, ".bak")).map_err(failed)?;
+    }
+    fs::rename(&temp, path).map_err(failed)?;
+
+    log::trace!("Rules written to {}", path.display());
+    Ok(())
+}
+
+/// Writes a file and waits for its contents to reach the disk.
+fn write_synced(path: &Path, contents: &str) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_keeps_backup() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rules.toml");
+
+        write(&path, "first", None).unwrap();
+        assert!(!sibling(&path, ".bak").exists());
+
+        write(&path, "second", Some(&sha256_hex(b"first"))).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_to_string(sibling(&path, ".bak")).unwrap(), "first");
+        assert!(!sibling(&path, ".tmp").exists());
+        assert!(!sibling(&path, ".lock").exists());
+    }
+
+    #[test]
+    fn test_write_waits_for_lock() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rules.toml");
+        let lock = Lock::acquire(&path).unwrap();
+
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || write(&path, "rules", None))
+        };
+        thread::sleep(LOCK_POLL * 2);
+        assert!(!path.exists(), "the writer waits for the lock");
+
+        drop(lock);
+        writer.join().unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "rules");
+    }
+
+    #[test]
+    fn test_write_fails_if_changed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rules.toml");
+        fs::write(&path, "changed elsewhere").unwrap();
+
+        let result = write(&path, "mine", Some(&sha256_hex(b"as loaded")));
+        assert!(matches!(result, Err(Error::RulesChanged(_))));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "changed elsewhere");
+        assert!(!sibling(&path, ".lock").exists());
+    }
+}
@@ -1,8 +1,76 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
 use log::LevelFilter;
 
+use crate::{Result, label_manager::LabelOperations};
+
 pub(crate) fn get_test_logger() {
     let mut builder = env_logger::Builder::new();
     builder.filter(None, LevelFilter::Debug);
     builder.format_timestamp_secs().format_module_path(false);
     let _ = builder.try_init();
 }
+
+/// A batch modify call: the number of messages, added and removed label IDs.
+pub(crate) type BatchCall = (usize, Vec<String>, Vec<String>);
+
+/// Fake client recording label creation and batch modify calls.
+#[derive(Default)]
+pub(crate) struct FakeLabels {
+    pub(crate) label_map: BTreeMap<String, String>,
+    pub(crate) message_ids: Vec<String>,
+    pub(crate) created: Vec<String>,
+    calls: Mutex<Vec<BatchCall>>,
+}
+
+impl FakeLabels {
+    pub(crate) fn new(labels: &[(&str, &str)], messages: usize) -> Self {
+        Self {
+            label_map: labels
+                .iter()
+                .map(|(name, id)| (name.to_string(), id.to_string()))
+                .collect(),
+            message_ids: (0..messages).map(|i| format!("m{i}")).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn calls(&self) -> Vec<BatchCall> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl LabelOperations for FakeLabels {
+    fn label_id_for(&self, name: &str) -> Option<String> {
+        self.label_map.get(name).cloned()
+    }
+
+    async fn create_label_id(&mut self, name: &str) -> Result<String> {
+        let id = format!("Label_new_{}", self.created.len());
+        self.created.push(name.to_string());
+        self.label_map.insert(name.to_string(), id.clone());
+        Ok(id)
+    }
+
+    fn prepared_message_ids(&self) -> Vec<String> {
+        self.message_ids.clone()
+    }
+
+    async fn log_prepared(&mut self, _pre: &str, _post: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn batch_modify(
+        &self,
+        ids: &[String],
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> Result<()> {
+        self.calls.lock().unwrap().push((
+            ids.len(),
+            add_label_ids.to_vec(),
+            remove_label_ids.to_vec(),
+        ));
+        Ok(())
+    }
+}
@@ -242,6 +242,28 @@ mod labels_tests {
         assert!(stdout.contains("labels") || stdout.contains("List Gmail labels"));
     }
 
+    #[test]
+    fn test_labels_set_color_rejects_invalid_colour() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+
+        let output = fixture
+            .execute_cli(
+                &[
+                    "labels",
+                    "set-color",
+                    "receipts",
+                    "-b",
+                    "green",
+                    "-t",
+                    "#ffffff",
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("invalid colour `green`"));
+    }
+
     #[test]
     fn test_labels_without_credentials() {
         let fixture = CliTestFixture::new().expect("Failed to create test fixture");
@@ -358,6 +380,35 @@ mod messages_tests {
         );
     }
 
+    #[test]
+    fn test_messages_label_requires_a_change() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+
+        let output = fixture
+            .execute_cli(&["messages", "label"], None)
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--add <ADD>"));
+    }
+
+    #[test]
+    fn test_messages_older_than_validation() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+
+        // Ages must be a count with a unit Gmail understands
+        for age in ["6h", "6é", "m", "999999999w"] {
+            let output = fixture
+                .execute_cli(&["messages", "--older-than", age, "list"], None)
+                .expect("Failed to execute CLI");
+            assert_eq!(output.status.code(), Some(2), "`{age}` should be rejected");
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(
+                stderr.contains(&format!("invalid age `{age}`")),
+                "unexpected error for `{age}`: {stderr}"
+            );
+        }
+    }
+
     #[test]
     fn test_messages_pagination_options() {
         let fixture = CliTestFixture::new().expect("Failed to create test fixture");
@@ -386,6 +437,80 @@ mod messages_tests {
         );
     }
 
+    #[test]
+    fn test_messages_limit_must_be_positive() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+
+        let output = fixture
+            .execute_cli(&["messages", "--limit", "0", "list"], None)
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("invalid value '0' for '--limit <N>'")
+        );
+    }
+
+    #[test]
+    fn test_messages_ids_from() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let ids_file = fixture.temp_dir.path().join("ids.txt");
+        std::fs::write(&ids_file, "18c1f2a3b4c5d6e7\n").expect("Failed to write IDs");
+        let ids_file = ids_file.to_str().unwrap();
+
+        // The list is read before asking for authorisation
+        let bad_ids_file = fixture.temp_dir.path().join("bad-ids.txt");
+        std::fs::write(&bad_ids_file, "18c1f2a3b4c5d6e7\nnot-an-id\n")
+            .expect("Failed to write IDs");
+        let output = fixture
+            .execute_cli(
+                &[
+                    "messages",
+                    "--ids-from",
+                    bad_ids_file.to_str().unwrap(),
+                    "trash",
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("line 2: `not-an-id` is not a message ID"),
+            "{stderr}"
+        );
+        assert!(!stderr.contains("credentials"), "{stderr}");
+
+        // A valid list gets as far as authorisation
+        let output = fixture
+            .execute_cli(&["messages", "--ids-from", ids_file, "trash"], None)
+            .expect("Failed to execute CLI");
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("OAuth2 credentials file"));
+
+        // IDs replace the search, so filters cannot be combined with them
+        let output = fixture
+            .execute_cli(
+                &[
+                    "messages",
+                    "--ids-from",
+                    ids_file,
+                    "--older-than",
+                    "1y",
+                    "trash",
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+
+        let output = fixture
+            .execute_cli(&["messages", "--skip-unknown", "trash"], None)
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--ids-from"));
+    }
+
     #[test]
     fn test_messages_invalid_action() {
         let fixture = CliTestFixture::new().expect("Failed to create test fixture");
@@ -551,6 +676,43 @@ mod rules_tests {
         );
     }
 
+    #[test]
+    fn test_rules_apply_requires_plan() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+
+        let output = fixture
+            .execute_cli(&["rules", "apply"], None)
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("<FILE>"));
+
+        // Changes are only made by applying a plan
+        let output = fixture
+            .execute_cli(&["rules", "run", "--execute"], None)
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("unexpected argument '--execute'")
+        );
+    }
+
+    #[test]
+    fn test_execute_setting_fails() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let config_dir = fixture.temp_dir.path().join(".cull-gmail");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(config_dir.join("cull-gmail.toml"), "execute = true\n").unwrap();
+
+        // Scheduled jobs that relied on `execute` must not silently only preview
+        let output = fixture
+            .execute_cli(&[], None)
+            .expect("Failed to execute CLI");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("The `execute` setting is no longer supported"));
+        assert!(stderr.contains("rules apply"));
+    }
+
     #[test]
     fn test_rules_config_validation() {
         let fixture = CliTestFixture::new().expect("Failed to create test fixture");
@@ -918,6 +1080,211 @@ action = "Trash"
 "#
     }
 
+    #[test]
+    fn test_set_filter_on_rule() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let rules_file = fixture.temp_dir.path().join("rules.toml");
+        fs::write(&rules_file, valid_rules_toml()).unwrap();
+        let rules = rules_file.to_str().unwrap();
+
+        let output = fixture
+            .execute_cli(
+                &[
+                    "rules",
+                    rules,
+                    "config",
+                    "set-filter-on-rule",
+                    "--id",
+                    "1",
+                    "--from",
+                    "news@example.com",
+                    "--query",
+                    "-is:starred",
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert!(output.status.success());
+        let rules_toml = fs::read_to_string(&rules_file).unwrap();
+        assert!(rules_toml.contains(r#"senders = ["news@example.com"]"#));
+        assert!(rules_toml.contains(r#"query = "-is:starred""#));
+
+        let output = fixture
+            .execute_cli(
+                &[
+                    "rules",
+                    rules,
+                    "config",
+                    "set-filter-on-rule",
+                    "--id",
+                    "1",
+                    "--off",
+                    "--from",
+                    "a@b.c",
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+    }
+
+    #[test]
+    fn test_rules_export_and_import() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let rules_file = fixture.temp_dir.path().join("rules.toml");
+        fs::write(&rules_file, valid_rules_toml()).unwrap();
+        let rules = rules_file.to_str().unwrap();
+        let filters_file = fixture.temp_dir.path().join("mailFilters.xml");
+        fs::write(
+            &filters_file,
+            "<feed><entry>\
+                <apps:property name='from' value='news@example.com'/>\
+                <apps:property name='label' value='newsletters'/>\
+            </entry></feed>",
+        )
+        .unwrap();
+        let filters_file = filters_file.to_str().unwrap();
+        let export_file = fixture.temp_dir.path().join("exported.toml");
+
+        let output = fixture
+            .execute_cli(&["rules", rules, "export"], None)
+            .expect("Failed to execute CLI");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("[rules.1]") && stdout.contains("test-label"));
+
+        let output = fixture
+            .execute_cli(
+                &["rules", rules, "export", "--format", "gmail-filters"],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("<feed"));
+
+        let output = fixture
+            .execute_cli(
+                &[
+                    "rules",
+                    rules,
+                    "export",
+                    "--output",
+                    export_file.to_str().unwrap(),
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty());
+        assert!(
+            fs::read_to_string(&export_file)
+                .unwrap()
+                .contains("test-label")
+        );
+
+        let output = fixture
+            .execute_cli(
+                &["rules", rules, "import", filters_file, "--retention", "m:6"],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert!(output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stdout).contains("Added rule #2 for `newsletters`")
+        );
+        let rules_toml = fs::read_to_string(&rules_file).unwrap();
+        assert!(rules_toml.contains("newsletters") && rules_toml.contains("m:6"));
+
+        let output = fixture
+            .execute_cli(&["rules", rules, "import", filters_file, "--delete"], None)
+            .expect("Failed to execute CLI");
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("No new rules found"));
+
+        for args in [
+            &["rules", rules, "export", "--format", "xml"][..],
+            &[
+                "rules",
+                rules,
+                "import",
+                filters_file,
+                "--retention",
+                "6 months",
+            ][..],
+        ] {
+            let output = fixture
+                .execute_cli(args, None)
+                .expect("Failed to execute CLI");
+            assert_eq!(output.status.code(), Some(2), "{args:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_rules_explain_with_fixtures() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let rules_file = fixture.temp_dir.path().join("rules.toml");
+        fs::write(&rules_file, valid_rules_toml()).unwrap();
+        let fixtures_file = fixture.temp_dir.path().join("fixtures.toml");
+        fs::write(
+            &fixtures_file,
+            "now = \"2025-09-15\"\n[[messages]]\nlabels = [\"test-label\"]\nexpect = \"keep\"\n",
+        )
+        .unwrap();
+        let rules = rules_file.to_str().unwrap();
+        let fixtures = fixtures_file.to_str().unwrap();
+
+        let output = fixture
+            .execute_cli(
+                &[
+                    "rules",
+                    rules,
+                    "explain",
+                    "--id",
+                    "1",
+                    "--fixtures",
+                    fixtures,
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout
+                .contains("Rule #1, to move the message to trash if it is more than 30 days old.")
+        );
+        assert!(stdout.contains("  test-label"));
+
+        let output = fixture
+            .execute_cli(
+                &[
+                    "rules",
+                    rules,
+                    "explain",
+                    "--id",
+                    "9",
+                    "--fixtures",
+                    fixtures,
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("No rule for id 9"));
+    }
+
+    #[test]
+    fn test_explain_requires_message_id() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+
+        let output = fixture
+            .execute_cli(&["explain"], None)
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2), "a message ID is required");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("<MESSAGE_ID>"));
+    }
+
     #[test]
     fn test_rules_validate_valid_file_exits_zero() {
         let fixture = CliTestFixture::new().expect("Failed to create test fixture");
@@ -983,6 +1350,118 @@ action = "Trash"
         );
     }
 
+    #[test]
+    fn test_rules_validate_max_share_rejections() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let rules_file = fixture.temp_dir.path().join("rules.toml");
+        fs::write(&rules_file, valid_rules_toml()).unwrap();
+        let rules = rules_file.to_str().unwrap();
+
+        // The share limit only applies to the online check
+        let output = fixture
+            .execute_cli(&["rules", rules, "validate", "--max-share", "10"], None)
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--online"));
+
+        let output = fixture
+            .execute_cli(
+                &["rules", rules, "validate", "--online", "--max-share", "0"],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("0 is not in 1..=100"));
+    }
+
+    #[test]
+    fn test_local_commands_run_without_credentials() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let rules_file = fixture.temp_dir.path().join("rules.toml");
+        fs::write(&rules_file, valid_rules_toml()).unwrap();
+        let fixtures_file = fixture.temp_dir.path().join("fixtures.toml");
+        fs::write(
+            &fixtures_file,
+            "now = \"2025-09-15\"\n[[messages]]\nlabels = [\"test-label\"]\nexpect = \"keep\"\n",
+        )
+        .unwrap();
+        let rules = rules_file.to_str().unwrap();
+        let fixtures = fixtures_file.to_str().unwrap();
+
+        // No credential file or token exists in the fixture's home directory
+        for args in [
+            &["rules", rules, "config", "list-rules"][..],
+            &[
+                "rules",
+                rules,
+                "config",
+                "add-label",
+                "--id",
+                "1",
+                "--label",
+                "extra",
+            ][..],
+            &["rules", rules, "export"][..],
+            &["rules", rules, "explain", "--fixtures", fixtures][..],
+        ] {
+            let output = fixture
+                .execute_cli(args, None)
+                .expect("Failed to execute CLI");
+            assert!(
+                output.status.success(),
+                "{args:?} should not need Gmail: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        let output = fixture
+            .execute_cli(&["rules", rules, "explain", "--fixtures", fixtures], None)
+            .expect("Failed to execute CLI");
+        assert!(String::from_utf8_lossy(&output.stdout).contains("test-label"));
+    }
+
+    #[test]
+    fn test_rule_edits_saved_to_rules_path() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let rules_file = fixture.temp_dir.path().join("custom-rules.toml");
+        fs::write(&rules_file, valid_rules_toml()).unwrap();
+        let rules = rules_file.to_str().unwrap();
+
+        let output = fixture
+            .execute_cli(
+                &[
+                    "rules",
+                    rules,
+                    "config",
+                    "add-label",
+                    "--id",
+                    "1",
+                    "--label",
+                    "extra",
+                ],
+                None,
+            )
+            .expect("Failed to execute CLI");
+        assert!(output.status.success());
+
+        let rules_toml = fs::read_to_string(&rules_file).unwrap();
+        assert!(
+            rules_toml.contains("extra"),
+            "the given rules file is edited"
+        );
+        let backup = fs::read_to_string(fixture.temp_dir.path().join("custom-rules.toml.bak"))
+            .expect("the previous rules are kept");
+        assert!(!backup.contains("extra"));
+        assert!(
+            !fixture
+                .temp_dir
+                .path()
+                .join(".cull-gmail/rules.toml")
+                .exists(),
+            "the default rules file is not written"
+        );
+    }
+
     #[test]
     fn test_rules_validate_missing_file_exits_nonzero() {
         let fixture = CliTestFixture::new().expect("Failed to create test fixture");
@@ -997,4 +1476,63 @@ action = "Trash"
             "Expected non-zero exit for missing rules file"
         );
     }
+
+    #[test]
+    fn test_rules_test_fixtures() {
+        let fixture = CliTestFixture::new().expect("Failed to create test fixture");
+        let rules_file = fixture.temp_dir.path().join("rules.toml");
+        fs::write(&rules_file, valid_rules_toml()).unwrap();
+        let fixtures_file = fixture.temp_dir.path().join("fixtures.toml");
+        let rules = rules_file.to_str().unwrap();
+        let fixtures = fixtures_file.to_str().unwrap();
+
+        fs::write(
+            &fixtures_file,
+            r#"
+now = "2025-09-15"
+
+[[messages]]
+name = "old test mail is trashed"
+labels = ["test-label"]
+received = "2025-01-01"
+expect = "trash"
+
+[[messages]]
+name = "recent test mail is kept"
+labels = ["test-label"]
+received = "2025-09-01"
+expect = "keep"
+"#,
+        )
+        .unwrap();
+        let output = fixture
+            .execute_cli(&["rules", rules, "test", fixtures], None)
+            .expect("Failed to execute CLI");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "expected all fixtures to pass: {stdout}"
+        );
+        assert!(stdout.contains("2 passed, 0 failed"));
+
+        fs::write(
+            &fixtures_file,
+            r#"
+now = "2025-09-15"
+
+[[messages]]
+name = "test mail must be kept"
+labels = ["test-label"]
+received = "2025-01-01"
+expect = "keep"
+"#,
+        )
+        .unwrap();
+        let output = fixture
+            .execute_cli(&["rules", rules, "test", fixtures], None)
+            .expect("Failed to execute CLI");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!output.status.success(), "expected a mismatch to fail");
+        assert!(stdout.contains("FAIL  test mail must be kept: expected keep, got trash"));
+    }
 }
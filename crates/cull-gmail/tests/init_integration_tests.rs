@@ -95,7 +95,7 @@ fn verify_standard_init_files(config_dir: &std::path::Path) {
 fn verify_standard_config_content(config_dir: &std::path::Path) {
     let config_content = std::fs::read_to_string(config_dir.join("cull-gmail.toml")).unwrap();
     assert!(config_content.contains("credential_file = \"credential.json\""));
-    assert!(config_content.contains("execute = false"));
+    assert!(!config_content.contains("execute"));
 }
 
 /// Helper to run init command with basic options.